@@ -1,15 +1,22 @@
-///! Shitty, non-optimized (hopefully correct) AES implementation
-///!
-///! DO NOT USE THIS LIBRARY FOR REAL SHIT, OR THE WORST THINGS WILL HAPPEN
-///!
-///! - Sia will read your private messages
-///! - Phoebe will compromise your eBay purchase
-///! - Ensa will listen to your encrypted phone call
-///!
-///! Seriously though, I have done my best to follow the [FIPS-197](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.197.pdf) specification,
-///! but the library has not been tested for side-channel resistance, or other cryptanalytic attacks.
-///!
-///! I coded this AES implementation to learn more about how AES actually works.
+//! Shitty, non-optimized (hopefully correct) AES implementation
+//!
+//! DO NOT USE THIS LIBRARY FOR REAL SHIT, OR THE WORST THINGS WILL HAPPEN
+//!
+//! - Sia will read your private messages
+//! - Phoebe will compromise your eBay purchase
+//! - Ensa will listen to your encrypted phone call
+//!
+//! Seriously though, I have done my best to follow the [FIPS-197](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.197.pdf) specification,
+//! but the library has not been tested for side-channel resistance, or other cryptanalytic attacks.
+//!
+//! I coded this AES implementation to learn more about how AES actually works.
+
+use crate::BlockCipher;
+
+#[cfg(target_arch = "x86_64")]
+use crate::aesni;
+#[cfg(feature = "ttable")]
+use crate::ttable;
 
 /// Number of bits in AES-128
 pub const N_128: usize = 128;
@@ -20,6 +27,12 @@ pub const N_BLOCK: usize = 4;
 /// Number of 32-bit words per key block in AES-128
 pub const N_KEY_128: usize = 4;
 
+/// Number of 32-bit words per key block in AES-192
+pub const N_KEY_192: usize = 6;
+
+/// Number of 32-bit words per key block in AES-256
+pub const N_KEY_256: usize = 8;
+
 /// Number of rounds in AES-128
 pub const N_ROUND_128: usize = 10;
 
@@ -32,217 +45,546 @@ pub const N_ROUND_256: usize = 14;
 /// Number of 32-bit words per expanded key in AES-128
 pub const N_EXPW_128: usize = 44; /* Nb * (Nr + 1) */
 
+/// Number of 32-bit words per expanded key in AES-192
+pub const N_EXPW_192: usize = 52; /* Nb * (Nr + 1) */
+
+/// Number of 32-bit words per expanded key in AES-256
+pub const N_EXPW_256: usize = 60; /* Nb * (Nr + 1) */
+
 /// Block length (in bytes) for AES-{128,192,256}
 pub const BLOCK_LEN: usize = 16;
 
 /// Key length (in bytes) for AES-128
 pub const KEY_LEN_128: usize = 16;
 
-/// AES-128 cipher over a single block
+/// Key length (in bytes) for AES-192
+pub const KEY_LEN_192: usize = 24;
+
+/// Key length (in bytes) for AES-256
+pub const KEY_LEN_256: usize = 32;
+
+/// AES-128 block cipher with a pre-expanded key schedule
 ///
-/// @param msg: Message block to be encrypted
-/// @param key: Key for encrypting the message
-pub fn aes_128(msg: &[u8; BLOCK_LEN], key: &[u8; KEY_LEN_128]) -> [u8; BLOCK_LEN] {
-    aes_inner_128(msg, expand_key_128(key))
+/// The key is expanded once on creation, and reused for every block
+///
+/// Uses AES-NI on x86_64 when supported, falling back to the portable implementation otherwise
+#[derive(Clone)]
+pub struct Aes128 {
+    w: [u32; N_EXPW_128],
+    dw: [u32; N_EXPW_128],
+    #[cfg(target_arch = "x86_64")]
+    ni: Option<aesni::Aes128>,
 }
 
-fn aes_inner_128(msg: &[u8; BLOCK_LEN], w: [u32; N_EXPW_128]) -> [u8; BLOCK_LEN] {
-    let mut state: [u32; 4] = [
-        u32::from_be_bytes([msg[0], msg[1], msg[2], msg[3]]),
-        u32::from_be_bytes([msg[4], msg[5], msg[6], msg[7]]),
-        u32::from_be_bytes([msg[8], msg[9], msg[10], msg[11]]),
-        u32::from_be_bytes([msg[12], msg[13], msg[14], msg[15]]),
-    ];
+impl Aes128 {
+    /// Create an AES-128 cipher from the given key
+    pub fn new(key: &[u8; KEY_LEN_128]) -> Self {
+        #[allow(unused_mut)]
+        let mut aes = Self::new_portable(key);
+        #[cfg(target_arch = "x86_64")]
+        {
+            aes.ni = aesni::Aes128::new(key);
+        }
+        aes
+    }
 
-    add_round_key(&mut state, &w[..N_BLOCK]);
+    /// Create an AES-128 cipher that never uses hardware instructions
+    pub fn new_portable(key: &[u8; KEY_LEN_128]) -> Self {
+        let w = expand_key_128(key);
+        let mut dw = [0_u32; N_EXPW_128];
+        expand_dec_key(&w, &mut dw);
+        Self {
+            w,
+            dw,
+            #[cfg(target_arch = "x86_64")]
+            ni: None,
+        }
+    }
+}
 
-    for i in 1..N_ROUND_128 {
-        sub_bytes(&mut state);
-        shift_rows(&mut state);
-        mix_columns(&mut state);
-        add_round_key(&mut state, &w[i*N_BLOCK..(i + 1) * N_BLOCK]);
+impl BlockCipher for Aes128 {
+    fn encrypt_block(&self, msg: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if let Some(ni) = self.ni.as_ref() {
+                return ni.encrypt_block(msg);
+            }
+        }
+        cipher(msg, &self.w, N_ROUND_128)
     }
 
-    sub_bytes(&mut state);
-    shift_rows(&mut state);
-    add_round_key(&mut state, &w[N_ROUND_128 * N_BLOCK..(N_ROUND_128 + 1) * N_BLOCK]);
+    fn decrypt_block(&self, cipher: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if let Some(ni) = self.ni.as_ref() {
+                return ni.decrypt_block(cipher);
+            }
+        }
+        inv_cipher(cipher, &self.dw, N_ROUND_128)
+    }
 
-    let mut out = [0u8; BLOCK_LEN];
+    fn encrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if let Some(ni) = self.ni.as_ref() {
+                return ni.encrypt_blocks(blocks);
+            }
+        }
+        for block in blocks.iter_mut() {
+            *block = cipher(block, &self.w, N_ROUND_128);
+        }
+    }
 
-    for (i, word) in state.iter().enumerate() {
-        for (si, &byte) in word.to_be_bytes().iter().enumerate() {
-            out[i*N_BLOCK + si] = byte;
+    fn decrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if let Some(ni) = self.ni.as_ref() {
+                return ni.decrypt_blocks(blocks);
+            }
+        }
+        for block in blocks.iter_mut() {
+            *block = inv_cipher(block, &self.dw, N_ROUND_128);
         }
     }
+}
 
-    out
+/// AES-192 block cipher with a pre-expanded key schedule
+///
+/// The key is expanded once on creation, and reused for every block
+///
+/// Uses AES-NI on x86_64 when supported, falling back to the portable implementation otherwise
+#[derive(Clone)]
+pub struct Aes192 {
+    w: [u32; N_EXPW_192],
+    dw: [u32; N_EXPW_192],
+    #[cfg(target_arch = "x86_64")]
+    ni: Option<aesni::Aes192>,
+}
+
+impl Aes192 {
+    /// Create an AES-192 cipher from the given key
+    pub fn new(key: &[u8; KEY_LEN_192]) -> Self {
+        #[allow(unused_mut)]
+        let mut aes = Self::new_portable(key);
+        #[cfg(target_arch = "x86_64")]
+        {
+            aes.ni = aesni::Aes192::new(key);
+        }
+        aes
+    }
+
+    /// Create an AES-192 cipher that never uses hardware instructions
+    pub fn new_portable(key: &[u8; KEY_LEN_192]) -> Self {
+        let w = expand_key_192(key);
+        let mut dw = [0_u32; N_EXPW_192];
+        expand_dec_key(&w, &mut dw);
+        Self {
+            w,
+            dw,
+            #[cfg(target_arch = "x86_64")]
+            ni: None,
+        }
+    }
+}
+
+impl BlockCipher for Aes192 {
+    fn encrypt_block(&self, msg: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if let Some(ni) = self.ni.as_ref() {
+                return ni.encrypt_block(msg);
+            }
+        }
+        cipher(msg, &self.w, N_ROUND_192)
+    }
+
+    fn decrypt_block(&self, cipher: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if let Some(ni) = self.ni.as_ref() {
+                return ni.decrypt_block(cipher);
+            }
+        }
+        inv_cipher(cipher, &self.dw, N_ROUND_192)
+    }
+
+    fn encrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if let Some(ni) = self.ni.as_ref() {
+                return ni.encrypt_blocks(blocks);
+            }
+        }
+        for block in blocks.iter_mut() {
+            *block = cipher(block, &self.w, N_ROUND_192);
+        }
+    }
+
+    fn decrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if let Some(ni) = self.ni.as_ref() {
+                return ni.decrypt_blocks(blocks);
+            }
+        }
+        for block in blocks.iter_mut() {
+            *block = inv_cipher(block, &self.dw, N_ROUND_192);
+        }
+    }
+}
+
+/// AES-256 block cipher with a pre-expanded key schedule
+///
+/// The key is expanded once on creation, and reused for every block
+///
+/// Uses AES-NI on x86_64 when supported, falling back to the portable implementation otherwise
+#[derive(Clone)]
+pub struct Aes256 {
+    w: [u32; N_EXPW_256],
+    dw: [u32; N_EXPW_256],
+    #[cfg(target_arch = "x86_64")]
+    ni: Option<aesni::Aes256>,
+}
+
+impl Aes256 {
+    /// Create an AES-256 cipher from the given key
+    pub fn new(key: &[u8; KEY_LEN_256]) -> Self {
+        #[allow(unused_mut)]
+        let mut aes = Self::new_portable(key);
+        #[cfg(target_arch = "x86_64")]
+        {
+            aes.ni = aesni::Aes256::new(key);
+        }
+        aes
+    }
+
+    /// Create an AES-256 cipher that never uses hardware instructions
+    pub fn new_portable(key: &[u8; KEY_LEN_256]) -> Self {
+        let w = expand_key_256(key);
+        let mut dw = [0_u32; N_EXPW_256];
+        expand_dec_key(&w, &mut dw);
+        Self {
+            w,
+            dw,
+            #[cfg(target_arch = "x86_64")]
+            ni: None,
+        }
+    }
+}
+
+impl BlockCipher for Aes256 {
+    fn encrypt_block(&self, msg: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if let Some(ni) = self.ni.as_ref() {
+                return ni.encrypt_block(msg);
+            }
+        }
+        cipher(msg, &self.w, N_ROUND_256)
+    }
+
+    fn decrypt_block(&self, cipher: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if let Some(ni) = self.ni.as_ref() {
+                return ni.decrypt_block(cipher);
+            }
+        }
+        inv_cipher(cipher, &self.dw, N_ROUND_256)
+    }
+
+    fn encrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if let Some(ni) = self.ni.as_ref() {
+                return ni.encrypt_blocks(blocks);
+            }
+        }
+        for block in blocks.iter_mut() {
+            *block = cipher(block, &self.w, N_ROUND_256);
+        }
+    }
+
+    fn decrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if let Some(ni) = self.ni.as_ref() {
+                return ni.decrypt_blocks(blocks);
+            }
+        }
+        for block in blocks.iter_mut() {
+            *block = inv_cipher(block, &self.dw, N_ROUND_256);
+        }
+    }
+}
+
+/// AES-128 cipher over a single block
+///
+/// Uses AES-NI on x86_64 when supported, falling back to the portable implementation otherwise
+///
+/// Expands the key on every call, use [Aes128](struct.Aes128.html) to encrypt multiple blocks
+///
+/// @param msg: Message block to be encrypted
+/// @param key: Key for encrypting the message
+pub fn aes_128(msg: &[u8; BLOCK_LEN], key: &[u8; KEY_LEN_128]) -> [u8; BLOCK_LEN] {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if let Some(ni) = aesni::Aes128::new(key) {
+            return ni.encrypt_block(msg);
+        }
+    }
+    cipher(msg, &expand_key_128(key), N_ROUND_128)
 }
 
 /// AES-128 inverse cipher over a single block
 ///
+/// Uses AES-NI on x86_64 when supported, falling back to the portable implementation otherwise
+///
+/// Expands the key on every call, use [Aes128](struct.Aes128.html) to decrypt multiple blocks
+///
+/// @param cipher: Cipher block to be decrypted
+/// @param key: Key for decrypting the cipher block
+pub fn aes_inv_128(cipher: &[u8; BLOCK_LEN], key: &[u8; KEY_LEN_128]) -> [u8; BLOCK_LEN] {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if let Some(ni) = aesni::Aes128::new(key) {
+            return ni.decrypt_block(cipher);
+        }
+    }
+    let mut dw = [0_u32; N_EXPW_128];
+    expand_dec_key(&expand_key_128(key), &mut dw);
+    inv_cipher(cipher, &dw, N_ROUND_128)
+}
+
+/// AES-192 cipher over a single block
+///
+/// Uses AES-NI on x86_64 when supported, falling back to the portable implementation otherwise
+///
+/// Expands the key on every call, use [Aes192](struct.Aes192.html) to encrypt multiple blocks
+///
 /// @param msg: Message block to be encrypted
 /// @param key: Key for encrypting the message
-pub fn aes_inv_128(cipher: &[u8; BLOCK_LEN],  key: &[u8; KEY_LEN_128]) -> [u8; BLOCK_LEN] {
-    aes_inv_inner_128(cipher, expand_key_128(key))
+pub fn aes_192(msg: &[u8; BLOCK_LEN], key: &[u8; KEY_LEN_192]) -> [u8; BLOCK_LEN] {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if let Some(ni) = aesni::Aes192::new(key) {
+            return ni.encrypt_block(msg);
+        }
+    }
+    cipher(msg, &expand_key_192(key), N_ROUND_192)
 }
 
-fn aes_inv_inner_128(msg: &[u8; BLOCK_LEN], w: [u32; N_EXPW_128]) -> [u8; BLOCK_LEN] {
-    let mut state: [u32; 4] = [
-        u32::from_be_bytes([msg[0], msg[1], msg[2], msg[3]]),
-        u32::from_be_bytes([msg[4], msg[5], msg[6], msg[7]]),
-        u32::from_be_bytes([msg[8], msg[9], msg[10], msg[11]]),
-        u32::from_be_bytes([msg[12], msg[13], msg[14], msg[15]]),
-    ];
+/// AES-192 inverse cipher over a single block
+///
+/// Uses AES-NI on x86_64 when supported, falling back to the portable implementation otherwise
+///
+/// Expands the key on every call, use [Aes192](struct.Aes192.html) to decrypt multiple blocks
+///
+/// @param cipher: Cipher block to be decrypted
+/// @param key: Key for decrypting the cipher block
+pub fn aes_inv_192(cipher: &[u8; BLOCK_LEN], key: &[u8; KEY_LEN_192]) -> [u8; BLOCK_LEN] {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if let Some(ni) = aesni::Aes192::new(key) {
+            return ni.decrypt_block(cipher);
+        }
+    }
+    let mut dw = [0_u32; N_EXPW_192];
+    expand_dec_key(&expand_key_192(key), &mut dw);
+    inv_cipher(cipher, &dw, N_ROUND_192)
+}
 
-    add_round_key(&mut state, &w[N_ROUND_128 * N_BLOCK..(N_ROUND_128 + 1) * N_BLOCK]);
+/// AES-256 cipher over a single block
+///
+/// Uses AES-NI on x86_64 when supported, falling back to the portable implementation otherwise
+///
+/// Expands the key on every call, use [Aes256](struct.Aes256.html) to encrypt multiple blocks
+///
+/// @param msg: Message block to be encrypted
+/// @param key: Key for encrypting the message
+pub fn aes_256(msg: &[u8; BLOCK_LEN], key: &[u8; KEY_LEN_256]) -> [u8; BLOCK_LEN] {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if let Some(ni) = aesni::Aes256::new(key) {
+            return ni.encrypt_block(msg);
+        }
+    }
+    cipher(msg, &expand_key_256(key), N_ROUND_256)
+}
 
-    for i in 1..N_ROUND_128 {
-        inv_shift_rows(&mut state);
+/// AES-256 inverse cipher over a single block
+///
+/// Uses AES-NI on x86_64 when supported, falling back to the portable implementation otherwise
+///
+/// Expands the key on every call, use [Aes256](struct.Aes256.html) to decrypt multiple blocks
+///
+/// @param cipher: Cipher block to be decrypted
+/// @param key: Key for decrypting the cipher block
+pub fn aes_inv_256(cipher: &[u8; BLOCK_LEN], key: &[u8; KEY_LEN_256]) -> [u8; BLOCK_LEN] {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if let Some(ni) = aesni::Aes256::new(key) {
+            return ni.decrypt_block(cipher);
+        }
+    }
+    let mut dw = [0_u32; N_EXPW_256];
+    expand_dec_key(&expand_key_256(key), &mut dw);
+    inv_cipher(cipher, &dw, N_ROUND_256)
+}
+
+// Cipher over a single block, using the T-table backend when enabled
+#[cfg(feature = "ttable")]
+fn cipher(msg: &[u8; BLOCK_LEN], w: &[u32], n_round: usize) -> [u8; BLOCK_LEN] {
+    ttable::cipher(msg, w, n_round)
+}
+
+// Cipher over a single block, using the reference implementation
+#[cfg(not(feature = "ttable"))]
+fn cipher(msg: &[u8; BLOCK_LEN], w: &[u32], n_round: usize) -> [u8; BLOCK_LEN] {
+    aes_inner(msg, w, n_round)
+}
+
+// Inverse cipher over a single block, using the T-table backend when enabled
+#[cfg(feature = "ttable")]
+fn inv_cipher(msg: &[u8; BLOCK_LEN], dw: &[u32], n_round: usize) -> [u8; BLOCK_LEN] {
+    ttable::inv_cipher(msg, dw, n_round)
+}
+
+// Inverse cipher over a single block, using the reference implementation
+#[cfg(not(feature = "ttable"))]
+fn inv_cipher(msg: &[u8; BLOCK_LEN], dw: &[u32], n_round: usize) -> [u8; BLOCK_LEN] {
+    aes_inv_inner(msg, dw, n_round)
+}
+
+// Cipher over a single block for any key size
+//
+// The expanded key must contain Nb * (Nr + 1) words
+#[cfg_attr(feature = "ttable", allow(dead_code))]
+pub(crate) fn aes_inner(msg: &[u8; BLOCK_LEN], w: &[u32], n_round: usize) -> [u8; BLOCK_LEN] {
+    let mut state = block_to_state(msg);
+
+    add_round_key(&mut state, &w[..N_BLOCK]);
+
+    for i in 1..n_round {
+        sub_bytes(&mut state);
+        shift_rows(&mut state);
+        mix_columns(&mut state);
+        add_round_key(&mut state, &w[i * N_BLOCK..(i + 1) * N_BLOCK]);
+    }
+
+    sub_bytes(&mut state);
+    shift_rows(&mut state);
+    add_round_key(&mut state, &w[n_round * N_BLOCK..(n_round + 1) * N_BLOCK]);
+
+    state_to_block(&state)
+}
+
+// Equivalent Inverse Cipher (FIPS-197 5.3.5) over a single block for any key size
+//
+// Round order matches the cipher, so the decryption key schedule must
+// have InvMixColumns applied to the middle round keys (see expand_dec_key)
+//
+// The expanded key must contain Nb * (Nr + 1) words
+#[cfg_attr(feature = "ttable", allow(dead_code))]
+pub(crate) fn aes_inv_inner(msg: &[u8; BLOCK_LEN], dw: &[u32], n_round: usize) -> [u8; BLOCK_LEN] {
+    let mut state = block_to_state(msg);
+
+    add_round_key(&mut state, &dw[n_round * N_BLOCK..(n_round + 1) * N_BLOCK]);
+
+    for i in (1..n_round).rev() {
         inv_sub_bytes(&mut state);
-        add_round_key(&mut state, &w[(N_ROUND_128 - i)*N_BLOCK..(N_ROUND_128 - i + 1) * N_BLOCK]);
+        inv_shift_rows(&mut state);
         inv_mix_columns(&mut state);
+        add_round_key(&mut state, &dw[i * N_BLOCK..(i + 1) * N_BLOCK]);
     }
 
-    inv_shift_rows(&mut state);
     inv_sub_bytes(&mut state);
+    inv_shift_rows(&mut state);
+    add_round_key(&mut state, &dw[..N_BLOCK]);
 
-    add_round_key(&mut state, &w[..N_BLOCK]);
+    state_to_block(&state)
+}
+
+// Load a block into state words (one word per column)
+pub(crate) fn block_to_state(block: &[u8; BLOCK_LEN]) -> [u32; N_BLOCK] {
+    [
+        u32::from_be_bytes([block[0], block[1], block[2], block[3]]),
+        u32::from_be_bytes([block[4], block[5], block[6], block[7]]),
+        u32::from_be_bytes([block[8], block[9], block[10], block[11]]),
+        u32::from_be_bytes([block[12], block[13], block[14], block[15]]),
+    ]
+}
 
+// Store state words into an output block
+pub(crate) fn state_to_block(state: &[u32; N_BLOCK]) -> [u8; BLOCK_LEN] {
     let mut out = [0u8; BLOCK_LEN];
 
     for (i, word) in state.iter().enumerate() {
         for (si, &byte) in word.to_be_bytes().iter().enumerate() {
-            out[i*N_BLOCK + si] = byte;
+            out[i * N_BLOCK + si] = byte;
         }
     }
 
     out
 }
 
-/// Substitution box
-fn sbox(byte: u8) -> u8 {
-    match byte {
-        // row 0
-        0x00 => 0x63, 0x01 => 0x7c, 0x02 => 0x77, 0x03 => 0x7b, 0x04 => 0xf2, 0x05 => 0x6b, 0x06 => 0x6f, 0x07 => 0xc5,
-        0x08 => 0x30, 0x09 => 0x01, 0x0a => 0x67, 0x0b => 0x2b, 0x0c => 0xfe, 0x0d => 0xd7, 0x0e => 0xab, 0x0f => 0x76,
-        // row 1
-        0x10 => 0xca, 0x11 => 0x82, 0x12 => 0xc9, 0x13 => 0x7d, 0x14 => 0xfa, 0x15 => 0x59, 0x16 => 0x47, 0x17 => 0xf0,
-        0x18 => 0xad, 0x19 => 0xd4, 0x1a => 0xa2, 0x1b => 0xaf, 0x1c => 0x9c, 0x1d => 0xa4, 0x1e => 0x72, 0x1f => 0xc0,
-        // row 2
-        0x20 => 0xb7, 0x21 => 0xfd, 0x22 => 0x93, 0x23 => 0x26, 0x24 => 0x36, 0x25 => 0x3f, 0x26 => 0xf7, 0x27 => 0xcc,
-        0x28 => 0x34, 0x29 => 0xa5, 0x2a => 0xe5, 0x2b => 0xf1, 0x2c => 0x71, 0x2d => 0xd8, 0x2e => 0x31, 0x2f => 0x15,
-        // row 3
-        0x30 => 0x04, 0x31 => 0xc7, 0x32 => 0x23, 0x33 => 0xc3, 0x34 => 0x18, 0x35 => 0x96, 0x36 => 0x05, 0x37 => 0x9a,
-        0x38 => 0x07, 0x39 => 0x12, 0x3a => 0x80, 0x3b => 0xe2, 0x3c => 0xeb, 0x3d => 0x27, 0x3e => 0xb2, 0x3f => 0x75,
-        // row 4
-        0x40 => 0x09, 0x41 => 0x83, 0x42 => 0x2c, 0x43 => 0x1a, 0x44 => 0x1b, 0x45 => 0x6e, 0x46 => 0x5a, 0x47 => 0xa0,
-        0x48 => 0x52, 0x49 => 0x3b, 0x4a => 0xd6, 0x4b => 0xb3, 0x4c => 0x29, 0x4d => 0xe3, 0x4e => 0x2f, 0x4f => 0x84,
-        // row 5
-        0x50 => 0x53, 0x51 => 0xd1, 0x52 => 0x00, 0x53 => 0xed, 0x54 => 0x20, 0x55 => 0xfc, 0x56 => 0xb1, 0x57 => 0x5b,
-        0x58 => 0x6a, 0x59 => 0xcb, 0x5a => 0xbe, 0x5b => 0x39, 0x5c => 0x4a, 0x5d => 0x4c, 0x5e => 0x58, 0x5f => 0xcf,
-        // row 6
-        0x60 => 0xd0, 0x61 => 0xef, 0x62 => 0xaa, 0x63 => 0xfb, 0x64 => 0x43, 0x65 => 0x4d, 0x66 => 0x33, 0x67 => 0x85,
-        0x68 => 0x45, 0x69 => 0xf9, 0x6a => 0x02, 0x6b => 0x7f, 0x6c => 0x50, 0x6d => 0x3c, 0x6e => 0x9f, 0x6f => 0xa8,
-        // row 7
-        0x70 => 0x51, 0x71 => 0xa3, 0x72 => 0x40, 0x73 => 0x8f, 0x74 => 0x92, 0x75 => 0x9d, 0x76 => 0x38, 0x77 => 0xf5,
-        0x78 => 0xbc, 0x79 => 0xb6, 0x7a => 0xda, 0x7b => 0x21, 0x7c => 0x10, 0x7d => 0xff, 0x7e => 0xf3, 0x7f => 0xd2,
-        // row 8
-        0x80 => 0xcd, 0x81 => 0x0c, 0x82 => 0x13, 0x83 => 0xec, 0x84 => 0x5f, 0x85 => 0x97, 0x86 => 0x44, 0x87 => 0x17,
-        0x88 => 0xc4, 0x89 => 0xa7, 0x8a => 0x7e, 0x8b => 0x3d, 0x8c => 0x64, 0x8d => 0x5d, 0x8e => 0x19, 0x8f => 0x73,
-        // row 9
-        0x90 => 0x60, 0x91 => 0x81, 0x92 => 0x4f, 0x93 => 0xdc, 0x94 => 0x22, 0x95 => 0x2a, 0x96 => 0x90, 0x97 => 0x88,
-        0x98 => 0x46, 0x99 => 0xee, 0x9a => 0xb8, 0x9b => 0x14, 0x9c => 0xde, 0x9d => 0x5e, 0x9e => 0x0b, 0x9f => 0xdb,
-        // row a
-        0xa0 => 0xe0, 0xa1 => 0x32, 0xa2 => 0x3a, 0xa3 => 0x0a, 0xa4 => 0x49, 0xa5 => 0x06, 0xa6 => 0x24, 0xa7 => 0x5c,
-        0xa8 => 0xc2, 0xa9 => 0xd3, 0xaa => 0xac, 0xab => 0x62, 0xac => 0x91, 0xad => 0x95, 0xae => 0xe4, 0xaf => 0x79,
-        // row b
-        0xb0 => 0xe7, 0xb1 => 0xc8, 0xb2 => 0x37, 0xb3 => 0x6d, 0xb4 => 0x8d, 0xb5 => 0xd5, 0xb6 => 0x4e, 0xb7 => 0xa9,
-        0xb8 => 0x6c, 0xb9 => 0x56, 0xba => 0xf4, 0xbb => 0xea, 0xbc => 0x65, 0xbd => 0x7a, 0xbe => 0xae, 0xbf => 0x08,
-        // row c
-        0xc0 => 0xba, 0xc1 => 0x78, 0xc2 => 0x25, 0xc3 => 0x2e, 0xc4 => 0x1c, 0xc5 => 0xa6, 0xc6 => 0xb4, 0xc7 => 0xc6,
-        0xc8 => 0xe8, 0xc9 => 0xdd, 0xca => 0x74, 0xcb => 0x1f, 0xcc => 0x4b, 0xcd => 0xbd, 0xce => 0x8b, 0xcf => 0x8a,
-        // row d
-        0xd0 => 0x70, 0xd1 => 0x3e, 0xd2 => 0xb5, 0xd3 => 0x66, 0xd4 => 0x48, 0xd5 => 0x03, 0xd6 => 0xf6, 0xd7 => 0x0e,
-        0xd8 => 0x61, 0xd9 => 0x35, 0xda => 0x57, 0xdb => 0xb9, 0xdc => 0x86, 0xdd => 0xc1, 0xde => 0x1d, 0xdf => 0x9e,
-        // row e
-        0xe0 => 0xe1, 0xe1 => 0xf8, 0xe2 => 0x98, 0xe3 => 0x11, 0xe4 => 0x69, 0xe5 => 0xd9, 0xe6 => 0x8e, 0xe7 => 0x94,
-        0xe8 => 0x9b, 0xe9 => 0x1e, 0xea => 0x87, 0xeb => 0xe9, 0xec => 0xce, 0xed => 0x55, 0xee => 0x28, 0xef => 0xdf,
-        // row f
-        0xf0 => 0x8c, 0xf1 => 0xa1, 0xf2 => 0x89, 0xf3 => 0x0d, 0xf4 => 0xbf, 0xf5 => 0xe6, 0xf6 => 0x42, 0xf7 => 0x68,
-        0xf8 => 0x41, 0xf9 => 0x99, 0xfa => 0x2d, 0xfb => 0x0f, 0xfc => 0xb0, 0xfd => 0x54, 0xfe => 0xbb, 0xff => 0x16,
-    }
-}
-
-/// Substitution box inverse
-fn sbox_inv(byte: u8) -> u8 {
-    match byte {
-        // row 0
-        0x63 => 0x00, 0x7c => 0x01, 0x77 => 0x02, 0x7b => 0x03, 0xf2 => 0x04, 0x6b => 0x05, 0x6f => 0x06, 0xc5 => 0x07,
-        0x30 => 0x08, 0x01 => 0x09, 0x67 => 0x0a, 0x2b => 0x0b, 0xfe => 0x0c, 0xd7 => 0x0d, 0xab => 0x0e, 0x76 => 0x0f,
-        // row 1
-        0xca => 0x10, 0x82 => 0x11, 0xc9 => 0x12, 0x7d => 0x13, 0xfa => 0x14, 0x59 => 0x15, 0x47 => 0x16, 0xf0 => 0x17,
-        0xad => 0x18, 0xd4 => 0x19, 0xa2 => 0x1a, 0xaf => 0x1b, 0x9c => 0x1c, 0xa4 => 0x1d, 0x72 => 0x1e, 0xc0 => 0x1f,
-        // row 2
-        0xb7 => 0x20, 0xfd => 0x21, 0x93 => 0x22, 0x26 => 0x23, 0x36 => 0x24, 0x3f => 0x25, 0xf7 => 0x26, 0xcc => 0x27,
-        0x34 => 0x28, 0xa5 => 0x29, 0xe5 => 0x2a, 0xf1 => 0x2b, 0x71 => 0x2c, 0xd8 => 0x2d, 0x31 => 0x2e, 0x15 => 0x2f,
-        // row 3
-        0x04 => 0x30, 0xc7 => 0x31, 0x23 => 0x32, 0xc3 => 0x33, 0x18 => 0x34, 0x96 => 0x35, 0x05 => 0x36, 0x9a => 0x37,
-        0x07 => 0x38, 0x12 => 0x39, 0x80 => 0x3a, 0xe2 => 0x3b, 0xeb => 0x3c, 0x27 => 0x3d, 0xb2 => 0x3e, 0x75 => 0x3f,
-        // row 4
-        0x09 => 0x40, 0x83 => 0x41, 0x2c => 0x42, 0x1a => 0x43, 0x1b => 0x44, 0x6e => 0x45, 0x5a => 0x46, 0xa0 => 0x47,
-        0x52 => 0x48, 0x3b => 0x49, 0xd6 => 0x4a, 0xb3 => 0x4b, 0x29 => 0x4c, 0xe3 => 0x4d, 0x2f => 0x4e, 0x84 => 0x4f,
-        // row 5
-        0x53 => 0x50, 0xd1 => 0x51, 0x00 => 0x52, 0xed => 0x53, 0x20 => 0x54, 0xfc => 0x55, 0xb1 => 0x56, 0x5b => 0x57,
-        0x6a => 0x58, 0xcb => 0x59, 0xbe => 0x5a, 0x39 => 0x5b, 0x4a => 0x5c, 0x4c => 0x5d, 0x58 => 0x5e, 0xcf => 0x5f,
-        // row 6
-        0xd0 => 0x60, 0xef => 0x61, 0xaa => 0x62, 0xfb => 0x63, 0x43 => 0x64, 0x4d => 0x65, 0x33 => 0x66, 0x85 => 0x67,
-        0x45 => 0x68, 0xf9 => 0x69, 0x02 => 0x6a, 0x7f => 0x6b, 0x50 => 0x6c, 0x3c => 0x6d, 0x9f => 0x6e, 0xa8 => 0x6f,
-        // row 7
-        0x51 => 0x70, 0xa3 => 0x71, 0x40 => 0x72, 0x8f => 0x73, 0x92 => 0x74, 0x9d => 0x75, 0x38 => 0x76, 0xf5 => 0x77,
-        0xbc => 0x78, 0xb6 => 0x79, 0xda => 0x7a, 0x21 => 0x7b, 0x10 => 0x7c, 0xff => 0x7d, 0xf3 => 0x7e, 0xd2 => 0x7f,
-        // row 8
-        0xcd => 0x80, 0x0c => 0x81, 0x13 => 0x82, 0xec => 0x83, 0x5f => 0x84, 0x97 => 0x85, 0x44 => 0x86, 0x17 => 0x87,
-        0xc4 => 0x88, 0xa7 => 0x89, 0x7e => 0x8a, 0x3d => 0x8b, 0x64 => 0x8c, 0x5d => 0x8d, 0x19 => 0x8e, 0x73 => 0x8f,
-        // row 9
-        0x60 => 0x90, 0x81 => 0x91, 0x4f => 0x92, 0xdc => 0x93, 0x22 => 0x94, 0x2a => 0x95, 0x90 => 0x96, 0x88 => 0x97,
-        0x46 => 0x98, 0xee => 0x99, 0xb8 => 0x9a, 0x14 => 0x9b, 0xde => 0x9c, 0x5e => 0x9d, 0x0b => 0x9e, 0xdb => 0x9f,
-        // row a
-        0xe0 => 0xa0, 0x32 => 0xa1, 0x3a => 0xa2, 0x0a => 0xa3, 0x49 => 0xa4, 0x06 => 0xa5, 0x24 => 0xa6, 0x5c => 0xa7,
-        0xc2 => 0xa8, 0xd3 => 0xa9, 0xac => 0xaa, 0x62 => 0xab, 0x91 => 0xac, 0x95 => 0xad, 0xe4 => 0xae, 0x79 => 0xaf,
-        // row b
-        0xe7 => 0xb0, 0xc8 => 0xb1, 0x37 => 0xb2, 0x6d => 0xb3, 0x8d => 0xb4, 0xd5 => 0xb5, 0x4e => 0xb6, 0xa9 => 0xb7,
-        0x6c => 0xb8, 0x56 => 0xb9, 0xf4 => 0xba, 0xea => 0xbb, 0x65 => 0xbc, 0x7a => 0xbd, 0xae => 0xbe, 0x08 => 0xbf,
-        // row c
-        0xba => 0xc0, 0x78 => 0xc1, 0x25 => 0xc2, 0x2e => 0xc3, 0x1c => 0xc4, 0xa6 => 0xc5, 0xb4 => 0xc6, 0xc6 => 0xc7,
-        0xe8 => 0xc8, 0xdd => 0xc9, 0x74 => 0xca, 0x1f => 0xcb, 0x4b => 0xcc, 0xbd => 0xcd, 0x8b => 0xce, 0x8a => 0xcf,
-        // row d
-        0x70 => 0xd0, 0x3e => 0xd1, 0xb5 => 0xd2, 0x66 => 0xd3, 0x48 => 0xd4, 0x03 => 0xd5, 0xf6 => 0xd6, 0x0e => 0xd7,
-        0x61 => 0xd8, 0x35 => 0xd9, 0x57 => 0xda, 0xb9 => 0xdb, 0x86 => 0xdc, 0xc1 => 0xdd, 0x1d => 0xde, 0x9e => 0xdf,
-        // row e
-        0xe1 => 0xe0, 0xf8 => 0xe1, 0x98 => 0xe2, 0x11 => 0xe3, 0x69 => 0xe4, 0xd9 => 0xe5, 0x8e => 0xe6, 0x94 => 0xe7,
-        0x9b => 0xe8, 0x1e => 0xe9, 0x87 => 0xea, 0xe9 => 0xeb, 0xce => 0xec, 0x55 => 0xed, 0x28 => 0xee, 0xdf => 0xef,
-        // row f
-        0x8c => 0xf0, 0xa1 => 0xf1, 0x89 => 0xf2, 0x0d => 0xf3, 0xbf => 0xf4, 0xe6 => 0xf5, 0x42 => 0xf6, 0x68 => 0xf7,
-        0x41 => 0xf8, 0x99 => 0xf9, 0x2d => 0xfa, 0x0f => 0xfb, 0xb0 => 0xfc, 0x54 => 0xfd, 0xbb => 0xfe, 0x16 => 0xff,
-    }
+// Substitution box
+//
+// Computed arithmetically instead of with a lookup table, so no memory
+// access or branch depends on the (secret) input byte:
+//
+// b' = b ^ (b <<< 1) ^ (b <<< 2) ^ (b <<< 3) ^ (b <<< 4) ^ 0x63
+//
+// where b is the multiplicative inverse of the input in GF(2^8)
+pub(crate) const fn sbox(byte: u8) -> u8 {
+    let b = gf_inv(byte);
+
+    b ^ b.rotate_left(1) ^ b.rotate_left(2) ^ b.rotate_left(3) ^ b.rotate_left(4) ^ 0x63
+}
+
+// Inverse substitution box
+//
+// Applies the inverse affine transform, then takes the multiplicative inverse:
+//
+// b = (b' <<< 1) ^ (b' <<< 3) ^ (b' <<< 6) ^ 0x05
+pub(crate) const fn sbox_inv(byte: u8) -> u8 {
+    gf_inv(byte.rotate_left(1) ^ byte.rotate_left(3) ^ byte.rotate_left(6) ^ 0x05)
+}
+
+// Multiplicative inverse in GF(2^8), computed as b^254 = b^-1
+//
+// Uses a fixed addition chain, so zero maps to zero without a branch
+const fn gf_inv(b: u8) -> u8 {
+    let b2 = xmul(b, b);
+    let b3 = xmul(b2, b);
+    let b6 = xmul(b3, b3);
+    let b12 = xmul(b6, b6);
+    let b15 = xmul(b12, b3);
+    let b30 = xmul(b15, b15);
+    let b60 = xmul(b30, b30);
+    let b120 = xmul(b60, b60);
+    let b240 = xmul(b120, b120);
+    let b252 = xmul(b240, b12);
+
+    xmul(b252, b2)
 }
 
 // Apply S-Box to each word in the state
-fn sub_bytes(state: &mut [u32; N_BLOCK]) {
+pub(crate) fn sub_bytes(state: &mut [u32; N_BLOCK]) {
     for word in state.iter_mut() {
-        *word = sub_word(&word);
+        *word = sub_word(word);
     }
 }
 
 // Apply inverse S-Box to each word in the state
-fn inv_sub_bytes(state: &mut [u32; N_BLOCK]) {
+pub(crate) fn inv_sub_bytes(state: &mut [u32; N_BLOCK]) {
     for word in state.iter_mut() {
-        *word = inv_sub_word(&word);
+        *word = inv_sub_word(word);
     }
 }
 
 // Shift state words relative to row index
-fn shift_rows(state: &mut [u32; N_BLOCK]) {
+pub(crate) fn shift_rows(state: &mut [u32; N_BLOCK]) {
     let s0 = state[0].to_be_bytes();
     let s1 = state[1].to_be_bytes();
     let s2 = state[2].to_be_bytes();
@@ -255,7 +597,7 @@ fn shift_rows(state: &mut [u32; N_BLOCK]) {
 }
 
 // Inverse shift state words relative to row index
-fn inv_shift_rows(state: &mut [u32; N_BLOCK]) {
+pub(crate) fn inv_shift_rows(state: &mut [u32; N_BLOCK]) {
     let s0 = state[0].to_be_bytes();
     let s1 = state[1].to_be_bytes();
     let s2 = state[2].to_be_bytes();
@@ -275,7 +617,7 @@ fn inv_shift_rows(state: &mut [u32; N_BLOCK]) {
 // s1' = s0 + 0x02*s1 + 0x03*s2 + s3
 // s2' = s0 + s1 + 0x02*s2 + 0x03*s3
 // s3' = 0x03*s0 + s1 + s2 + 0x02*s3
-fn mix_columns(state: &mut [u32; N_BLOCK]) {
+pub(crate) fn mix_columns(state: &mut [u32; N_BLOCK]) {
     state[0] = mix_word(state[0]);
     state[1] = mix_word(state[1]);
     state[2] = mix_word(state[2]);
@@ -295,7 +637,7 @@ fn mix_word(word: u32) -> u32 {
 }
 
 // Perform InvMixColumns as matrix-vector multiplication
-fn inv_mix_columns(state: &mut [u32; N_BLOCK]) {
+pub(crate) fn inv_mix_columns(state: &mut [u32; N_BLOCK]) {
     state[0] = inv_mix_word(state[0]);
     state[1] = inv_mix_word(state[1]);
     state[2] = inv_mix_word(state[2]);
@@ -315,7 +657,7 @@ fn inv_mix_word(word: u32) -> u32 {
 }
 
 // Multiply a byte by a constant
-fn xmul(b: u8, c: u8) -> u8 {
+pub(crate) const fn xmul(b: u8, c: u8) -> u8 {
     // keep intermediate calculations of xtime(b1)..xtime(b8)
     let b2 = xtime(b);
     let b3 = xtime(b2);
@@ -340,7 +682,7 @@ fn xmul(b: u8, c: u8) -> u8 {
 
 // Multiply a byte by two, and apply conditional XOR to reduce
 // to a number mod 255
-fn xtime(b: u8) -> u8 {
+const fn xtime(b: u8) -> u8 {
     let res = (b as u16) << 1;
     let is_red = b & 0b1000_0000 == 0;
 
@@ -350,16 +692,12 @@ fn xtime(b: u8) -> u8 {
 }
 
 // Add round key words to state words using XOR
-fn add_round_key(state: &mut [u32; 4], round_key: &[u32]) {
+pub(crate) fn add_round_key(state: &mut [u32; 4], round_key: &[u32]) {
     for (state_word, key_word) in state.iter_mut().zip(round_key.iter()) {
         *state_word ^= *key_word;
     }
 }
 
-// Nb = 4
-// Nk = 4
-// Nr = 10
-//
 // KeyExpansion(byte key[4*Nk], word w[Nb*(Nr+1)], Nk)
 // begin
 //   word  tempi = 0
@@ -379,26 +717,78 @@ fn add_round_key(state: &mut [u32; 4], round_key: &[u32]) {
 //     i = i + 1
 //   end while
 // end
-fn expand_key_128(key: &[u8; KEY_LEN_128]) -> [u32; N_EXPW_128] {
+//
+// Nb = 4
+// Nk = 4
+// Nr = 10
+pub(crate) fn expand_key_128(key: &[u8; KEY_LEN_128]) -> [u32; N_EXPW_128] {
     let mut out = [0_u32; N_EXPW_128];
-    for i in 0..N_KEY_128 {
-        let base = i*4;
-        out[i] = ((key[base] as u32) << 24)
-            + ((key[base + 1] as u32) << 16)
-            + ((key[base + 2] as u32) << 8)
-            + (key[base + 3] as u32);
-    }
-    for i in N_KEY_128..N_EXPW_128 {
+    expand_key(key, &mut out);
+    out
+}
+
+// Nb = 4
+// Nk = 6
+// Nr = 12
+pub(crate) fn expand_key_192(key: &[u8; KEY_LEN_192]) -> [u32; N_EXPW_192] {
+    let mut out = [0_u32; N_EXPW_192];
+    expand_key(key, &mut out);
+    out
+}
+
+// Nb = 4
+// Nk = 8
+// Nr = 14
+pub(crate) fn expand_key_256(key: &[u8; KEY_LEN_256]) -> [u32; N_EXPW_256] {
+    let mut out = [0_u32; N_EXPW_256];
+    expand_key(key, &mut out);
+    out
+}
+
+// KeyExpansion for Nk = key.len() / 4, filling every word of the output schedule
+fn expand_key(key: &[u8], out: &mut [u32]) {
+    let n_key = key.len() / 4;
+
+    for (word, chunk) in out.iter_mut().zip(key.chunks_exact(4)) {
+        *word = ((chunk[0] as u32) << 24)
+            + ((chunk[1] as u32) << 16)
+            + ((chunk[2] as u32) << 8)
+            + (chunk[3] as u32);
+    }
+    for i in n_key..out.len() {
         let mut temp = out[i - 1];
-        let k_round = (i % N_KEY_128 == 0) as u32;
-        // calculate k-round transform every round for constant-time execution
-        let xform_temp = sub_word(&rot_word(&temp, 1)) ^ round_const(i / N_KEY_128);
-        // evaluates to temp for non-k-round, and transform(temp) for k-round
-        // Note: AES-256 requires evaluating a third branch for mod-4 rounds
-        temp = (temp * (k_round ^ 0x0000_0001)) + (xform_temp * k_round);
-        out[i] = out[i - N_KEY_128] ^ temp;
+        let k_round = (i % n_key == 0) as u32;
+        // only AES-256 (Nk > 6) applies SubWord on mod-4 rounds
+        let s_round = (n_key > 6 && i % n_key == 4) as u32;
+        // calculate k-round and mod-4 round transforms every round for constant-time execution
+        let xform_temp = sub_word(&rot_word(&temp, 1)) ^ round_const(i / n_key);
+        let sub_temp = sub_word(&temp);
+        // evaluates to temp for regular rounds, transform(temp) for k-round,
+        // and SubWord(temp) for AES-256 mod-4 rounds
+        temp = (temp * (k_round ^ s_round ^ 0x0000_0001))
+            + (xform_temp * k_round)
+            + (sub_temp * s_round);
+        out[i] = out[i - n_key] ^ temp;
+    }
+}
+
+// Decryption key schedule for the Equivalent Inverse Cipher
+//
+// for i = 0 step 1 to (Nr+1)*Nb-1
+//   dw[i] = w[i]
+// end for
+//
+// for round = 1 step 1 to Nr-1
+//   InvMixColumns(dw[round*Nb, (round+1)*Nb-1])
+// end for
+pub(crate) fn expand_dec_key(w: &[u32], dw: &mut [u32]) {
+    let len = w.len();
+
+    dw.copy_from_slice(w);
+
+    for word in dw[N_BLOCK..len - N_BLOCK].iter_mut() {
+        *word = inv_mix_word(*word);
     }
-    out
 }
 
 // Apply SBOX substitution on the state word as bytes
@@ -432,7 +822,7 @@ fn rot_word(word: &u32, cycle: u32) -> u32 {
 }
 
 // Round constant: 2^(i-1) % 2^8
-fn round_const(round: usize) -> u32 {
+pub(crate) fn round_const(round: usize) -> u32 {
     match round {
         1 => 0x01000000,
         2 => 0x02000000,
@@ -451,6 +841,46 @@ fn round_const(round: usize) -> u32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use core::convert::TryInto;
+
+    // FIPS-197 Figure 7 (S-box) and Figure 14 (inverse S-box)
+    const SBOX: [u8; 256] = [
+        /* 0 */ 0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+        /* 1 */ 0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+        /* 2 */ 0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+        /* 3 */ 0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+        /* 4 */ 0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+        /* 5 */ 0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+        /* 6 */ 0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+        /* 7 */ 0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+        /* 8 */ 0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+        /* 9 */ 0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+        /* a */ 0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+        /* b */ 0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+        /* c */ 0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+        /* d */ 0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+        /* e */ 0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+        /* f */ 0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+    ];
+
+    const SBOX_INV: [u8; 256] = [
+        /* 0 */ 0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
+        /* 1 */ 0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
+        /* 2 */ 0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
+        /* 3 */ 0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
+        /* 4 */ 0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
+        /* 5 */ 0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
+        /* 6 */ 0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a, 0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
+        /* 7 */ 0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02, 0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
+        /* 8 */ 0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
+        /* 9 */ 0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
+        /* a */ 0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
+        /* b */ 0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
+        /* c */ 0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
+        /* d */ 0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
+        /* e */ 0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+        /* f */ 0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
+    ];
 
     #[test]
     fn check_xtime() {
@@ -494,7 +924,7 @@ mod tests {
     #[test]
     fn check_inv_sub_bytes() {
         let mut state = [0xcf4f3c09, 0x6c76052a, 0x59f67f73, 0x7a883b6d];
-        let orig = state.clone();
+        let orig = state;
 
         sub_bytes(&mut state);
         inv_sub_bytes(&mut state);
@@ -527,7 +957,7 @@ mod tests {
     #[test]
     fn check_inv_mix_columns() {
         let mut inp = [0xd4bf5d30, 0xe0b452ae, 0xb84111f1, 0x1e2798e5];
-        let orig = inp.clone();
+        let orig = inp;
 
         mix_columns(&mut inp);
         inv_mix_columns(&mut inp);
@@ -546,7 +976,7 @@ mod tests {
     #[test]
     fn check_inv_shift_rows() {
         let mut inp = [0xd42711ae, 0xe0bf98f1, 0xb8b45de5, 0x1e415230];
-        let orig = inp.clone();
+        let orig = inp;
 
         shift_rows(&mut inp);
         inv_shift_rows(&mut inp);
@@ -586,6 +1016,9 @@ mod tests {
         ];
 
         assert_eq!(aes_128(&msg, &key), exp_out);
+        for aes in [Aes128::new_portable(&key), Aes128::new(&key)].iter() {
+            assert_eq!(aes.encrypt_block(&msg), exp_out);
+        }
     }
 
     #[test]
@@ -601,6 +1034,201 @@ mod tests {
 
         assert_eq!(aes_128(&msg, &key), exp_out);
         assert_eq!(aes_inv_128(&exp_out, &key), msg);
+        for aes in [Aes128::new_portable(&key), Aes128::new(&key)].iter() {
+            assert_eq!(aes.decrypt_block(&exp_out), msg);
+        }
+    }
+
+    #[test]
+    fn check_key_expand_192() {
+        // FIPS-197 Appendix A.2
+        let key: [u8; KEY_LEN_192] = [
+            0x8e, 0x73, 0xb0, 0xf7, 0xda, 0x0e, 0x64, 0x52, 0xc8, 0x10, 0xf3, 0x2b,
+            0x80, 0x90, 0x79, 0xe5, 0x62, 0xf8, 0xea, 0xd2, 0x52, 0x2c, 0x6b, 0x7b,
+        ];
+
+        let w = expand_key_192(&key);
+
+        assert_eq!(w[..N_KEY_192], [0x8e73b0f7, 0xda0e6452, 0xc810f32b, 0x809079e5, 0x62f8ead2, 0x522c6b7b]);
+        assert_eq!(w[N_KEY_192..N_KEY_192 + 6], [0xfe0c91f7, 0x2402f5a5, 0xec12068e, 0x6c827f6b, 0x0e7a95b9, 0x5c56fec2]);
+        assert_eq!(w[N_EXPW_192 - 1], 0x01002202);
+    }
+
+    #[test]
+    fn check_aes_192() {
+        // FIPS-197 Appendix C.2
+        let msg: [u8; BLOCK_LEN] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff,];
+        let key: [u8; KEY_LEN_192] = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,
+            0x0c, 0x0d, 0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17,
+        ];
+        let exp_out: [u8; BLOCK_LEN] = [
+            0xdd, 0xa9, 0x7c, 0xa4, /*s0*/
+            0x86, 0x4c, 0xdf, 0xe0, /*s1*/
+            0x6e, 0xaf, 0x70, 0xa0, /*s2*/
+            0xec, 0x0d, 0x71, 0x91, /*s3*/
+        ];
+
+        assert_eq!(aes_192(&msg, &key), exp_out);
+        for aes in [Aes192::new_portable(&key), Aes192::new(&key)].iter() {
+            assert_eq!(aes.encrypt_block(&msg), exp_out);
+        }
+    }
+
+    #[test]
+    fn check_aes_inv_192() {
+        // FIPS-197 Appendix C.2
+        let msg: [u8; BLOCK_LEN] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff,];
+        let key: [u8; KEY_LEN_192] = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,
+            0x0c, 0x0d, 0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17,
+        ];
+        let exp_out: [u8; BLOCK_LEN] = [
+            0xdd, 0xa9, 0x7c, 0xa4, /*s0*/
+            0x86, 0x4c, 0xdf, 0xe0, /*s1*/
+            0x6e, 0xaf, 0x70, 0xa0, /*s2*/
+            0xec, 0x0d, 0x71, 0x91, /*s3*/
+        ];
+
+        assert_eq!(aes_inv_192(&exp_out, &key), msg);
+        for aes in [Aes192::new_portable(&key), Aes192::new(&key)].iter() {
+            assert_eq!(aes.decrypt_block(&exp_out), msg);
+        }
+    }
+
+    #[test]
+    fn check_key_expand_256() {
+        // FIPS-197 Appendix A.3
+        let key: [u8; KEY_LEN_256] = [
+            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d, 0x77, 0x81,
+            0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3, 0x09, 0x14, 0xdf, 0xf4,
+        ];
+
+        let w = expand_key_256(&key);
+
+        assert_eq!(
+            w[..N_KEY_256],
+            [0x603deb10, 0x15ca71be, 0x2b73aef0, 0x857d7781, 0x1f352c07, 0x3b6108d7, 0x2d9810a3, 0x0914dff4]
+        );
+        // w[12] is the first SubWord-only (i mod Nk = 4) round
+        assert_eq!(
+            w[N_KEY_256..N_KEY_256 + 5],
+            [0x9ba35411, 0x8e6925af, 0xa51a8b5f, 0x2067fcde, 0xa8b09c1a]
+        );
+        assert_eq!(w[N_EXPW_256 - 1], 0x706c631e);
+    }
+
+    #[test]
+    fn check_aes_256() {
+        // FIPS-197 Appendix C.3
+        let msg: [u8; BLOCK_LEN] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff,];
+        let key: [u8; KEY_LEN_256] = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+            0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+        ];
+        let exp_out: [u8; BLOCK_LEN] = [
+            0x8e, 0xa2, 0xb7, 0xca, /*s0*/
+            0x51, 0x67, 0x45, 0xbf, /*s1*/
+            0xea, 0xfc, 0x49, 0x90, /*s2*/
+            0x4b, 0x49, 0x60, 0x89, /*s3*/
+        ];
+
+        assert_eq!(aes_256(&msg, &key), exp_out);
+        for aes in [Aes256::new_portable(&key), Aes256::new(&key)].iter() {
+            assert_eq!(aes.encrypt_block(&msg), exp_out);
+        }
+    }
+
+    #[test]
+    fn check_aes_inv_256() {
+        // FIPS-197 Appendix C.3
+        let msg: [u8; BLOCK_LEN] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff,];
+        let key: [u8; KEY_LEN_256] = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+            0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+        ];
+        let exp_out: [u8; BLOCK_LEN] = [
+            0x8e, 0xa2, 0xb7, 0xca, /*s0*/
+            0x51, 0x67, 0x45, 0xbf, /*s1*/
+            0xea, 0xfc, 0x49, 0x90, /*s2*/
+            0x4b, 0x49, 0x60, 0x89, /*s3*/
+        ];
+
+        assert_eq!(aes_inv_256(&exp_out, &key), msg);
+        for aes in [Aes256::new_portable(&key), Aes256::new(&key)].iter() {
+            assert_eq!(aes.decrypt_block(&exp_out), msg);
+        }
+    }
+
+    #[test]
+    fn check_reused_schedule() {
+        let key: [u8; KEY_LEN_128] = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,];
+        let aes = Aes128::new(&key);
+
+        // the same cipher must produce the same output as a freshly expanded key for every block
+        for i in 0..=0xff_u8 {
+            let msg = [i; BLOCK_LEN];
+            let cipher = aes.encrypt_block(&msg);
+
+            assert_eq!(cipher, aes_128(&msg, &key));
+            assert_eq!(aes.decrypt_block(&cipher), msg);
+        }
+    }
+
+    #[test]
+    fn check_dec_key_expand() {
+        let key: [u8; KEY_LEN_128] = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+        let w = expand_key_128(&key);
+        let mut dw = [0_u32; N_EXPW_128];
+
+        expand_dec_key(&w, &mut dw);
+
+        // first and last round keys are used as-is
+        assert_eq!(dw[..N_BLOCK], w[..N_BLOCK]);
+        assert_eq!(dw[N_EXPW_128 - N_BLOCK..], w[N_EXPW_128 - N_BLOCK..]);
+
+        // middle round keys have InvMixColumns applied
+        let mut round_key = [w[4], w[5], w[6], w[7]];
+        inv_mix_columns(&mut round_key);
+        assert_eq!(dw[4..8], round_key);
+    }
+
+    #[test]
+    fn check_eq_inv_cipher() {
+        let key: [u8; KEY_LEN_256] = [
+            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d, 0x77, 0x81,
+            0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3, 0x09, 0x14, 0xdf, 0xf4,
+        ];
+        let aes_128 = Aes128::new(key[..KEY_LEN_128].try_into().unwrap());
+        let aes_192 = Aes192::new(key[..KEY_LEN_192].try_into().unwrap());
+        let aes_256 = Aes256::new(&key);
+
+        for i in 0..=0xff_u8 {
+            let mut msg = [i; BLOCK_LEN];
+            msg[i as usize % BLOCK_LEN] ^= 0xa5;
+
+            assert_eq!(aes_128.decrypt_block(&aes_128.encrypt_block(&msg)), msg);
+            assert_eq!(aes_192.decrypt_block(&aes_192.encrypt_block(&msg)), msg);
+            assert_eq!(aes_256.decrypt_block(&aes_256.encrypt_block(&msg)), msg);
+        }
+    }
+
+    #[test]
+    fn check_gf_inv() {
+        assert_eq!(gf_inv(0x00), 0x00);
+        assert_eq!(gf_inv(0x01), 0x01);
+
+        for byte in 0x01_u8..=0xff_u8 {
+            assert_eq!(xmul(byte, gf_inv(byte)), 0x01);
+        }
+    }
+
+    #[test]
+    fn check_sbox_tables() {
+        for byte in 0x00_u8..=0xff_u8 {
+            assert_eq!(sbox(byte), SBOX[byte as usize]);
+            assert_eq!(sbox_inv(byte), SBOX_INV[byte as usize]);
+        }
     }
 
     #[test]
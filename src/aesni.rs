@@ -0,0 +1,463 @@
+//! AES-NI hardware AES implementation for x86_64
+//!
+//! Uses AESENC/AESENCLAST for encryption, AESDEC/AESDECLAST for decryption (Equivalent Inverse Cipher),
+//! and AESKEYGENASSIST/AESIMC for the key schedules.
+//!
+//! Support is detected at runtime with the `std` feature, otherwise the `aes` target feature
+//! must be enabled at compile time (e.g. `RUSTFLAGS="-C target-feature=+aes"`).
+
+use core::arch::x86_64::*;
+
+use crate::aes::{self, BLOCK_LEN, N_BLOCK};
+use crate::{BlockCipher, PAR_BLOCKS};
+
+/// Check if the CPU supports the AES-NI instructions
+#[cfg(any(feature = "std", test))]
+pub fn is_supported() -> bool {
+    std::is_x86_feature_detected!("aes") && std::is_x86_feature_detected!("sse2")
+}
+
+/// Check if the CPU supports the AES-NI instructions
+///
+/// Without the `std` feature, only compile-time target features are checked
+#[cfg(not(any(feature = "std", test)))]
+pub fn is_supported() -> bool {
+    cfg!(all(target_feature = "aes", target_feature = "sse2"))
+}
+
+/// AES-128 block cipher using AES-NI
+#[derive(Clone, Copy)]
+pub struct Aes128 {
+    rk: [__m128i; aes::N_ROUND_128 + 1],
+    drk: [__m128i; aes::N_ROUND_128 + 1],
+}
+
+impl Aes128 {
+    /// Create an AES-NI AES-128 cipher from the given key
+    ///
+    /// Returns None if the CPU does not support AES-NI
+    pub fn new(key: &[u8; aes::KEY_LEN_128]) -> Option<Self> {
+        if !is_supported() {
+            return None;
+        }
+
+        let mut w = [0_u32; aes::N_EXPW_128];
+        // safety: AES-NI support checked above
+        let (rk, drk) = unsafe {
+            expand_key(key, &mut w);
+            let mut rk = [_mm_setzero_si128(); aes::N_ROUND_128 + 1];
+            let mut drk = [_mm_setzero_si128(); aes::N_ROUND_128 + 1];
+            load_round_keys(&w, &mut rk, &mut drk);
+            (rk, drk)
+        };
+
+        Some(Self { rk, drk })
+    }
+}
+
+impl BlockCipher for Aes128 {
+    fn encrypt_block(&self, msg: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        // safety: only constructed when AES-NI is supported
+        unsafe { cipher(msg, &self.rk) }
+    }
+
+    fn decrypt_block(&self, cipher: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        // safety: only constructed when AES-NI is supported
+        unsafe { inv_cipher(cipher, &self.drk) }
+    }
+
+    fn encrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        // safety: only constructed when AES-NI is supported
+        unsafe { cipher_blocks(blocks, &self.rk) }
+    }
+
+    fn decrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        // safety: only constructed when AES-NI is supported
+        unsafe { inv_cipher_blocks(blocks, &self.drk) }
+    }
+}
+
+/// AES-192 block cipher using AES-NI
+#[derive(Clone, Copy)]
+pub struct Aes192 {
+    rk: [__m128i; aes::N_ROUND_192 + 1],
+    drk: [__m128i; aes::N_ROUND_192 + 1],
+}
+
+impl Aes192 {
+    /// Create an AES-NI AES-192 cipher from the given key
+    ///
+    /// Returns None if the CPU does not support AES-NI
+    pub fn new(key: &[u8; aes::KEY_LEN_192]) -> Option<Self> {
+        if !is_supported() {
+            return None;
+        }
+
+        let mut w = [0_u32; aes::N_EXPW_192];
+        // safety: AES-NI support checked above
+        let (rk, drk) = unsafe {
+            expand_key(key, &mut w);
+            let mut rk = [_mm_setzero_si128(); aes::N_ROUND_192 + 1];
+            let mut drk = [_mm_setzero_si128(); aes::N_ROUND_192 + 1];
+            load_round_keys(&w, &mut rk, &mut drk);
+            (rk, drk)
+        };
+
+        Some(Self { rk, drk })
+    }
+}
+
+impl BlockCipher for Aes192 {
+    fn encrypt_block(&self, msg: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        // safety: only constructed when AES-NI is supported
+        unsafe { cipher(msg, &self.rk) }
+    }
+
+    fn decrypt_block(&self, cipher: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        // safety: only constructed when AES-NI is supported
+        unsafe { inv_cipher(cipher, &self.drk) }
+    }
+
+    fn encrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        // safety: only constructed when AES-NI is supported
+        unsafe { cipher_blocks(blocks, &self.rk) }
+    }
+
+    fn decrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        // safety: only constructed when AES-NI is supported
+        unsafe { inv_cipher_blocks(blocks, &self.drk) }
+    }
+}
+
+/// AES-256 block cipher using AES-NI
+#[derive(Clone, Copy)]
+pub struct Aes256 {
+    rk: [__m128i; aes::N_ROUND_256 + 1],
+    drk: [__m128i; aes::N_ROUND_256 + 1],
+}
+
+impl Aes256 {
+    /// Create an AES-NI AES-256 cipher from the given key
+    ///
+    /// Returns None if the CPU does not support AES-NI
+    pub fn new(key: &[u8; aes::KEY_LEN_256]) -> Option<Self> {
+        if !is_supported() {
+            return None;
+        }
+
+        let mut w = [0_u32; aes::N_EXPW_256];
+        // safety: AES-NI support checked above
+        let (rk, drk) = unsafe {
+            expand_key(key, &mut w);
+            let mut rk = [_mm_setzero_si128(); aes::N_ROUND_256 + 1];
+            let mut drk = [_mm_setzero_si128(); aes::N_ROUND_256 + 1];
+            load_round_keys(&w, &mut rk, &mut drk);
+            (rk, drk)
+        };
+
+        Some(Self { rk, drk })
+    }
+}
+
+impl BlockCipher for Aes256 {
+    fn encrypt_block(&self, msg: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        // safety: only constructed when AES-NI is supported
+        unsafe { cipher(msg, &self.rk) }
+    }
+
+    fn decrypt_block(&self, cipher: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        // safety: only constructed when AES-NI is supported
+        unsafe { inv_cipher(cipher, &self.drk) }
+    }
+
+    fn encrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        // safety: only constructed when AES-NI is supported
+        unsafe { cipher_blocks(blocks, &self.rk) }
+    }
+
+    fn decrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        // safety: only constructed when AES-NI is supported
+        unsafe { inv_cipher_blocks(blocks, &self.drk) }
+    }
+}
+
+// Cipher over a single block, Nr = rk.len() - 1
+#[target_feature(enable = "aes,sse2")]
+unsafe fn cipher(msg: &[u8; BLOCK_LEN], rk: &[__m128i]) -> [u8; BLOCK_LEN] {
+    let n_round = rk.len() - 1;
+
+    let mut state = _mm_loadu_si128(msg.as_ptr() as *const __m128i);
+
+    state = _mm_xor_si128(state, rk[0]);
+
+    for round_key in rk[1..n_round].iter() {
+        state = _mm_aesenc_si128(state, *round_key);
+    }
+
+    state = _mm_aesenclast_si128(state, rk[n_round]);
+
+    let mut out = [0_u8; BLOCK_LEN];
+    _mm_storeu_si128(out.as_mut_ptr() as *mut __m128i, state);
+    out
+}
+
+// Equivalent Inverse Cipher over a single block, Nr = drk.len() - 1
+#[target_feature(enable = "aes,sse2")]
+unsafe fn inv_cipher(cipher: &[u8; BLOCK_LEN], drk: &[__m128i]) -> [u8; BLOCK_LEN] {
+    let n_round = drk.len() - 1;
+
+    let mut state = _mm_loadu_si128(cipher.as_ptr() as *const __m128i);
+
+    state = _mm_xor_si128(state, drk[n_round]);
+
+    for round_key in drk[1..n_round].iter().rev() {
+        state = _mm_aesdec_si128(state, *round_key);
+    }
+
+    state = _mm_aesdeclast_si128(state, drk[0]);
+
+    let mut out = [0_u8; BLOCK_LEN];
+    _mm_storeu_si128(out.as_mut_ptr() as *mut __m128i, state);
+    out
+}
+
+// Cipher over multiple independent blocks
+//
+// Interleaves PAR_BLOCKS blocks per round, so AESENC latency is hidden behind the other blocks
+#[target_feature(enable = "aes,sse2")]
+unsafe fn cipher_blocks(blocks: &mut [[u8; BLOCK_LEN]], rk: &[__m128i]) {
+    let n_round = rk.len() - 1;
+
+    let mut chunks = blocks.chunks_exact_mut(PAR_BLOCKS);
+
+    for chunk in &mut chunks {
+        let mut state = [_mm_setzero_si128(); PAR_BLOCKS];
+
+        for (s, block) in state.iter_mut().zip(chunk.iter()) {
+            *s = _mm_xor_si128(_mm_loadu_si128(block.as_ptr() as *const __m128i), rk[0]);
+        }
+
+        for round_key in rk[1..n_round].iter() {
+            for s in state.iter_mut() {
+                *s = _mm_aesenc_si128(*s, *round_key);
+            }
+        }
+
+        for (s, block) in state.iter().zip(chunk.iter_mut()) {
+            _mm_storeu_si128(block.as_mut_ptr() as *mut __m128i, _mm_aesenclast_si128(*s, rk[n_round]));
+        }
+    }
+
+    for block in chunks.into_remainder().iter_mut() {
+        *block = cipher(block, rk);
+    }
+}
+
+// Equivalent Inverse Cipher over multiple independent blocks
+//
+// Interleaves PAR_BLOCKS blocks per round, so AESDEC latency is hidden behind the other blocks
+#[target_feature(enable = "aes,sse2")]
+unsafe fn inv_cipher_blocks(blocks: &mut [[u8; BLOCK_LEN]], drk: &[__m128i]) {
+    let n_round = drk.len() - 1;
+
+    let mut chunks = blocks.chunks_exact_mut(PAR_BLOCKS);
+
+    for chunk in &mut chunks {
+        let mut state = [_mm_setzero_si128(); PAR_BLOCKS];
+
+        for (s, block) in state.iter_mut().zip(chunk.iter()) {
+            *s = _mm_xor_si128(_mm_loadu_si128(block.as_ptr() as *const __m128i), drk[n_round]);
+        }
+
+        for round_key in drk[1..n_round].iter().rev() {
+            for s in state.iter_mut() {
+                *s = _mm_aesdec_si128(*s, *round_key);
+            }
+        }
+
+        for (s, block) in state.iter().zip(chunk.iter_mut()) {
+            _mm_storeu_si128(block.as_mut_ptr() as *mut __m128i, _mm_aesdeclast_si128(*s, drk[0]));
+        }
+    }
+
+    for block in chunks.into_remainder().iter_mut() {
+        *block = inv_cipher(block, drk);
+    }
+}
+
+// KeyExpansion (see aes::expand_key), using AESKEYGENASSIST for SubWord and RotWord
+//
+// Branches only depend on the (public) word index
+#[target_feature(enable = "aes,sse2")]
+unsafe fn expand_key(key: &[u8], w: &mut [u32]) {
+    let n_key = key.len() / 4;
+
+    for (word, chunk) in w.iter_mut().zip(key.chunks_exact(4)) {
+        *word = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+
+    for i in n_key..w.len() {
+        let mut temp = w[i - 1];
+        if i % n_key == 0 {
+            temp = sub_rot_word(temp) ^ aes::round_const(i / n_key);
+        } else if n_key > 6 && i % n_key == 4 {
+            temp = sub_word(temp);
+        }
+        w[i] = w[i - n_key] ^ temp;
+    }
+}
+
+// SubWord(word) using AESKEYGENASSIST
+#[target_feature(enable = "aes,sse2")]
+unsafe fn sub_word(word: u32) -> u32 {
+    key_assist(word)[0]
+}
+
+// SubWord(RotWord(word)) using AESKEYGENASSIST (with a zero round constant)
+#[target_feature(enable = "aes,sse2")]
+unsafe fn sub_rot_word(word: u32) -> u32 {
+    key_assist(word)[1]
+}
+
+// Returns [SubWord(word), RotWord(SubWord(word))]
+//
+// AESKEYGENASSIST treats dwords as little-endian, so swap bytes to match the FIPS-197 word order
+#[target_feature(enable = "aes,sse2")]
+unsafe fn key_assist(word: u32) -> [u32; 2] {
+    let input = _mm_set_epi32(0, 0, word.swap_bytes() as i32, 0);
+    let assist = _mm_aeskeygenassist_si128(input, 0);
+
+    let mut out = [0_u32; N_BLOCK];
+    _mm_storeu_si128(out.as_mut_ptr() as *mut __m128i, assist);
+
+    [out[0].swap_bytes(), out[1].swap_bytes()]
+}
+
+// Load the expanded key words into encryption round keys,
+// and derive the Equivalent Inverse Cipher round keys using AESIMC
+#[target_feature(enable = "aes,sse2")]
+unsafe fn load_round_keys(w: &[u32], rk: &mut [__m128i], drk: &mut [__m128i]) {
+    let n_round = rk.len() - 1;
+
+    for (round_key, words) in rk.iter_mut().zip(w.chunks_exact(N_BLOCK)) {
+        let block = aes::state_to_block(&[words[0], words[1], words[2], words[3]]);
+        *round_key = _mm_loadu_si128(block.as_ptr() as *const __m128i);
+    }
+
+    drk[0] = rk[0];
+    drk[n_round] = rk[n_round];
+
+    for (dec_key, round_key) in drk[1..n_round].iter_mut().zip(rk[1..n_round].iter()) {
+        *dec_key = _mm_aesimc_si128(*round_key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryInto;
+
+    #[test]
+    fn check_key_expand() {
+        if !is_supported() {
+            return;
+        }
+
+        let key_128 = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+        let key_256 = [
+            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d, 0x77, 0x81,
+            0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3, 0x09, 0x14, 0xdf, 0xf4,
+        ];
+
+        let mut w_128 = [0_u32; aes::N_EXPW_128];
+        let mut w_192 = [0_u32; aes::N_EXPW_192];
+        let mut w_256 = [0_u32; aes::N_EXPW_256];
+
+        unsafe {
+            expand_key(&key_128, &mut w_128);
+            expand_key(&key_256[..aes::KEY_LEN_192], &mut w_192);
+            expand_key(&key_256, &mut w_256);
+        }
+
+        assert_eq!(w_128, aes::expand_key_128(&key_128));
+        assert_eq!(w_192[..], aes::expand_key_192(key_256[..aes::KEY_LEN_192].try_into().unwrap())[..]);
+        assert_eq!(w_256[..], aes::expand_key_256(&key_256)[..]);
+    }
+
+    #[test]
+    fn check_aes_128() {
+        let msg = [0x32, 0x43, 0xf6, 0xa8, 0x88, 0x5a, 0x30, 0x8d, 0x31, 0x31, 0x98, 0xa2, 0xe0, 0x37, 0x07, 0x34];
+        let key = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+        let exp_out = [0x39, 0x25, 0x84, 0x1d, 0x02, 0xdc, 0x09, 0xfb, 0xdc, 0x11, 0x85, 0x97, 0x19, 0x6a, 0x0b, 0x32];
+
+        let aes = match Aes128::new(&key) {
+            Some(aes) => aes,
+            None => return,
+        };
+
+        assert_eq!(aes.encrypt_block(&msg), exp_out);
+        assert_eq!(aes.decrypt_block(&exp_out), msg);
+    }
+
+    #[test]
+    fn check_aes_192() {
+        let msg = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+        let key = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,
+            0x0c, 0x0d, 0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17,
+        ];
+        let exp_out = [0xdd, 0xa9, 0x7c, 0xa4, 0x86, 0x4c, 0xdf, 0xe0, 0x6e, 0xaf, 0x70, 0xa0, 0xec, 0x0d, 0x71, 0x91];
+
+        let aes = match Aes192::new(&key) {
+            Some(aes) => aes,
+            None => return,
+        };
+
+        assert_eq!(aes.encrypt_block(&msg), exp_out);
+        assert_eq!(aes.decrypt_block(&exp_out), msg);
+    }
+
+    #[test]
+    fn check_aes_256() {
+        let msg = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+        let key = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+            0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+        ];
+        let exp_out = [0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49, 0x60, 0x89];
+
+        let aes = match Aes256::new(&key) {
+            Some(aes) => aes,
+            None => return,
+        };
+
+        assert_eq!(aes.encrypt_block(&msg), exp_out);
+        assert_eq!(aes.decrypt_block(&exp_out), msg);
+    }
+
+    #[test]
+    fn check_blocks() {
+        let key = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+
+        let aes = match Aes128::new(&key) {
+            Some(aes) => aes,
+            None => return,
+        };
+
+        // cover full interleaved chunks, and a partial remainder
+        let mut blocks = [[0_u8; BLOCK_LEN]; 2 * PAR_BLOCKS + 3];
+        for (i, block) in blocks.iter_mut().enumerate() {
+            *block = [i as u8; BLOCK_LEN];
+        }
+
+        let mut cipher_blocks = blocks;
+        aes.encrypt_blocks(&mut cipher_blocks);
+
+        for (block, cipher_block) in blocks.iter().zip(cipher_blocks.iter()) {
+            assert_eq!(aes.encrypt_block(block), *cipher_block);
+        }
+
+        aes.decrypt_blocks(&mut cipher_blocks);
+        assert_eq!(cipher_blocks, blocks);
+    }
+}
@@ -0,0 +1,631 @@
+//! Bitsliced AES implementation
+//!
+//! Two blocks are processed in parallel, with the state held as eight 32-bit bit-planes:
+//!
+//! plane[b] bit (16 * blk + i) = bit b of byte i in block blk
+//!
+//! Every operation (including the S-box) is computed with bitwise logic over whole planes,
+//! so no memory access or branch depends on secret data.
+
+use crate::aes::{self, BLOCK_LEN, N_BLOCK};
+use crate::BlockCipher;
+
+/// Number of blocks processed in parallel
+pub const N_PARALLEL: usize = 2;
+
+/// Number of bit-planes in the bitsliced state
+const N_PLANE: usize = 8;
+
+/// Bitsliced state for two blocks
+type State = [u32; N_PLANE];
+
+// Byte index in the block for column c, row r
+const fn byte_index(c: usize, r: usize) -> usize {
+    c * N_BLOCK + r
+}
+
+// ShiftRows: s'[r, c] = s[r, (c + r) mod 4]
+const SHIFT_ROWS: [usize; BLOCK_LEN] = byte_map(0, 1);
+
+// InvShiftRows: s'[r, c] = s[r, (c - r) mod 4]
+const INV_SHIFT_ROWS: [usize; BLOCK_LEN] = byte_map(0, 3);
+
+// Rotate each column up by one row: s'[r, c] = s[(r + 1) mod 4, c]
+const ROT_COL_1: [usize; BLOCK_LEN] = byte_map(1, 0);
+
+// Rotate each column up by two rows: s'[r, c] = s[(r + 2) mod 4, c]
+const ROT_COL_2: [usize; BLOCK_LEN] = byte_map(2, 0);
+
+// Rotate each column up by three rows: s'[r, c] = s[(r + 3) mod 4, c]
+const ROT_COL_3: [usize; BLOCK_LEN] = byte_map(3, 0);
+
+// Build a byte permutation where s'[r, c] = s[(r + row_shift) mod 4, (c + r * col_shift) mod 4]
+const fn byte_map(row_shift: usize, col_shift: usize) -> [usize; BLOCK_LEN] {
+    let mut map = [0_usize; BLOCK_LEN];
+    let mut c = 0;
+    while c < N_BLOCK {
+        let mut r = 0;
+        while r < N_BLOCK {
+            map[byte_index(c, r)] = byte_index((c + r * col_shift) % N_BLOCK, (r + row_shift) % N_BLOCK);
+            r += 1;
+        }
+        c += 1;
+    }
+    map
+}
+
+/// Bitsliced AES-128 block cipher
+#[derive(Clone)]
+pub struct Aes128 {
+    rk: [State; aes::N_ROUND_128 + 1],
+    drk: [State; aes::N_ROUND_128 + 1],
+}
+
+impl Aes128 {
+    /// Create a bitsliced AES-128 cipher from the given key
+    pub fn new(key: &[u8; aes::KEY_LEN_128]) -> Self {
+        let w = aes::expand_key_128(key);
+        let mut dw = [0_u32; aes::N_EXPW_128];
+        aes::expand_dec_key(&w, &mut dw);
+
+        let mut rk = [[0_u32; N_PLANE]; aes::N_ROUND_128 + 1];
+        let mut drk = [[0_u32; N_PLANE]; aes::N_ROUND_128 + 1];
+        pack_round_keys(&w, &mut rk);
+        pack_round_keys(&dw, &mut drk);
+
+        Self { rk, drk }
+    }
+}
+
+impl BlockCipher for Aes128 {
+    fn encrypt_block(&self, msg: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        let mut blocks = [*msg; N_PARALLEL];
+        self.encrypt_blocks(&mut blocks);
+        blocks[0]
+    }
+
+    fn decrypt_block(&self, cipher: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        let mut blocks = [*cipher; N_PARALLEL];
+        self.decrypt_blocks(&mut blocks);
+        blocks[0]
+    }
+
+    fn encrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        encrypt_blocks_inner(blocks, &self.rk);
+    }
+
+    fn decrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        decrypt_blocks_inner(blocks, &self.drk);
+    }
+}
+
+/// Bitsliced AES-192 block cipher
+#[derive(Clone)]
+pub struct Aes192 {
+    rk: [State; aes::N_ROUND_192 + 1],
+    drk: [State; aes::N_ROUND_192 + 1],
+}
+
+impl Aes192 {
+    /// Create a bitsliced AES-192 cipher from the given key
+    pub fn new(key: &[u8; aes::KEY_LEN_192]) -> Self {
+        let w = aes::expand_key_192(key);
+        let mut dw = [0_u32; aes::N_EXPW_192];
+        aes::expand_dec_key(&w, &mut dw);
+
+        let mut rk = [[0_u32; N_PLANE]; aes::N_ROUND_192 + 1];
+        let mut drk = [[0_u32; N_PLANE]; aes::N_ROUND_192 + 1];
+        pack_round_keys(&w, &mut rk);
+        pack_round_keys(&dw, &mut drk);
+
+        Self { rk, drk }
+    }
+}
+
+impl BlockCipher for Aes192 {
+    fn encrypt_block(&self, msg: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        let mut blocks = [*msg; N_PARALLEL];
+        self.encrypt_blocks(&mut blocks);
+        blocks[0]
+    }
+
+    fn decrypt_block(&self, cipher: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        let mut blocks = [*cipher; N_PARALLEL];
+        self.decrypt_blocks(&mut blocks);
+        blocks[0]
+    }
+
+    fn encrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        encrypt_blocks_inner(blocks, &self.rk);
+    }
+
+    fn decrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        decrypt_blocks_inner(blocks, &self.drk);
+    }
+}
+
+/// Bitsliced AES-256 block cipher
+#[derive(Clone)]
+pub struct Aes256 {
+    rk: [State; aes::N_ROUND_256 + 1],
+    drk: [State; aes::N_ROUND_256 + 1],
+}
+
+impl Aes256 {
+    /// Create a bitsliced AES-256 cipher from the given key
+    pub fn new(key: &[u8; aes::KEY_LEN_256]) -> Self {
+        let w = aes::expand_key_256(key);
+        let mut dw = [0_u32; aes::N_EXPW_256];
+        aes::expand_dec_key(&w, &mut dw);
+
+        let mut rk = [[0_u32; N_PLANE]; aes::N_ROUND_256 + 1];
+        let mut drk = [[0_u32; N_PLANE]; aes::N_ROUND_256 + 1];
+        pack_round_keys(&w, &mut rk);
+        pack_round_keys(&dw, &mut drk);
+
+        Self { rk, drk }
+    }
+}
+
+impl BlockCipher for Aes256 {
+    fn encrypt_block(&self, msg: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        let mut blocks = [*msg; N_PARALLEL];
+        self.encrypt_blocks(&mut blocks);
+        blocks[0]
+    }
+
+    fn decrypt_block(&self, cipher: &[u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        let mut blocks = [*cipher; N_PARALLEL];
+        self.decrypt_blocks(&mut blocks);
+        blocks[0]
+    }
+
+    fn encrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        encrypt_blocks_inner(blocks, &self.rk);
+    }
+
+    fn decrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        decrypt_blocks_inner(blocks, &self.drk);
+    }
+}
+
+/// Bitsliced AES-128 cipher over a single block
+///
+/// @param msg: Message block to be encrypted
+/// @param key: Key for encrypting the message
+pub fn aes_128(msg: &[u8; BLOCK_LEN], key: &[u8; aes::KEY_LEN_128]) -> [u8; BLOCK_LEN] {
+    Aes128::new(key).encrypt_block(msg)
+}
+
+/// Bitsliced AES-128 inverse cipher over a single block
+///
+/// @param cipher: Cipher block to be decrypted
+/// @param key: Key for decrypting the cipher block
+pub fn aes_inv_128(cipher: &[u8; BLOCK_LEN], key: &[u8; aes::KEY_LEN_128]) -> [u8; BLOCK_LEN] {
+    Aes128::new(key).decrypt_block(cipher)
+}
+
+/// Bitsliced AES-192 cipher over a single block
+///
+/// @param msg: Message block to be encrypted
+/// @param key: Key for encrypting the message
+pub fn aes_192(msg: &[u8; BLOCK_LEN], key: &[u8; aes::KEY_LEN_192]) -> [u8; BLOCK_LEN] {
+    Aes192::new(key).encrypt_block(msg)
+}
+
+/// Bitsliced AES-192 inverse cipher over a single block
+///
+/// @param cipher: Cipher block to be decrypted
+/// @param key: Key for decrypting the cipher block
+pub fn aes_inv_192(cipher: &[u8; BLOCK_LEN], key: &[u8; aes::KEY_LEN_192]) -> [u8; BLOCK_LEN] {
+    Aes192::new(key).decrypt_block(cipher)
+}
+
+/// Bitsliced AES-256 cipher over a single block
+///
+/// @param msg: Message block to be encrypted
+/// @param key: Key for encrypting the message
+pub fn aes_256(msg: &[u8; BLOCK_LEN], key: &[u8; aes::KEY_LEN_256]) -> [u8; BLOCK_LEN] {
+    Aes256::new(key).encrypt_block(msg)
+}
+
+/// Bitsliced AES-256 inverse cipher over a single block
+///
+/// @param cipher: Cipher block to be decrypted
+/// @param key: Key for decrypting the cipher block
+pub fn aes_inv_256(cipher: &[u8; BLOCK_LEN], key: &[u8; aes::KEY_LEN_256]) -> [u8; BLOCK_LEN] {
+    Aes256::new(key).decrypt_block(cipher)
+}
+
+// Encrypt blocks two at a time, padding an odd final block with a copy of itself
+fn encrypt_blocks_inner(blocks: &mut [[u8; BLOCK_LEN]], rk: &[State]) {
+    for pair in blocks.chunks_mut(N_PARALLEL) {
+        let mut input = [pair[0]; N_PARALLEL];
+        input[..pair.len()].copy_from_slice(pair);
+
+        let mut state = pack(&input);
+        cipher(&mut state, rk);
+
+        let output = unpack(&state);
+        pair.copy_from_slice(&output[..pair.len()]);
+    }
+}
+
+// Decrypt blocks two at a time, padding an odd final block with a copy of itself
+fn decrypt_blocks_inner(blocks: &mut [[u8; BLOCK_LEN]], drk: &[State]) {
+    for pair in blocks.chunks_mut(N_PARALLEL) {
+        let mut input = [pair[0]; N_PARALLEL];
+        input[..pair.len()].copy_from_slice(pair);
+
+        let mut state = pack(&input);
+        inv_cipher(&mut state, drk);
+
+        let output = unpack(&state);
+        pair.copy_from_slice(&output[..pair.len()]);
+    }
+}
+
+// Cipher over a bitsliced state, Nr = rk.len() - 1
+fn cipher(state: &mut State, rk: &[State]) {
+    let n_round = rk.len() - 1;
+
+    add_round_key(state, &rk[0]);
+
+    for round_key in rk[1..n_round].iter() {
+        sub_bytes(state);
+        shift_rows(state);
+        mix_columns(state);
+        add_round_key(state, round_key);
+    }
+
+    sub_bytes(state);
+    shift_rows(state);
+    add_round_key(state, &rk[n_round]);
+}
+
+// Equivalent Inverse Cipher over a bitsliced state, Nr = drk.len() - 1
+fn inv_cipher(state: &mut State, drk: &[State]) {
+    let n_round = drk.len() - 1;
+
+    add_round_key(state, &drk[n_round]);
+
+    for round_key in drk[1..n_round].iter().rev() {
+        inv_sub_bytes(state);
+        inv_shift_rows(state);
+        inv_mix_columns(state);
+        add_round_key(state, round_key);
+    }
+
+    inv_sub_bytes(state);
+    inv_shift_rows(state);
+    add_round_key(state, &drk[0]);
+}
+
+// Bitslice each round key of an expanded key, duplicated for both blocks
+fn pack_round_keys(w: &[u32], rk: &mut [State]) {
+    for (round_key, words) in rk.iter_mut().zip(w.chunks_exact(N_BLOCK)) {
+        let block = aes::state_to_block(&[words[0], words[1], words[2], words[3]]);
+        *round_key = pack(&[block; N_PARALLEL]);
+    }
+}
+
+// Transpose blocks into bit-planes
+fn pack(blocks: &[[u8; BLOCK_LEN]; N_PARALLEL]) -> State {
+    let mut state = [0_u32; N_PLANE];
+
+    for (blk, block) in blocks.iter().enumerate() {
+        for (i, &byte) in block.iter().enumerate() {
+            for (b, plane) in state.iter_mut().enumerate() {
+                *plane |= (((byte >> b) & 1) as u32) << (blk * BLOCK_LEN + i);
+            }
+        }
+    }
+
+    state
+}
+
+// Transpose bit-planes back into blocks
+fn unpack(state: &State) -> [[u8; BLOCK_LEN]; N_PARALLEL] {
+    let mut blocks = [[0_u8; BLOCK_LEN]; N_PARALLEL];
+
+    for (blk, block) in blocks.iter_mut().enumerate() {
+        for (i, byte) in block.iter_mut().enumerate() {
+            for (b, plane) in state.iter().enumerate() {
+                *byte |= (((plane >> (blk * BLOCK_LEN + i)) & 1) as u8) << b;
+            }
+        }
+    }
+
+    blocks
+}
+
+// Add round key planes to state planes using XOR
+fn add_round_key(state: &mut State, round_key: &State) {
+    xor_planes(state, round_key);
+}
+
+// XOR bit-planes into the state
+fn xor_planes(state: &mut State, other: &State) {
+    for (plane, other_plane) in state.iter_mut().zip(other.iter()) {
+        *plane ^= *other_plane;
+    }
+}
+
+// Apply the S-Box to every byte of the state
+//
+// Multiplicative inverse in GF(2^8) followed by the affine transform (see aes::sbox)
+fn sub_bytes(state: &mut State) {
+    let inv = gf_inv(state);
+
+    // b'_i = b_i ^ b_(i+4) ^ b_(i+5) ^ b_(i+6) ^ b_(i+7) ^ c_i, c = 0x63
+    for (i, plane) in state.iter_mut().enumerate() {
+        *plane = inv[i]
+            ^ inv[(i + 4) % N_PLANE]
+            ^ inv[(i + 5) % N_PLANE]
+            ^ inv[(i + 6) % N_PLANE]
+            ^ inv[(i + 7) % N_PLANE]
+            ^ const_plane(0x63, i);
+    }
+}
+
+// Apply the inverse S-Box to every byte of the state
+//
+// Inverse affine transform followed by the multiplicative inverse in GF(2^8)
+fn inv_sub_bytes(state: &mut State) {
+    let mut affine = [0_u32; N_PLANE];
+
+    // b_i = b'_(i+7) ^ b'_(i+5) ^ b'_(i+2) ^ d_i, d = 0x05
+    for (i, plane) in affine.iter_mut().enumerate() {
+        *plane = state[(i + 7) % N_PLANE]
+            ^ state[(i + 5) % N_PLANE]
+            ^ state[(i + 2) % N_PLANE]
+            ^ const_plane(0x05, i);
+    }
+
+    *state = gf_inv(&affine);
+}
+
+// All-ones plane if bit i of the (public) constant is set, zero otherwise
+fn const_plane(c: u8, i: usize) -> u32 {
+    0_u32.wrapping_sub(((c >> i) & 1) as u32)
+}
+
+// Multiplicative inverse in GF(2^8) of every byte, computed as b^254 (see aes::gf_inv)
+fn gf_inv(b: &State) -> State {
+    let b2 = gf_mul(b, b);
+    let b3 = gf_mul(&b2, b);
+    let b6 = gf_mul(&b3, &b3);
+    let b12 = gf_mul(&b6, &b6);
+    let b15 = gf_mul(&b12, &b3);
+    let b30 = gf_mul(&b15, &b15);
+    let b60 = gf_mul(&b30, &b30);
+    let b120 = gf_mul(&b60, &b60);
+    let b240 = gf_mul(&b120, &b120);
+    let b252 = gf_mul(&b240, &b12);
+
+    gf_mul(&b252, &b2)
+}
+
+// Multiply every byte in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1
+fn gf_mul(a: &State, b: &State) -> State {
+    let mut prod = [0_u32; 2 * N_PLANE - 1];
+
+    for (i, a_plane) in a.iter().enumerate() {
+        for (j, b_plane) in b.iter().enumerate() {
+            prod[i + j] ^= a_plane & b_plane;
+        }
+    }
+
+    // reduce from the highest degree down: x^k = x^(k-4) + x^(k-5) + x^(k-7) + x^(k-8)
+    for k in (N_PLANE..2 * N_PLANE - 1).rev() {
+        let high = prod[k];
+        prod[k - 4] ^= high;
+        prod[k - 5] ^= high;
+        prod[k - 7] ^= high;
+        prod[k - 8] ^= high;
+    }
+
+    let mut out = [0_u32; N_PLANE];
+    out.copy_from_slice(&prod[..N_PLANE]);
+    out
+}
+
+// Multiply every byte by two modulo x^8 + x^4 + x^3 + x + 1
+fn xtime(a: &State) -> State {
+    [a[7], a[0] ^ a[7], a[1], a[2] ^ a[7], a[3] ^ a[7], a[4], a[5], a[6]]
+}
+
+// Move bytes within each block according to a (public) byte permutation
+fn permute(state: &State, map: &[usize; BLOCK_LEN]) -> State {
+    let mut out = [0_u32; N_PLANE];
+
+    for (plane, out_plane) in state.iter().zip(out.iter_mut()) {
+        for blk in 0..N_PARALLEL {
+            let base = blk * BLOCK_LEN;
+            for (i, &src) in map.iter().enumerate() {
+                *out_plane |= ((plane >> (base + src)) & 1) << (base + i);
+            }
+        }
+    }
+
+    out
+}
+
+// Shift state rows relative to row index
+fn shift_rows(state: &mut State) {
+    *state = permute(state, &SHIFT_ROWS);
+}
+
+// Inverse shift state rows relative to row index
+fn inv_shift_rows(state: &mut State) {
+    *state = permute(state, &INV_SHIFT_ROWS);
+}
+
+// s'_r = 0x02*s_r + 0x03*s_(r+1) + s_(r+2) + s_(r+3)
+//      = xtime(s_r + s_(r+1)) + s_(r+1) + s_(r+2) + s_(r+3)
+fn mix_columns(state: &mut State) {
+    let rot1 = permute(state, &ROT_COL_1);
+    let rot2 = permute(state, &ROT_COL_2);
+    let rot3 = permute(state, &ROT_COL_3);
+
+    let mut sum = *state;
+    xor_planes(&mut sum, &rot1);
+    let doubled = xtime(&sum);
+
+    for (i, plane) in state.iter_mut().enumerate() {
+        *plane = doubled[i] ^ rot1[i] ^ rot2[i] ^ rot3[i];
+    }
+}
+
+// InvMixColumns decomposed into a pre-multiplication followed by MixColumns:
+//
+// u = 0x04 * (s_r + s_(r+2))
+// s_r = s_r + u
+// MixColumns(s)
+fn inv_mix_columns(state: &mut State) {
+    let mut u = permute(state, &ROT_COL_2);
+    xor_planes(&mut u, state);
+    u = xtime(&xtime(&u));
+
+    xor_planes(state, &u);
+    mix_columns(state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two distinct blocks, so lanes cannot mask each other's errors
+    const BLOCKS: [[u8; BLOCK_LEN]; N_PARALLEL] = [
+        [0x32, 0x43, 0xf6, 0xa8, 0x88, 0x5a, 0x30, 0x8d, 0x31, 0x31, 0x98, 0xa2, 0xe0, 0x37, 0x07, 0x34],
+        [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff],
+    ];
+
+    // Apply a bitsliced round function, and compare with the reference round function on each block
+    fn check_round_fn(bitsliced: fn(&mut State), reference: fn(&mut [u32; N_BLOCK])) {
+        let mut state = pack(&BLOCKS);
+        bitsliced(&mut state);
+
+        for (block, out) in BLOCKS.iter().zip(unpack(&state).iter()) {
+            let mut exp_state = aes::block_to_state(block);
+            reference(&mut exp_state);
+            assert_eq!(*out, aes::state_to_block(&exp_state));
+        }
+    }
+
+    #[test]
+    fn check_pack() {
+        assert_eq!(unpack(&pack(&BLOCKS)), BLOCKS);
+    }
+
+    #[test]
+    fn check_sub_bytes() {
+        check_round_fn(sub_bytes, aes::sub_bytes);
+    }
+
+    #[test]
+    fn check_inv_sub_bytes() {
+        check_round_fn(inv_sub_bytes, aes::inv_sub_bytes);
+    }
+
+    #[test]
+    fn check_sbox_all_bytes() {
+        for i in (0..=0xff_u8).step_by(BLOCK_LEN) {
+            let mut blocks = [[0_u8; BLOCK_LEN]; N_PARALLEL];
+            for (j, byte) in blocks[0].iter_mut().enumerate() {
+                *byte = i + j as u8;
+            }
+            blocks[1] = blocks[0];
+            blocks[1].reverse();
+
+            let mut state = pack(&blocks);
+            sub_bytes(&mut state);
+            let out = unpack(&state);
+
+            for (block, out_block) in blocks.iter().zip(out.iter()) {
+                let mut exp_state = aes::block_to_state(block);
+                aes::sub_bytes(&mut exp_state);
+                assert_eq!(*out_block, aes::state_to_block(&exp_state));
+            }
+
+            inv_sub_bytes(&mut state);
+            assert_eq!(unpack(&state), blocks);
+        }
+    }
+
+    #[test]
+    fn check_shift_rows() {
+        check_round_fn(shift_rows, aes::shift_rows);
+    }
+
+    #[test]
+    fn check_inv_shift_rows() {
+        check_round_fn(inv_shift_rows, aes::inv_shift_rows);
+    }
+
+    #[test]
+    fn check_mix_columns() {
+        check_round_fn(mix_columns, aes::mix_columns);
+    }
+
+    #[test]
+    fn check_inv_mix_columns() {
+        check_round_fn(inv_mix_columns, aes::inv_mix_columns);
+    }
+
+    #[test]
+    fn check_aes_128() {
+        let key = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+        let exp_out = [0x39, 0x25, 0x84, 0x1d, 0x02, 0xdc, 0x09, 0xfb, 0xdc, 0x11, 0x85, 0x97, 0x19, 0x6a, 0x0b, 0x32];
+
+        assert_eq!(aes_128(&BLOCKS[0], &key), exp_out);
+        assert_eq!(aes_inv_128(&exp_out, &key), BLOCKS[0]);
+    }
+
+    #[test]
+    fn check_aes_192() {
+        let key = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,
+            0x0c, 0x0d, 0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17,
+        ];
+        let exp_out = [0xdd, 0xa9, 0x7c, 0xa4, 0x86, 0x4c, 0xdf, 0xe0, 0x6e, 0xaf, 0x70, 0xa0, 0xec, 0x0d, 0x71, 0x91];
+
+        assert_eq!(aes_192(&BLOCKS[1], &key), exp_out);
+        assert_eq!(aes_inv_192(&exp_out, &key), BLOCKS[1]);
+    }
+
+    #[test]
+    fn check_aes_256() {
+        let key = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+            0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+        ];
+        let exp_out = [0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49, 0x60, 0x89];
+
+        assert_eq!(aes_256(&BLOCKS[1], &key), exp_out);
+        assert_eq!(aes_inv_256(&exp_out, &key), BLOCKS[1]);
+    }
+
+    #[test]
+    fn check_blocks_against_reference() {
+        let key = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+        let bitsliced = Aes128::new(&key);
+        let reference = aes::Aes128::new(&key);
+
+        // odd number of blocks exercises the padded final pair
+        let mut blocks = [[0_u8; BLOCK_LEN]; 5];
+        for (i, block) in blocks.iter_mut().enumerate() {
+            *block = [i as u8 * 0x11; BLOCK_LEN];
+            block[i] ^= 0x80;
+        }
+        let orig = blocks;
+
+        bitsliced.encrypt_blocks(&mut blocks);
+        for (block, msg) in blocks.iter().zip(orig.iter()) {
+            assert_eq!(*block, reference.encrypt_block(msg));
+        }
+
+        bitsliced.decrypt_blocks(&mut blocks);
+        assert_eq!(blocks, orig);
+    }
+}
@@ -1,18 +1,17 @@
 use alloc::vec::Vec;
-use core::convert::TryInto;
 
-use crate::{aes, xor};
-use crate::Error;
+use crate::{aes, to_blocks, xor_equals};
+use crate::{BlockCipher, Error};
 
-/// Initialization vector length for AES-128-CBC
+/// Initialization vector length for AES-CBC
 pub const IV_LEN: usize = 16_usize;
 
 /// Encrypt a message in CBC mode
 ///
 /// Message must be a multiple of the AES block length (16)
-pub fn encrypt(
+pub fn encrypt<C: BlockCipher>(
     msg: &[u8],
-    key: &[u8; aes::KEY_LEN_128],
+    cipher: &C,
     iv: &[u8; IV_LEN],
 ) -> Result<Vec<u8>, Error> {
     let len = msg.len();
@@ -22,90 +21,100 @@ pub fn encrypt(
 
     let mut res = Vec::with_capacity(len);
 
-    for (i, block) in msg.chunks_exact(aes::BLOCK_LEN).enumerate() {
-        if i == 0 {
-            // next cipher block:       AES-128( block_i ^ IV , key )
-            res.extend_from_slice(&aes::aes_128(
-                &xor(&block, iv.as_ref())?.as_slice().try_into().unwrap(),
-                &key,
-            ));
-        } else {
-            // next cipher block:       AES-128( block_i ^ cipher_block[i-1] , key )
-            res.extend_from_slice(&aes::aes_128(
-                &xor(&block, &res[aes::BLOCK_LEN * (i - 1)..aes::BLOCK_LEN * i])?
-                    .as_slice()
-                    .try_into()
-                    .unwrap(),
-                &key,
-            ));
-        }
-    }
+    chain(msg, cipher, iv, |block| res.extend_from_slice(block));
 
     Ok(res)
 }
 
+// Run the CBC chain over whole blocks, passing each cipher block to `out`
+//
+// Returns the final cipher block (or the IV for an empty message)
+pub(crate) fn chain<C: BlockCipher, F: FnMut(&[u8; aes::BLOCK_LEN])>(
+    msg: &[u8],
+    cipher: &C,
+    iv: &[u8; IV_LEN],
+    mut out: F,
+) -> [u8; aes::BLOCK_LEN] {
+    let mut prev = *iv;
+
+    for block in msg.chunks_exact(aes::BLOCK_LEN) {
+        // next cipher block:       CIPHER( block_i ^ IV , key )                  i = 0
+        // next cipher block:       CIPHER( block_i ^ cipher_block[i-1] , key )   i > 0
+        // blocks guaranteed to be the same length, safe to just unwrap here
+        xor_equals(&mut prev, block).unwrap();
+        prev = cipher.encrypt_block(&prev);
+        out(&prev);
+    }
+
+    prev
+}
+
 /// Decrypt a message in CBC mode
 ///
 /// Message must be a multiple of the AES block length (16)
-pub fn decrypt(
-    cipher: &[u8],
-    key: &[u8; aes::KEY_LEN_128],
+pub fn decrypt<C: BlockCipher>(
+    ciphertext: &[u8],
+    cipher: &C,
     iv: &[u8; IV_LEN],
 ) -> Result<Vec<u8>, Error> {
-    let len = cipher.len();
+    let len = ciphertext.len();
     if len % aes::BLOCK_LEN != 0 {
         return Err(Error::InvalidLength);
     }
 
-    let mut res = Vec::with_capacity(len);
+    // blocks are independent before chaining, decrypt them all at once
+    let mut blocks = to_blocks(ciphertext);
+    cipher.decrypt_blocks(&mut blocks);
 
-    for (i, block) in cipher.chunks_exact(aes::BLOCK_LEN).enumerate() {
+    for (i, block) in blocks.iter_mut().enumerate() {
         if i == 0 {
-            // next plaintext block:       AES-INV-128( block_i , key ) ^ IV 
-            res.extend_from_slice(&xor(
-                &aes::aes_inv_128(&block.try_into().unwrap(), key),
-                iv.as_ref(),
-            )?);
+            // next plaintext block:       CIPHER-INV( block_i , key ) ^ IV
+            xor_equals(block, iv.as_ref())?;
         } else {
-            // next plaintext block:       AES-INV-128( block_i , key ) ^ cipher_block[i-1]
-            res.extend_from_slice(&xor(
-                &aes::aes_inv_128(&block.try_into().unwrap(), key),
-                &cipher[aes::BLOCK_LEN * (i - 1)..aes::BLOCK_LEN * i],
-            )?);
+            // next plaintext block:       CIPHER-INV( block_i , key ) ^ cipher_block[i-1]
+            xor_equals(block, &ciphertext[aes::BLOCK_LEN * (i - 1)..aes::BLOCK_LEN * i])?;
         }
     }
 
-    Ok(res)
+    Ok(blocks.concat())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::xor;
+    use core::convert::TryInto;
 
     #[test]
     fn check_cbc_encrypt() {
         let txt = b"This block good! This block good";
         let key = b"YELLOW SUBMARINE";
-        let iv = [0_u8; IV_LEN];
 
-        assert!(encrypt(txt.as_ref(), &key, &iv).is_ok());
+        for aes in [aes::Aes128::new_portable(key), aes::Aes128::new(key)].iter() {
+            let iv = [0_u8; IV_LEN];
+
+            assert!(encrypt(txt.as_ref(), aes, &iv).is_ok());
 
-        // use an invalid length plaintext, ensure error is returned
-        assert!(encrypt(&txt[1..], &key, &iv).is_err());
+            // use an invalid length plaintext, ensure error is returned
+            assert!(encrypt(&txt[1..], aes, &iv).is_err());
+        }
     }
 
     #[test]
     fn check_cbc_decrypt() {
         let txt = b"This block good! This block good";
         let key = b"YELLOW SUBMARINE";
-        let iv = [0_u8; IV_LEN];
 
-        let cipher = encrypt(txt.as_ref(), &key, &iv);
-        assert!(cipher.is_ok());
+        for aes in [aes::Aes128::new_portable(key), aes::Aes128::new(key)].iter() {
+            let iv = [0_u8; IV_LEN];
 
-        let pt = decrypt(&cipher.unwrap(), &key, &iv);
-        assert!(pt.is_ok());
-        assert_eq!(pt.unwrap()[..], txt[..]);
+            let cipher = encrypt(txt.as_ref(), aes, &iv);
+            assert!(cipher.is_ok());
+
+            let pt = decrypt(&cipher.unwrap(), aes, &iv);
+            assert!(pt.is_ok());
+            assert_eq!(pt.unwrap()[..], txt[..]);
+        }
     }
 
     // NIST test vectors from RFC 3602: https://tools.ietf.org/html/rfc3602
@@ -125,11 +134,13 @@ mod tests {
             0x18, 0x1a,
         ];
 
-        let cipher = encrypt(msg.as_ref(), &key, &iv).unwrap();
-        assert_eq!(cipher.as_slice(), exp_cipher.as_ref());
+        for aes in [aes::Aes128::new_portable(&key), aes::Aes128::new(&key)].iter() {
+            let cipher = encrypt(msg.as_ref(), aes, &iv).unwrap();
+            assert_eq!(cipher.as_slice(), exp_cipher.as_ref());
 
-        let plaintext = decrypt(&cipher, &key, &iv).unwrap();
-        assert_eq!(plaintext.as_slice(), msg.as_ref());
+            let plaintext = decrypt(&cipher, aes, &iv).unwrap();
+            assert_eq!(plaintext.as_slice(), msg.as_ref());
+        }
     }
 
     // NIST test vectors from RFC 3602: https://tools.ietf.org/html/rfc3602
@@ -154,11 +165,13 @@ mod tests {
             0xa6, 0xd6, 0x1a, 0xb1,
         ];
 
-        let cipher = encrypt(msg.as_ref(), &key, &iv).unwrap();
-        assert_eq!(cipher.as_slice(), exp_cipher.as_ref());
+        for aes in [aes::Aes128::new_portable(&key), aes::Aes128::new(&key)].iter() {
+            let cipher = encrypt(msg.as_ref(), aes, &iv).unwrap();
+            assert_eq!(cipher.as_slice(), exp_cipher.as_ref());
 
-        let plaintext = decrypt(&cipher, &key, &iv).unwrap();
-        assert_eq!(plaintext.as_slice(), msg.as_ref());
+            let plaintext = decrypt(&cipher, aes, &iv).unwrap();
+            assert_eq!(plaintext.as_slice(), msg.as_ref());
+        }
     }
 
     // NIST test vectors from RFC 3602: https://tools.ietf.org/html/rfc3602
@@ -180,11 +193,13 @@ mod tests {
             0x69, 0x5d, 0x83, 0xba, 0x26, 0x84,
         ];
 
-        let cipher = encrypt(msg.as_ref(), &key, &iv).unwrap();
-        assert_eq!(cipher.as_slice(), exp_cipher.as_ref());
+        for aes in [aes::Aes128::new_portable(&key), aes::Aes128::new(&key)].iter() {
+            let cipher = encrypt(msg.as_ref(), aes, &iv).unwrap();
+            assert_eq!(cipher.as_slice(), exp_cipher.as_ref());
 
-        let plaintext = decrypt(&cipher, &key, &iv).unwrap();
-        assert_eq!(plaintext.as_slice(), msg.as_ref());
+            let plaintext = decrypt(&cipher, aes, &iv).unwrap();
+            assert_eq!(plaintext.as_slice(), msg.as_ref());
+        }
     }
 
     // NIST test vectors from RFC 3602: https://tools.ietf.org/html/rfc3602
@@ -213,10 +228,69 @@ mod tests {
             0x49, 0xa5, 0x3e, 0x87, 0xf4, 0xc3, 0xda, 0x55,
         ];
 
-        let cipher = encrypt(msg.as_ref(), &key, &iv).unwrap();
-        assert_eq!(cipher.as_slice(), exp_cipher.as_ref());
+        for aes in [aes::Aes128::new_portable(&key), aes::Aes128::new(&key)].iter() {
+            let cipher = encrypt(msg.as_ref(), aes, &iv).unwrap();
+            assert_eq!(cipher.as_slice(), exp_cipher.as_ref());
+
+            let plaintext = decrypt(&cipher, aes, &iv).unwrap();
+            assert_eq!(plaintext.as_slice(), msg.as_ref());
+        }
+    }
+
+    // NIST SP 800-38A F.2.5: CBC-AES256.Encrypt
+    #[test]
+    fn nist_test_vector_aes_256() {
+        let key = [
+            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d,
+            0x77, 0x81, 0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3,
+            0x09, 0x14, 0xdf, 0xf4,
+        ];
+        let iv = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f,
+        ];
+        let msg = [
+            0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93,
+            0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac,
+            0x45, 0xaf, 0x8e, 0x51,
+        ];
+        let exp_cipher = [
+            0xf5, 0x8c, 0x4c, 0x04, 0xd6, 0xe5, 0xf1, 0xba, 0x77, 0x9e, 0xab, 0xfb, 0x5f, 0x7b,
+            0xfb, 0xd6, 0x9c, 0xfc, 0x4e, 0x96, 0x7e, 0xdb, 0x80, 0x8d, 0x67, 0x9f, 0x77, 0x7b,
+            0xc6, 0x70, 0x2c, 0x7d,
+        ];
+
+        for aes in [aes::Aes256::new_portable(&key), aes::Aes256::new(&key)].iter() {
+            let cipher = encrypt(msg.as_ref(), aes, &iv).unwrap();
+            assert_eq!(cipher.as_slice(), exp_cipher.as_ref());
 
-        let plaintext = decrypt(&cipher, &key, &iv).unwrap();
-        assert_eq!(plaintext.as_slice(), msg.as_ref());
+            let plaintext = decrypt(&cipher, aes, &iv).unwrap();
+            assert_eq!(plaintext.as_slice(), msg.as_ref());
+        }
+    }
+
+    #[test]
+    fn check_multi_block_decrypt() {
+        let key = b"YELLOW SUBMARINE";
+        let iv = [0x42_u8; IV_LEN];
+
+        let mut msg = [0_u8; aes::BLOCK_LEN * 19];
+        for (i, byte) in msg.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+
+        for aes in [aes::Aes128::new_portable(key), aes::Aes128::new(key)].iter() {
+            let cipher = encrypt(msg.as_ref(), aes, &iv).unwrap();
+
+            // chaining is undone after decrypting all blocks together
+            let mut prev = iv;
+            for (block, cipher_block) in msg.chunks_exact(aes::BLOCK_LEN).zip(cipher.chunks_exact(aes::BLOCK_LEN)) {
+                let pt = xor(&aes.decrypt_block(cipher_block.try_into().unwrap()), &prev).unwrap();
+                assert_eq!(pt, block);
+                prev.copy_from_slice(cipher_block);
+            }
+
+            assert_eq!(decrypt(&cipher, aes, &iv).unwrap(), msg[..]);
+        }
     }
 }
@@ -0,0 +1,239 @@
+//! CBC with ciphertext stealing (Addendum to NIST SP 800-38A: CBC-CS1, CBC-CS2, CBC-CS3)
+//!
+//! The final partial plaintext block is zero padded and CBC encrypted,
+//! then the unused bytes of the penultimate cipher block are dropped,
+//! so the ciphertext is the same length as the plaintext.
+//!
+//! The variants only differ in the order of the final two cipher blocks:
+//!
+//! - CS1: penultimate (partial) block, then the final block
+//! - CS2: same as CS1 for complete messages, otherwise the same as CS3
+//! - CS3: final block, then the penultimate (partial) block, always swapped (Kerberos, RFC 3962)
+
+use alloc::vec::Vec;
+
+use crate::cbc::{self, IV_LEN};
+use crate::{aes, xor, xor_equals, BlockCipher, Error};
+
+/// Minimum message length: one full block
+pub const MIN_MSG_LEN: usize = aes::BLOCK_LEN;
+
+/// Ciphertext stealing variant
+#[derive(Debug, PartialEq)]
+pub enum Variant {
+    Cs1,
+    Cs2,
+    Cs3,
+}
+
+/// Encrypt a message in CBC mode with ciphertext stealing
+///
+/// Message must be at least one AES block long (16), but otherwise any length
+pub fn encrypt<C: BlockCipher>(
+    msg: &[u8],
+    cipher: &C,
+    iv: &[u8; IV_LEN],
+    variant: &Variant,
+) -> Result<Vec<u8>, Error> {
+    let len = msg.len();
+    if len < MIN_MSG_LEN {
+        return Err(Error::InvalidLength);
+    }
+
+    let (split, d) = last_block(len);
+
+    let mut res = Vec::with_capacity(len + aes::BLOCK_LEN);
+
+    // C_1 ... C_n-1 are plain CBC
+    let prev = cbc::chain(&msg[..split], cipher, iv, |block| res.extend_from_slice(block));
+
+    // C_n = CIPHER( ( P_n* || 0^(b-d) ) ^ C_n-1 , key )
+    let mut last = [0_u8; aes::BLOCK_LEN];
+    last[..d].copy_from_slice(&msg[split..]);
+    xor_equals(&mut last, &prev)?;
+    last = cipher.encrypt_block(&last);
+
+    // a single block message has nothing to steal from
+    if split == 0 {
+        res.extend_from_slice(&last);
+        return Ok(res);
+    }
+
+    // C_n-1* = MSB_d( C_n-1 )
+    let stolen = res.split_off(split - aes::BLOCK_LEN);
+
+    if is_swapped(variant, d) {
+        res.extend_from_slice(&last);
+        res.extend_from_slice(&stolen[..d]);
+    } else {
+        res.extend_from_slice(&stolen[..d]);
+        res.extend_from_slice(&last);
+    }
+
+    Ok(res)
+}
+
+/// Decrypt a message in CBC mode with ciphertext stealing
+///
+/// Ciphertext must be at least one AES block long (16), but otherwise any length
+pub fn decrypt<C: BlockCipher>(
+    ciphertext: &[u8],
+    cipher: &C,
+    iv: &[u8; IV_LEN],
+    variant: &Variant,
+) -> Result<Vec<u8>, Error> {
+    let len = ciphertext.len();
+    if len < MIN_MSG_LEN {
+        return Err(Error::InvalidLength);
+    }
+
+    let (split, d) = last_block(len);
+
+    if split == 0 {
+        return cbc::decrypt(ciphertext, cipher, iv);
+    }
+
+    let head_len = split - aes::BLOCK_LEN;
+    let (head, tail) = ciphertext.split_at(head_len);
+
+    // put the final two blocks back into CS1 order: C_n-1* || C_n
+    let (stolen, last) = if is_swapped(variant, d) {
+        let (last, stolen) = tail.split_at(aes::BLOCK_LEN);
+        (stolen, last)
+    } else {
+        tail.split_at(d)
+    };
+
+    // Z = CIPHER-INV( C_n , key ) = C_n-1 ^ ( P_n* || 0^(b-d) )
+    let mut block = [0_u8; aes::BLOCK_LEN];
+    block.copy_from_slice(last);
+    let z = cipher.decrypt_block(&block);
+
+    // P_n* = MSB_d( Z ) ^ C_n-1*
+    // C_n-1 = C_n-1* || LSB_b-d( Z )
+    let last_plain = xor(&z[..d], stolen)?;
+
+    let mut chained = Vec::with_capacity(split);
+    chained.extend_from_slice(head);
+    chained.extend_from_slice(stolen);
+    chained.extend_from_slice(&z[d..]);
+
+    let mut res = cbc::decrypt(&chained, cipher, iv)?;
+    res.extend_from_slice(&last_plain);
+
+    Ok(res)
+}
+
+// Offset and length (1 to 16) of the final, possibly partial, block
+fn last_block(len: usize) -> (usize, usize) {
+    let split = (len - 1) / aes::BLOCK_LEN * aes::BLOCK_LEN;
+    (split, len - split)
+}
+
+// Whether the final two cipher blocks are swapped for the variant
+fn is_swapped(variant: &Variant, d: usize) -> bool {
+    match variant {
+        Variant::Cs1 => false,
+        Variant::Cs2 => d != aes::BLOCK_LEN,
+        Variant::Cs3 => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 3962 Appendix B: AES-128 key "chicken teriyaki", zero IV
+    const KEY: [u8; aes::KEY_LEN_128] = *b"chicken teriyaki";
+    const MSG: &[u8; 64] = b"I would like the General Gau's Chicken, please, and wonton soup.";
+
+    #[test]
+    fn rfc_test_vectors() {
+        let exp_ciphers: [&[u8]; 6] = [
+            &[
+                0xc6, 0x35, 0x35, 0x68, 0xf2, 0xbf, 0x8c, 0xb4, 0xd8, 0xa5, 0x80, 0x36, 0x2d, 0xa7, 0xff, 0x7f,
+                0x97,
+            ],
+            &[
+                0xfc, 0x00, 0x78, 0x3e, 0x0e, 0xfd, 0xb2, 0xc1, 0xd4, 0x45, 0xd4, 0xc8, 0xef, 0xf7, 0xed, 0x22,
+                0x97, 0x68, 0x72, 0x68, 0xd6, 0xec, 0xcc, 0xc0, 0xc0, 0x7b, 0x25, 0xe2, 0x5e, 0xcf, 0xe5,
+            ],
+            &[
+                0x39, 0x31, 0x25, 0x23, 0xa7, 0x86, 0x62, 0xd5, 0xbe, 0x7f, 0xcb, 0xcc, 0x98, 0xeb, 0xf5, 0xa8,
+                0x97, 0x68, 0x72, 0x68, 0xd6, 0xec, 0xcc, 0xc0, 0xc0, 0x7b, 0x25, 0xe2, 0x5e, 0xcf, 0xe5, 0x84,
+            ],
+            &[
+                0x97, 0x68, 0x72, 0x68, 0xd6, 0xec, 0xcc, 0xc0, 0xc0, 0x7b, 0x25, 0xe2, 0x5e, 0xcf, 0xe5, 0x84,
+                0xb3, 0xff, 0xfd, 0x94, 0x0c, 0x16, 0xa1, 0x8c, 0x1b, 0x55, 0x49, 0xd2, 0xf8, 0x38, 0x02, 0x9e,
+                0x39, 0x31, 0x25, 0x23, 0xa7, 0x86, 0x62, 0xd5, 0xbe, 0x7f, 0xcb, 0xcc, 0x98, 0xeb, 0xf5,
+            ],
+            &[
+                0x97, 0x68, 0x72, 0x68, 0xd6, 0xec, 0xcc, 0xc0, 0xc0, 0x7b, 0x25, 0xe2, 0x5e, 0xcf, 0xe5, 0x84,
+                0x9d, 0xad, 0x8b, 0xbb, 0x96, 0xc4, 0xcd, 0xc0, 0x3b, 0xc1, 0x03, 0xe1, 0xa1, 0x94, 0xbb, 0xd8,
+                0x39, 0x31, 0x25, 0x23, 0xa7, 0x86, 0x62, 0xd5, 0xbe, 0x7f, 0xcb, 0xcc, 0x98, 0xeb, 0xf5, 0xa8,
+            ],
+            &[
+                0x97, 0x68, 0x72, 0x68, 0xd6, 0xec, 0xcc, 0xc0, 0xc0, 0x7b, 0x25, 0xe2, 0x5e, 0xcf, 0xe5, 0x84,
+                0x39, 0x31, 0x25, 0x23, 0xa7, 0x86, 0x62, 0xd5, 0xbe, 0x7f, 0xcb, 0xcc, 0x98, 0xeb, 0xf5, 0xa8,
+                0x48, 0x07, 0xef, 0xe8, 0x36, 0xee, 0x89, 0xa5, 0x26, 0x73, 0x0d, 0xbc, 0x2f, 0x7b, 0xc8, 0x40,
+                0x9d, 0xad, 0x8b, 0xbb, 0x96, 0xc4, 0xcd, 0xc0, 0x3b, 0xc1, 0x03, 0xe1, 0xa1, 0x94, 0xbb, 0xd8,
+            ],
+        ];
+
+        let iv = [0_u8; IV_LEN];
+
+        for aes in [aes::Aes128::new_portable(&KEY), aes::Aes128::new(&KEY)].iter() {
+            for exp_cipher in exp_ciphers.iter() {
+                let msg = &MSG[..exp_cipher.len()];
+
+                let cipher = encrypt(msg, aes, &iv, &Variant::Cs3).unwrap();
+                assert_eq!(cipher[..], exp_cipher[..]);
+
+                assert_eq!(decrypt(&cipher, aes, &iv, &Variant::Cs3).unwrap(), msg);
+            }
+        }
+    }
+
+    #[test]
+    fn check_variants() {
+        let aes = aes::Aes128::new(&KEY);
+        let iv = [0_u8; IV_LEN];
+
+        // complete messages: CS1 and CS2 are plain CBC, CS3 swaps the final two blocks
+        let cbc_cipher = cbc::encrypt(&MSG[..48], &aes, &iv).unwrap();
+        let cs3 = encrypt(&MSG[..48], &aes, &iv, &Variant::Cs3).unwrap();
+
+        assert_eq!(encrypt(&MSG[..48], &aes, &iv, &Variant::Cs1).unwrap(), cbc_cipher);
+        assert_eq!(encrypt(&MSG[..48], &aes, &iv, &Variant::Cs2).unwrap(), cbc_cipher);
+        assert_eq!(cs3[..16], cbc_cipher[..16]);
+        assert_eq!(cs3[16..32], cbc_cipher[32..]);
+        assert_eq!(cs3[32..], cbc_cipher[16..32]);
+
+        // partial messages: CS2 and CS3 are the same, CS1 keeps the stolen block first
+        let cs1 = encrypt(&MSG[..40], &aes, &iv, &Variant::Cs1).unwrap();
+        let cs2 = encrypt(&MSG[..40], &aes, &iv, &Variant::Cs2).unwrap();
+
+        assert_eq!(cs2, encrypt(&MSG[..40], &aes, &iv, &Variant::Cs3).unwrap());
+        assert_eq!(cs1[..16], cs2[..16]);
+        assert_eq!(cs1[16..24], cs2[32..]);
+        assert_eq!(cs1[24..], cs2[16..32]);
+    }
+
+    #[test]
+    fn check_round_trip() {
+        let aes = aes::Aes128::new(&KEY);
+        let iv = [0x42_u8; IV_LEN];
+
+        for variant in [Variant::Cs1, Variant::Cs2, Variant::Cs3].iter() {
+            for len in MIN_MSG_LEN..=MSG.len() {
+                let cipher = encrypt(&MSG[..len], &aes, &iv, variant).unwrap();
+                assert_eq!(cipher.len(), len);
+
+                assert_eq!(decrypt(&cipher, &aes, &iv, variant).unwrap(), MSG[..len]);
+            }
+
+            assert!(encrypt(&MSG[..MIN_MSG_LEN - 1], &aes, &iv, variant).is_err());
+            assert!(decrypt(&[], &aes, &iv, variant).is_err());
+        }
+    }
+}
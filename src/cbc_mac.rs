@@ -0,0 +1,233 @@
+//! CBC-MAC (ISO/IEC 9797-1)
+//!
+//! MAC algorithm 1: final CBC block (output transformation 1)
+//! MAC algorithm 2: final CBC block encrypted under a second key (output transformation 2)
+//! MAC algorithm 5: CMAC (padding method 4)
+//!
+//! Plain CBC-MAC (algorithm 1) is only secure for messages of a fixed length,
+//! unless padding method 3 (length prefix) is used.
+
+use alloc::vec::Vec;
+
+use crate::cbc::{self, IV_LEN};
+use crate::{aes, cmac, BlockCipher, Error};
+
+/// Minimum truncated MAC length
+pub const MIN_MAC_LEN: usize = 4;
+
+/// Full MAC length
+pub const MAC_LEN: usize = 16;
+
+/// Padding methods from ISO/IEC 9797-1
+#[derive(Debug, PartialEq)]
+pub enum Padding {
+    /// Pad with as few (possibly none) zero bytes as needed, an empty message becomes one zero block
+    Method1,
+    /// Append 0x80, then pad with as few (possibly none) zero bytes as needed
+    Method2,
+    /// Prefix a block with the message length in bits, then pad with as few (possibly none) zero bytes as needed
+    Method3,
+}
+
+/// CBC-MAC using ISO/IEC 9797-1 MAC algorithm 1
+///
+/// Returns the leftmost `mac_len` bytes of the final CBC block
+pub fn mac_1<C: BlockCipher>(
+    msg: &[u8],
+    cipher: &C,
+    padding: &Padding,
+    mac_len: usize,
+) -> Result<Vec<u8>, Error> {
+    check_mac_len(mac_len)?;
+
+    Ok(chain(msg, cipher, padding)[..mac_len].to_vec())
+}
+
+/// CBC-MAC using ISO/IEC 9797-1 MAC algorithm 2
+///
+/// Returns the leftmost `mac_len` bytes of CIPHER( final CBC block , key' )
+pub fn mac_2<C: BlockCipher>(
+    msg: &[u8],
+    cipher: &C,
+    cipher_2: &C,
+    padding: &Padding,
+    mac_len: usize,
+) -> Result<Vec<u8>, Error> {
+    check_mac_len(mac_len)?;
+
+    let h = chain(msg, cipher, padding);
+
+    Ok(cipher_2.encrypt_block(&h)[..mac_len].to_vec())
+}
+
+/// CMAC using ISO/IEC 9797-1 MAC algorithm 5
+///
+/// Always uses padding method 4 (see the [cmac](../cmac/index.html) module)
+pub fn mac_5<C: BlockCipher>(msg: &[u8], cipher: &C, mac_len: usize) -> Result<Vec<u8>, Error> {
+    check_mac_len(mac_len)?;
+
+    Ok(cmac::mac(cipher, msg)[..mac_len].to_vec())
+}
+
+fn check_mac_len(mac_len: usize) -> Result<(), Error> {
+    if (MIN_MAC_LEN..=MAC_LEN).contains(&mac_len) {
+        Ok(())
+    } else {
+        Err(Error::InvalidLength)
+    }
+}
+
+// Run the CBC chain with a zero IV over the padded message, returning only the final block
+//
+// Whole message blocks are chained in place, only the padded final block is copied
+fn chain<C: BlockCipher>(msg: &[u8], cipher: &C, padding: &Padding) -> [u8; aes::BLOCK_LEN] {
+    let mut h = [0_u8; IV_LEN];
+
+    if *padding == Padding::Method3 {
+        // L_D: message length in bits, right-justified in the first block
+        let len_block = ((msg.len() as u128) * 8).to_be_bytes();
+        h = cbc::chain(&len_block, cipher, &h, |_| {});
+    }
+
+    let full_len = msg.len() - msg.len() % aes::BLOCK_LEN;
+    let (full, rem) = msg.split_at(full_len);
+
+    h = cbc::chain(full, cipher, &h, |_| {});
+
+    let mut last = [0_u8; aes::BLOCK_LEN];
+    last[..rem.len()].copy_from_slice(rem);
+
+    let pad_block = match padding {
+        Padding::Method1 => !rem.is_empty() || msg.is_empty(),
+        Padding::Method2 => {
+            last[rem.len()] = 0x80;
+            true
+        }
+        Padding::Method3 => !rem.is_empty(),
+    };
+
+    if pad_block {
+        h = cbc::chain(&last, cipher, &h, |_| {});
+    }
+
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; aes::KEY_LEN_128] = [
+        0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,
+    ];
+
+    const KEY_2: [u8; aes::KEY_LEN_128] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    ];
+
+    // NIST SP 800-38A plaintext
+    const MSG: [u8; 40] = [
+        0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a,
+        0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51,
+        0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11,
+    ];
+
+    // Pad the whole message, for comparing against CBC encryption
+    fn padded(msg: &[u8], padding: &Padding) -> Vec<u8> {
+        let mut res = Vec::new();
+
+        if *padding == Padding::Method3 {
+            res.extend_from_slice(&((msg.len() as u128) * 8).to_be_bytes());
+        }
+
+        res.extend_from_slice(msg);
+
+        if *padding == Padding::Method2 {
+            res.push(0x80);
+        }
+
+        while res.len() % aes::BLOCK_LEN != 0 || (res.is_empty() && *padding == Padding::Method1) {
+            res.push(0);
+        }
+
+        res
+    }
+
+    #[test]
+    fn check_chain_matches_cbc() {
+        let aes = aes::Aes128::new(&KEY);
+
+        for padding in [Padding::Method1, Padding::Method2, Padding::Method3].iter() {
+            for len in 0..=MSG.len() {
+                let cipher = cbc::encrypt(&padded(&MSG[..len], padding), &aes, &[0_u8; IV_LEN]).unwrap();
+                assert_eq!(chain(&MSG[..len], &aes, padding)[..], cipher[cipher.len() - aes::BLOCK_LEN..]);
+            }
+        }
+    }
+
+    // No AES vectors are published for ISO/IEC 9797-1, these were computed independently with
+    // Python `cryptography`: last block of AES-128-CBC (zero IV) over the padded message.
+    // The 16 byte Method1 MAC matches NIST SP 800-38A F.1.1 ECB-AES128 block #1.
+    #[test]
+    fn check_mac_1() {
+        let exp_macs = [
+            (0, Padding::Method1, [0x7d, 0xf7, 0x6b, 0x0c, 0x1a, 0xb8, 0x99, 0xb3, 0x3e, 0x42, 0xf0, 0x47, 0xb9, 0x1b, 0x54, 0x6f]),
+            (0, Padding::Method2, [0xf6, 0xc7, 0x1e, 0xed, 0xc3, 0xd9, 0x9b, 0xb1, 0x83, 0xcb, 0x5b, 0x8d, 0x15, 0x68, 0xe6, 0x06]),
+            (16, Padding::Method1, [0x3a, 0xd7, 0x7b, 0xb4, 0x0d, 0x7a, 0x36, 0x60, 0xa8, 0x9e, 0xca, 0xf3, 0x24, 0x66, 0xef, 0x97]),
+            (16, Padding::Method2, [0x05, 0x39, 0xbd, 0xa3, 0x0b, 0x3f, 0x76, 0x34, 0x46, 0x6a, 0x75, 0xd9, 0x84, 0x18, 0xbf, 0x65]),
+            (16, Padding::Method3, [0xc9, 0x88, 0x61, 0xab, 0x37, 0xa9, 0xcc, 0x12, 0x19, 0x6a, 0xe1, 0x7e, 0xe9, 0xdf, 0x6f, 0xd0]),
+            (40, Padding::Method1, [0x07, 0xd1, 0x92, 0xe3, 0xe6, 0xf0, 0x99, 0xed, 0xcc, 0x39, 0xfd, 0xe6, 0xd0, 0x9c, 0x76, 0x2d]),
+            (40, Padding::Method2, [0xa5, 0x26, 0x0f, 0x98, 0xf1, 0xab, 0xf2, 0xb2, 0x75, 0x62, 0xed, 0x5f, 0xc1, 0xfb, 0xeb, 0x8d]),
+            (40, Padding::Method3, [0x0b, 0xd6, 0xc8, 0x3c, 0x75, 0xe9, 0xd2, 0xb0, 0x0b, 0xf4, 0x37, 0x5a, 0x88, 0xc3, 0x31, 0xc0]),
+        ];
+
+        let aes = aes::Aes128::new(&KEY);
+
+        for (len, padding, exp_mac) in exp_macs.iter() {
+            assert_eq!(mac_1(&MSG[..*len], &aes, padding, MAC_LEN).unwrap(), exp_mac);
+            assert_eq!(mac_1(&MSG[..*len], &aes, padding, 8).unwrap(), exp_mac[..8]);
+        }
+    }
+
+    // Computed independently with Python `cryptography`: the algorithm 1 MAC for the same padding,
+    // encrypted once more with AES-128-ECB under KEY_2
+    #[test]
+    fn check_mac_2() {
+        let exp_macs = [
+            (0, Padding::Method3, [0x38, 0x30, 0x41, 0xbd, 0x95, 0x8b, 0x2c, 0x61, 0x82, 0xea, 0x5a, 0x0e, 0x14, 0x06, 0x0b, 0xa4]),
+            (16, Padding::Method1, [0x69, 0x12, 0xcc, 0x3d, 0x30, 0xf2, 0x8c, 0xb6, 0xb9, 0xd7, 0xbe, 0x5e, 0x63, 0xfc, 0x65, 0x65]),
+            (40, Padding::Method2, [0xdb, 0x8e, 0xf9, 0x90, 0x3d, 0x83, 0x9b, 0xe6, 0x4c, 0x72, 0x8c, 0xa0, 0x12, 0x0e, 0x63, 0x1b]),
+            (40, Padding::Method3, [0x99, 0xfc, 0xd8, 0xf4, 0x5f, 0x9f, 0x0d, 0x9a, 0xb9, 0x7b, 0x4d, 0x16, 0x15, 0xc3, 0xbb, 0xac]),
+        ];
+
+        let aes = aes::Aes128::new(&KEY);
+        let aes_2 = aes::Aes128::new(&KEY_2);
+
+        for (len, padding, exp_mac) in exp_macs.iter() {
+            assert_eq!(mac_2(&MSG[..*len], &aes, &aes_2, padding, MAC_LEN).unwrap(), exp_mac);
+            assert_eq!(mac_2(&MSG[..*len], &aes, &aes_2, padding, 4).unwrap(), exp_mac[..4]);
+        }
+    }
+
+    #[test]
+    fn check_mac_5() {
+        // RFC 4493 Example 3
+        let exp_mac = [0xdf, 0xa6, 0x67, 0x47, 0xde, 0x9a, 0xe6, 0x30, 0x30, 0xca, 0x32, 0x61, 0x14, 0x97, 0xc8, 0x27];
+
+        let aes = aes::Aes128::new(&KEY);
+
+        assert_eq!(mac_5(&MSG, &aes, MAC_LEN).unwrap(), exp_mac);
+        assert_eq!(mac_5(&MSG, &aes, 12).unwrap(), exp_mac[..12]);
+    }
+
+    #[test]
+    fn check_invalid_mac_len() {
+        let aes = aes::Aes128::new(&KEY);
+
+        for &mac_len in [0, MIN_MAC_LEN - 1, MAC_LEN + 1].iter() {
+            assert!(mac_1(&MSG, &aes, &Padding::Method2, mac_len).is_err());
+            assert!(mac_2(&MSG, &aes, &aes, &Padding::Method2, mac_len).is_err());
+            assert!(mac_5(&MSG, &aes, mac_len).is_err());
+        }
+    }
+}
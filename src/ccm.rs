@@ -0,0 +1,572 @@
+//! Counter with CBC-MAC mode (NIST SP 800-38C, RFC 3610)
+//!
+//! Authenticates the formatted nonce, AAD and message with CBC-MAC,
+//! then encrypts the message and MAC with CTR mode.
+//!
+//! Ciphertexts are returned with the authentication tag appended.
+
+use alloc::vec::Vec;
+
+use crate::ctr::{self, Endian};
+use crate::{aes, ct_eq, xor_equals, BlockCipher, Error};
+
+/// Minimum nonce length (L = 8 length bytes)
+pub const MIN_NONCE_LEN: usize = 7;
+
+/// Maximum nonce length (L = 2 length bytes)
+pub const MAX_NONCE_LEN: usize = 13;
+
+/// Minimum authentication tag length
+pub const MIN_TAG_LEN: usize = 4;
+
+/// Maximum authentication tag length
+pub const MAX_TAG_LEN: usize = 16;
+
+/// Encrypt and authenticate a message in CCM mode
+///
+/// Nonce must be 7 to 13 bytes, shorter nonces allow longer messages (up to 2^(8 * (15 - nonce_len)) bytes)
+///
+/// Tag length must be even, and 4 to 16 bytes
+pub fn encrypt<C: BlockCipher>(
+    msg: &[u8],
+    cipher: &C,
+    nonce: &[u8],
+    aad: &[u8],
+    tag_len: usize,
+) -> Result<Vec<u8>, Error> {
+    check_params(msg.len(), nonce, tag_len)?;
+
+    let tag = cbc_mac(cipher, &format_input(msg, nonce, aad, tag_len));
+
+    let (ctr_nonce, a0_count) = counter_block(nonce);
+    let mut count = a0_count + 1;
+
+    let mut res = ctr::encrypt(msg, cipher, ctr_nonce, &mut count, &Endian::Big);
+    res.extend_from_slice(&encrypt_tag(cipher, ctr_nonce, a0_count, &tag[..tag_len]));
+
+    Ok(res)
+}
+
+/// Verify and decrypt a message in CCM mode
+///
+/// Ciphertext must have the `tag_len` byte authentication tag appended
+///
+/// No plaintext is returned unless the tag is valid
+pub fn decrypt<C: BlockCipher>(
+    ciphertext: &[u8],
+    cipher: &C,
+    nonce: &[u8],
+    aad: &[u8],
+    tag_len: usize,
+) -> Result<Vec<u8>, Error> {
+    if ciphertext.len() < tag_len {
+        return Err(Error::InvalidLength);
+    }
+
+    let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - tag_len);
+
+    check_params(ciphertext.len(), nonce, tag_len)?;
+
+    let (ctr_nonce, a0_count) = counter_block(nonce);
+    let mut count = a0_count + 1;
+
+    let msg = ctr::decrypt(ciphertext, cipher, ctr_nonce, &mut count, &Endian::Big);
+    let tag = encrypt_tag(cipher, ctr_nonce, a0_count, tag);
+
+    let exp_tag = cbc_mac(cipher, &format_input(&msg, nonce, aad, tag_len));
+
+    if !ct_eq(&exp_tag[..tag_len], &tag) {
+        return Err(Error::InvalidTag);
+    }
+
+    Ok(msg)
+}
+
+fn check_params(msg_len: usize, nonce: &[u8], tag_len: usize) -> Result<(), Error> {
+    if !(MIN_NONCE_LEN..=MAX_NONCE_LEN).contains(&nonce.len()) {
+        return Err(Error::InvalidLength);
+    }
+
+    if !(MIN_TAG_LEN..=MAX_TAG_LEN).contains(&tag_len) || tag_len % 2 != 0 {
+        return Err(Error::InvalidLength);
+    }
+
+    // message length must fit in q = 15 - n bytes
+    let q = 15 - nonce.len();
+    if q < 8 && msg_len as u64 >= 1 << (8 * q) {
+        return Err(Error::InvalidLength);
+    }
+
+    Ok(())
+}
+
+// B_0 || encoded AAD || 0-padding || P || 0-padding (SP 800-38C Appendix A.2)
+pub(crate) fn format_input(msg: &[u8], nonce: &[u8], aad: &[u8], tag_len: usize) -> Vec<u8> {
+    let q = 15 - nonce.len();
+
+    let mut b = Vec::with_capacity(aes::BLOCK_LEN * 3 + aad.len() + msg.len());
+
+    // Flags = 64 * Adata || 8 * [(t - 2) / 2] || [q - 1]
+    let adata = !aad.is_empty() as u8;
+    b.push((adata << 6) | ((((tag_len - 2) / 2) as u8) << 3) | (q - 1) as u8);
+    b.extend_from_slice(nonce);
+    b.extend_from_slice(&(msg.len() as u64).to_be_bytes()[8 - q..]);
+
+    if !aad.is_empty() {
+        let a = aad.len() as u64;
+
+        if a < 0xff00 {
+            b.extend_from_slice(&(a as u16).to_be_bytes());
+        } else if a < 1 << 32 {
+            b.extend_from_slice(&[0xff, 0xfe]);
+            b.extend_from_slice(&(a as u32).to_be_bytes());
+        } else {
+            b.extend_from_slice(&[0xff, 0xff]);
+            b.extend_from_slice(&a.to_be_bytes());
+        }
+
+        b.extend_from_slice(aad);
+        pad_block(&mut b);
+    }
+
+    b.extend_from_slice(msg);
+    pad_block(&mut b);
+
+    b
+}
+
+// Zero pad to the next block boundary
+fn pad_block(buf: &mut Vec<u8>) {
+    let rem = buf.len() % aes::BLOCK_LEN;
+    if rem != 0 {
+        buf.resize(buf.len() + aes::BLOCK_LEN - rem, 0);
+    }
+}
+
+// CBC-MAC with a zero IV over whole blocks
+//
+// Y_i = CIPHER( B_i ^ Y_i-1 , key )
+fn cbc_mac<C: BlockCipher>(cipher: &C, blocks: &[u8]) -> [u8; aes::BLOCK_LEN] {
+    let mut y = [0_u8; aes::BLOCK_LEN];
+
+    for block in blocks.chunks_exact(aes::BLOCK_LEN) {
+        // blocks guaranteed to be the same length, safe to just unwrap here
+        xor_equals(&mut y, block).unwrap();
+        y = cipher.encrypt_block(&y);
+    }
+
+    y
+}
+
+// A_0 = [q - 1] || N || 0^q, split into the CTR nonce and counter
+//
+// Message counter blocks start from A_1, and the message length limit
+// keeps the counter from carrying into the nonce
+pub(crate) fn counter_block(nonce: &[u8]) -> (u64, u64) {
+    let mut a0 = [0_u8; aes::BLOCK_LEN];
+    a0[0] = (14 - nonce.len()) as u8;
+    a0[1..=nonce.len()].copy_from_slice(nonce);
+
+    let mut ctr_nonce = [0_u8; ctr::NONCE_LEN];
+    let mut count = [0_u8; ctr::COUNT_LEN];
+    ctr_nonce.copy_from_slice(&a0[..ctr::NONCE_LEN]);
+    count.copy_from_slice(&a0[ctr::NONCE_LEN..]);
+
+    (u64::from_be_bytes(ctr_nonce), u64::from_be_bytes(count))
+}
+
+// T ^ MSB_Tlen( CIPHER( A_0 , key ) )
+pub(crate) fn encrypt_tag<C: BlockCipher>(cipher: &C, ctr_nonce: u64, count: u64, tag: &[u8]) -> Vec<u8> {
+    let mut a0 = [0_u8; aes::BLOCK_LEN];
+    a0[..ctr::NONCE_LEN].copy_from_slice(&ctr_nonce.to_be_bytes());
+
+    let s0 = ctr::ctr_inner_cipher(&mut a0, cipher, count, &Endian::Big);
+
+    let mut res = tag.to_vec();
+    // tag is at most a block long, safe to just unwrap here
+    xor_equals(&mut res, &s0[..tag.len()]).unwrap();
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryInto;
+
+    type Vector<'a> = (&'a [u8], &'a [u8], usize, usize, &'a [u8]);
+
+    // RFC 3610 packet vectors #1 - #12 share the key, and use the packet bytes 00 01 02 ...
+    const KEY: [u8; aes::KEY_LEN_128] = [
+        0xc0, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xcb, 0xcc, 0xcd, 0xce, 0xcf,
+    ];
+
+    fn packet(len: usize) -> Vec<u8> {
+        (0..len as u8).collect()
+    }
+
+    #[test]
+    fn check_format_input() {
+        // SP 800-38C C.1: B_0 for a 7 byte nonce, 4 byte tag and 8 bytes of AAD
+        let nonce = [0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16];
+        let b = format_input(&[0x20, 0x21, 0x22, 0x23], &nonce, &packet(8), 4);
+
+        assert_eq!(b.len(), 3 * aes::BLOCK_LEN);
+        assert_eq!(b[..aes::BLOCK_LEN], [0x4f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0, 0, 0, 0, 0, 0, 0, 0x04]);
+        assert_eq!(b[aes::BLOCK_LEN..aes::BLOCK_LEN + 2], [0x00, 0x08]);
+    }
+
+    #[test]
+    fn check_invalid_params() {
+        let aes = aes::Aes128::new(&KEY);
+        let msg = packet(32);
+
+        for &nonce_len in [0, 6, 14, 16].iter() {
+            assert!(encrypt(&msg, &aes, &packet(nonce_len), &[], 8).is_err());
+        }
+
+        for &tag_len in [0, 2, 5, 15, 18].iter() {
+            assert!(encrypt(&msg, &aes, &packet(13), &[], tag_len).is_err());
+        }
+
+        assert!(decrypt(&msg[..3], &aes, &packet(13), &[], 4).is_err());
+    }
+
+    // SP 800-38C C.1: Example 1
+    #[test]
+    fn nist_example_1() {
+        let key: Vec<u8> = (0x40..0x50).collect();
+        let aes = aes::Aes128::new(key[..].try_into().unwrap());
+        let nonce: Vec<u8> = (0x10..0x17).collect();
+        let msg = [0x20, 0x21, 0x22, 0x23];
+        let exp_cipher = [0x71, 0x62, 0x01, 0x5b, 0x4d, 0xac, 0x25, 0x5d];
+
+        let res = encrypt(&msg, &aes, &nonce, &packet(8), 4).unwrap();
+        assert_eq!(res, exp_cipher);
+
+        assert_eq!(decrypt(&res, &aes, &nonce, &packet(8), 4).unwrap(), msg);
+    }
+
+    // SP 800-38C C.2: Example 2
+    #[test]
+    fn nist_example_2() {
+        let key: Vec<u8> = (0x40..0x50).collect();
+        let aes = aes::Aes128::new(key[..].try_into().unwrap());
+        let nonce: Vec<u8> = (0x10..0x18).collect();
+        let msg: Vec<u8> = (0x20..0x30).collect();
+        let exp_cipher = [
+            0xd2, 0xa1, 0xf0, 0xe0, 0x51, 0xea, 0x5f, 0x62, 0x08, 0x1a, 0x77, 0x92, 0x07, 0x3d, 0x59, 0x3d,
+            0x1f, 0xc6, 0x4f, 0xbf, 0xac, 0xcd,
+        ];
+
+        let res = encrypt(&msg, &aes, &nonce, &packet(16), 6).unwrap();
+        assert_eq!(res, exp_cipher);
+
+        assert_eq!(decrypt(&res, &aes, &nonce, &packet(16), 6).unwrap(), msg);
+    }
+
+    // SP 800-38C C.4: Example 4, 2^16 bytes of AAD use the 0xff 0xfe || [a]_32 length encoding
+    #[test]
+    fn nist_example_4() {
+        let key: Vec<u8> = (0x40..0x50).collect();
+        let aes = aes::Aes128::new(key[..].try_into().unwrap());
+        let nonce: Vec<u8> = (0x10..0x1d).collect();
+        let aad: Vec<u8> = (0..0x10000).map(|i| i as u8).collect();
+        let msg: Vec<u8> = (0x20..0x40).collect();
+        let exp_cipher = [
+            0x69, 0x91, 0x5d, 0xad, 0x1e, 0x84, 0xc6, 0x37, 0x6a, 0x68, 0xc2, 0x96, 0x7e, 0x4d, 0xab, 0x61,
+            0x5a, 0xe0, 0xfd, 0x1f, 0xae, 0xc4, 0x4c, 0xc4, 0x84, 0x82, 0x85, 0x29, 0x46, 0x3c, 0xcf, 0x72,
+            0xb4, 0xac, 0x6b, 0xec, 0x93, 0xe8, 0x59, 0x8e, 0x7f, 0x0d, 0xad, 0xbc, 0xea, 0x5b,
+        ];
+
+        let res = encrypt(&msg, &aes, &nonce, &aad, 14).unwrap();
+        assert_eq!(res[..], exp_cipher[..]);
+
+        assert_eq!(decrypt(&res, &aes, &nonce, &aad, 14).unwrap(), msg);
+    }
+
+    // RFC 3610 Packet Vector #1
+    #[test]
+    fn rfc_packet_vector_1() {
+        let nonce = [0x00, 0x00, 0x00, 0x03, 0x02, 0x01, 0x00, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+        let pkt = packet(31);
+        let exp_cipher = [
+            0x58, 0x8c, 0x97, 0x9a, 0x61, 0xc6, 0x63, 0xd2, 0xf0, 0x66, 0xd0, 0xc2, 0xc0, 0xf9, 0x89, 0x80,
+            0x6d, 0x5f, 0x6b, 0x61, 0xda, 0xc3, 0x84, 0x17, 0xe8, 0xd1, 0x2c, 0xfd, 0xf9, 0x26, 0xe0,
+        ];
+
+        for aes in [aes::Aes128::new_portable(&KEY), aes::Aes128::new(&KEY)].iter() {
+            let res = encrypt(&pkt[8..], aes, &nonce, &pkt[..8], 8).unwrap();
+            assert_eq!(res, exp_cipher);
+
+            assert_eq!(decrypt(&res, aes, &nonce, &pkt[..8], 8).unwrap(), pkt[8..]);
+        }
+    }
+
+    // RFC 3610 Packet Vector #2
+    #[test]
+    fn rfc_packet_vector_2() {
+        let nonce = [0x00, 0x00, 0x00, 0x04, 0x03, 0x02, 0x01, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+        let pkt = packet(32);
+        let exp_cipher = [
+            0x72, 0xc9, 0x1a, 0x36, 0xe1, 0x35, 0xf8, 0xcf, 0x29, 0x1c, 0xa8, 0x94, 0x08, 0x5c, 0x87, 0xe3,
+            0xcc, 0x15, 0xc4, 0x39, 0xc9, 0xe4, 0x3a, 0x3b, 0xa0, 0x91, 0xd5, 0x6e, 0x10, 0x40, 0x09, 0x16,
+        ];
+
+        let aes = aes::Aes128::new(&KEY);
+
+        let res = encrypt(&pkt[8..], &aes, &nonce, &pkt[..8], 8).unwrap();
+        assert_eq!(res, exp_cipher);
+
+        assert_eq!(decrypt(&res, &aes, &nonce, &pkt[..8], 8).unwrap(), pkt[8..]);
+    }
+
+    // RFC 3610 Packet Vector #3
+    #[test]
+    fn rfc_packet_vector_3() {
+        let nonce = [0x00, 0x00, 0x00, 0x05, 0x04, 0x03, 0x02, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+        let pkt = packet(33);
+        let exp_cipher = [
+            0x51, 0xb1, 0xe5, 0xf4, 0x4a, 0x19, 0x7d, 0x1d, 0xa4, 0x6b, 0x0f, 0x8e, 0x2d, 0x28, 0x2a, 0xe8,
+            0x71, 0xe8, 0x38, 0xbb, 0x64, 0xda, 0x85, 0x96, 0x57, 0x4a, 0xda, 0xa7, 0x6f, 0xbd, 0x9f, 0xb0,
+            0xc5,
+        ];
+
+        let aes = aes::Aes128::new(&KEY);
+
+        let res = encrypt(&pkt[8..], &aes, &nonce, &pkt[..8], 8).unwrap();
+        assert_eq!(res, exp_cipher);
+
+        assert_eq!(decrypt(&res, &aes, &nonce, &pkt[..8], 8).unwrap(), pkt[8..]);
+    }
+
+    // RFC 3610 Packet Vector #4
+    #[test]
+    fn rfc_packet_vector_4() {
+        let nonce = [0x00, 0x00, 0x00, 0x06, 0x05, 0x04, 0x03, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+        let pkt = packet(31);
+        let exp_cipher = [
+            0xa2, 0x8c, 0x68, 0x65, 0x93, 0x9a, 0x9a, 0x79, 0xfa, 0xaa, 0x5c, 0x4c, 0x2a, 0x9d, 0x4a, 0x91,
+            0xcd, 0xac, 0x8c, 0x96, 0xc8, 0x61, 0xb9, 0xc9, 0xe6, 0x1e, 0xf1,
+        ];
+
+        let aes = aes::Aes128::new(&KEY);
+
+        let res = encrypt(&pkt[12..], &aes, &nonce, &pkt[..12], 8).unwrap();
+        assert_eq!(res, exp_cipher);
+
+        assert_eq!(decrypt(&res, &aes, &nonce, &pkt[..12], 8).unwrap(), pkt[12..]);
+    }
+
+    // RFC 3610 Packet Vector #7: 10 byte tag
+    #[test]
+    fn rfc_packet_vector_7() {
+        let nonce = [0x00, 0x00, 0x00, 0x09, 0x08, 0x07, 0x06, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+        let pkt = packet(31);
+        let exp_cipher = [
+            0x01, 0x35, 0xd1, 0xb2, 0xc9, 0x5f, 0x41, 0xd5, 0xd1, 0xd4, 0xfe, 0xc1, 0x85, 0xd1, 0x66, 0xb8,
+            0x09, 0x4e, 0x99, 0x9d, 0xfe, 0xd9, 0x6c, 0x04, 0x8c, 0x56, 0x60, 0x2c, 0x97, 0xac, 0xbb, 0x74,
+            0x90,
+        ];
+
+        let aes = aes::Aes128::new(&KEY);
+
+        let res = encrypt(&pkt[8..], &aes, &nonce, &pkt[..8], 10).unwrap();
+        assert_eq!(res, exp_cipher);
+
+        assert_eq!(decrypt(&res, &aes, &nonce, &pkt[..8], 10).unwrap(), pkt[8..]);
+
+        // tampered AAD, ciphertext or tag must fail verification
+        assert!(decrypt(&res, &aes, &nonce, &pkt[1..8], 10).is_err());
+
+        let mut tampered = res.clone();
+        tampered[0] ^= 0x01;
+        assert!(decrypt(&tampered, &aes, &nonce, &pkt[..8], 10).is_err());
+
+        tampered = res;
+        tampered[32] ^= 0x80;
+        assert!(decrypt(&tampered, &aes, &nonce, &pkt[..8], 10).is_err());
+    }
+
+    // RFC 3610 Packet Vectors #5, #6 and #8 - #12
+    #[test]
+    fn rfc_packet_vectors_same_key() {
+        // (vector, packet length, header length, tag length, ciphertext || tag)
+        let vectors: [(u8, usize, usize, usize, &[u8]); 7] = [
+            // #5
+            (5, 32, 12, 8, &[
+                0xdc, 0xf1, 0xfb, 0x7b, 0x5d, 0x9e, 0x23, 0xfb, 0x9d, 0x4e, 0x13, 0x12, 0x53, 0x65, 0x8a, 0xd8,
+                0x6e, 0xbd, 0xca, 0x3e, 0x51, 0xe8, 0x3f, 0x07, 0x7d, 0x9c, 0x2d, 0x93,
+            ]),
+            // #6
+            (6, 33, 12, 8, &[
+                0x6f, 0xc1, 0xb0, 0x11, 0xf0, 0x06, 0x56, 0x8b, 0x51, 0x71, 0xa4, 0x2d, 0x95, 0x3d, 0x46, 0x9b,
+                0x25, 0x70, 0xa4, 0xbd, 0x87, 0x40, 0x5a, 0x04, 0x43, 0xac, 0x91, 0xcb, 0x94,
+            ]),
+            // #8
+            (8, 32, 8, 10, &[
+                0x7b, 0x75, 0x39, 0x9a, 0xc0, 0x83, 0x1d, 0xd2, 0xf0, 0xbb, 0xd7, 0x58, 0x79, 0xa2, 0xfd, 0x8f,
+                0x6c, 0xae, 0x6b, 0x6c, 0xd9, 0xb7, 0xdb, 0x24, 0xc1, 0x7b, 0x44, 0x33, 0xf4, 0x34, 0x96, 0x3f,
+                0x34, 0xb4,
+            ]),
+            // #9
+            (9, 33, 8, 10, &[
+                0x82, 0x53, 0x1a, 0x60, 0xcc, 0x24, 0x94, 0x5a, 0x4b, 0x82, 0x79, 0x18, 0x1a, 0xb5, 0xc8, 0x4d,
+                0xf2, 0x1c, 0xe7, 0xf9, 0xb7, 0x3f, 0x42, 0xe1, 0x97, 0xea, 0x9c, 0x07, 0xe5, 0x6b, 0x5e, 0xb1,
+                0x7e, 0x5f, 0x4e,
+            ]),
+            // #10
+            (10, 31, 12, 10, &[
+                0x07, 0x34, 0x25, 0x94, 0x15, 0x77, 0x85, 0x15, 0x2b, 0x07, 0x40, 0x98, 0x33, 0x0a, 0xbb, 0x14,
+                0x1b, 0x94, 0x7b, 0x56, 0x6a, 0xa9, 0x40, 0x6b, 0x4d, 0x99, 0x99, 0x88, 0xdd,
+            ]),
+            // #11
+            (11, 32, 12, 10, &[
+                0x67, 0x6b, 0xb2, 0x03, 0x80, 0xb0, 0xe3, 0x01, 0xe8, 0xab, 0x79, 0x59, 0x0a, 0x39, 0x6d, 0xa7,
+                0x8b, 0x83, 0x49, 0x34, 0xf5, 0x3a, 0xa2, 0xe9, 0x10, 0x7a, 0x8b, 0x6c, 0x02, 0x2c,
+            ]),
+            // #12
+            (12, 33, 12, 10, &[
+                0xc0, 0xff, 0xa0, 0xd6, 0xf0, 0x5b, 0xdb, 0x67, 0xf2, 0x4d, 0x43, 0xa4, 0x33, 0x8d, 0x2a, 0xa4,
+                0xbe, 0xd7, 0xb2, 0x0e, 0x43, 0xcd, 0x1a, 0xa3, 0x16, 0x62, 0xe7, 0xad, 0x65, 0xd6, 0xdb,
+            ]),
+        ];
+
+        let aes = aes::Aes128::new(&KEY);
+
+        for (n, pkt_len, header_len, tag_len, exp_cipher) in vectors.iter() {
+            let nonce = [0x00, 0x00, 0x00, n + 2, n + 1, *n, n - 1, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5];
+            let pkt = packet(*pkt_len);
+
+            let res = encrypt(&pkt[*header_len..], &aes, &nonce, &pkt[..*header_len], *tag_len).unwrap();
+            assert_eq!(res[..], exp_cipher[..]);
+
+            assert_eq!(decrypt(&res, &aes, &nonce, &pkt[..*header_len], *tag_len).unwrap(), pkt[*header_len..]);
+        }
+    }
+
+    // RFC 3610 Packet Vectors #13 - #19: key D7828D13B2B0BDC325A76236DF93CC6B
+    #[test]
+    fn rfc_packet_vectors_other_key() {
+        let key = [
+            0xd7, 0x82, 0x8d, 0x13, 0xb2, 0xb0, 0xbd, 0xc3, 0x25, 0xa7, 0x62, 0x36, 0xdf, 0x93, 0xcc, 0x6b,
+        ];
+
+        // (nonce, packet, header length, tag length, ciphertext || tag)
+        let vectors: [Vector; 7] = [
+            // #13
+            (
+                &[0x00, 0x41, 0x2b, 0x4e, 0xa9, 0xcd, 0xbe, 0x3c, 0x96, 0x96, 0x76, 0x6c, 0xfa],
+                &[
+                    0x0b, 0xe1, 0xa8, 0x8b, 0xac, 0xe0, 0x18, 0xb1, 0x08, 0xe8, 0xcf, 0x97, 0xd8, 0x20, 0xea, 0x25,
+                    0x84, 0x60, 0xe9, 0x6a, 0xd9, 0xcf, 0x52, 0x89, 0x05, 0x4d, 0x89, 0x5c, 0xea, 0xc4, 0x7c,
+                ],
+                8,
+                8,
+                &[
+                    0x4c, 0xb9, 0x7f, 0x86, 0xa2, 0xa4, 0x68, 0x9a, 0x87, 0x79, 0x47, 0xab, 0x80, 0x91, 0xef, 0x53,
+                    0x86, 0xa6, 0xff, 0xbd, 0xd0, 0x80, 0xf8, 0xe7, 0x8c, 0xf7, 0xcb, 0x0c, 0xdd, 0xd7, 0xb3,
+                ],
+            ),
+            // #14
+            (
+                &[0x00, 0x33, 0x56, 0x8e, 0xf7, 0xb2, 0x63, 0x3c, 0x96, 0x96, 0x76, 0x6c, 0xfa],
+                &[
+                    0x63, 0x01, 0x8f, 0x76, 0xdc, 0x8a, 0x1b, 0xcb, 0x90, 0x20, 0xea, 0x6f, 0x91, 0xbd, 0xd8, 0x5a,
+                    0xfa, 0x00, 0x39, 0xba, 0x4b, 0xaf, 0xf9, 0xbf, 0xb7, 0x9c, 0x70, 0x28, 0x94, 0x9c, 0xd0, 0xec,
+                ],
+                8,
+                8,
+                &[
+                    0x4c, 0xcb, 0x1e, 0x7c, 0xa9, 0x81, 0xbe, 0xfa, 0xa0, 0x72, 0x6c, 0x55, 0xd3, 0x78, 0x06, 0x12,
+                    0x98, 0xc8, 0x5c, 0x92, 0x81, 0x4a, 0xbc, 0x33, 0xc5, 0x2e, 0xe8, 0x1d, 0x7d, 0x77, 0xc0, 0x8a,
+                ],
+            ),
+            // #15
+            (
+                &[0x00, 0x10, 0x3f, 0xe4, 0x13, 0x36, 0x71, 0x3c, 0x96, 0x96, 0x76, 0x6c, 0xfa],
+                &[
+                    0xaa, 0x6c, 0xfa, 0x36, 0xca, 0xe8, 0x6b, 0x40, 0xb9, 0x16, 0xe0, 0xea, 0xcc, 0x1c, 0x00, 0xd7,
+                    0xdc, 0xec, 0x68, 0xec, 0x0b, 0x3b, 0xbb, 0x1a, 0x02, 0xde, 0x8a, 0x2d, 0x1a, 0xa3, 0x46, 0x13,
+                    0x2e,
+                ],
+                8,
+                8,
+                &[
+                    0xb1, 0xd2, 0x3a, 0x22, 0x20, 0xdd, 0xc0, 0xac, 0x90, 0x0d, 0x9a, 0xa0, 0x3c, 0x61, 0xfc, 0xf4,
+                    0xa5, 0x59, 0xa4, 0x41, 0x77, 0x67, 0x08, 0x97, 0x08, 0xa7, 0x76, 0x79, 0x6e, 0xdb, 0x72, 0x35,
+                    0x06,
+                ],
+            ),
+            // #16
+            (
+                &[0x00, 0x76, 0x4c, 0x63, 0xb8, 0x05, 0x8e, 0x3c, 0x96, 0x96, 0x76, 0x6c, 0xfa],
+                &[
+                    0xd0, 0xd0, 0x73, 0x5c, 0x53, 0x1e, 0x1b, 0xec, 0xf0, 0x49, 0xc2, 0x44, 0x12, 0xda, 0xac, 0x56,
+                    0x30, 0xef, 0xa5, 0x39, 0x6f, 0x77, 0x0c, 0xe1, 0xa6, 0x6b, 0x21, 0xf7, 0xb2, 0x10, 0x1c,
+                ],
+                12,
+                8,
+                &[
+                    0x14, 0xd2, 0x53, 0xc3, 0x96, 0x7b, 0x70, 0x60, 0x9b, 0x7c, 0xbb, 0x7c, 0x49, 0x91, 0x60, 0x28,
+                    0x32, 0x45, 0x26, 0x9a, 0x6f, 0x49, 0x97, 0x5b, 0xca, 0xde, 0xaf,
+                ],
+            ),
+            // #17
+            (
+                &[0x00, 0xf8, 0xb6, 0x78, 0x09, 0x4e, 0x3b, 0x3c, 0x96, 0x96, 0x76, 0x6c, 0xfa],
+                &[
+                    0x77, 0xb6, 0x0f, 0x01, 0x1c, 0x03, 0xe1, 0x52, 0x58, 0x99, 0xbc, 0xae, 0xe8, 0x8b, 0x6a, 0x46,
+                    0xc7, 0x8d, 0x63, 0xe5, 0x2e, 0xb8, 0xc5, 0x46, 0xef, 0xb5, 0xde, 0x6f, 0x75, 0xe9, 0xcc, 0x0d,
+                ],
+                12,
+                8,
+                &[
+                    0x55, 0x45, 0xff, 0x1a, 0x08, 0x5e, 0xe2, 0xef, 0xbf, 0x52, 0xb2, 0xe0, 0x4b, 0xee, 0x1e, 0x23,
+                    0x36, 0xc7, 0x3e, 0x3f, 0x76, 0x2c, 0x0c, 0x77, 0x44, 0xfe, 0x7e, 0x3c,
+                ],
+            ),
+            // #18
+            (
+                &[0x00, 0xd5, 0x60, 0x91, 0x2d, 0x3f, 0x70, 0x3c, 0x96, 0x96, 0x76, 0x6c, 0xfa],
+                &[
+                    0xcd, 0x90, 0x44, 0xd2, 0xb7, 0x1f, 0xdb, 0x81, 0x20, 0xea, 0x60, 0xc0, 0x64, 0x35, 0xac, 0xba,
+                    0xfb, 0x11, 0xa8, 0x2e, 0x2f, 0x07, 0x1d, 0x7c, 0xa4, 0xa5, 0xeb, 0xd9, 0x3a, 0x80, 0x3b, 0xa8,
+                    0x7f,
+                ],
+                12,
+                8,
+                &[
+                    0x00, 0x97, 0x69, 0xec, 0xab, 0xdf, 0x48, 0x62, 0x55, 0x94, 0xc5, 0x92, 0x51, 0xe6, 0x03, 0x57,
+                    0x22, 0x67, 0x5e, 0x04, 0xc8, 0x47, 0x09, 0x9e, 0x5a, 0xe0, 0x70, 0x45, 0x51,
+                ],
+            ),
+            // #19
+            (
+                &[0x00, 0x42, 0xff, 0xf8, 0xf1, 0x95, 0x1c, 0x3c, 0x96, 0x96, 0x76, 0x6c, 0xfa],
+                &[
+                    0xd8, 0x5b, 0xc7, 0xe6, 0x9f, 0x94, 0x4f, 0xb8, 0x8a, 0x19, 0xb9, 0x50, 0xbc, 0xf7, 0x1a, 0x01,
+                    0x8e, 0x5e, 0x67, 0x01, 0xc9, 0x17, 0x87, 0x65, 0x98, 0x09, 0xd6, 0x7d, 0xbe, 0xdd, 0x18,
+                ],
+                8,
+                10,
+                &[
+                    0xbc, 0x21, 0x8d, 0xaa, 0x94, 0x74, 0x27, 0xb6, 0xdb, 0x38, 0x6a, 0x99, 0xac, 0x1a, 0xef, 0x23,
+                    0xad, 0xe0, 0xb5, 0x29, 0x39, 0xcb, 0x6a, 0x63, 0x7c, 0xf9, 0xbe, 0xc2, 0x40, 0x88, 0x97, 0xc6,
+                    0xba,
+                ],
+            ),
+        ];
+
+        for aes in [aes::Aes128::new_portable(&key), aes::Aes128::new(&key)].iter() {
+            for (nonce, pkt, header_len, tag_len, exp_cipher) in vectors.iter() {
+                let (header, msg) = pkt.split_at(*header_len);
+
+                let res = encrypt(msg, aes, nonce, header, *tag_len).unwrap();
+                assert_eq!(res[..], exp_cipher[..]);
+
+                assert_eq!(decrypt(&res, aes, nonce, header, *tag_len).unwrap(), msg);
+            }
+        }
+    }
+}
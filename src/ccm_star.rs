@@ -0,0 +1,275 @@
+//! CCM* mode for IEEE 802.15.4 (IEEE 802.15.4-2006 Annex B)
+//!
+//! Extends CCM with encryption-only (M = 0) and MIC-only security levels,
+//! using a 13 byte nonce built from the source address, frame counter and security level.
+//!
+//! Secured payloads are returned with the (encrypted) MIC appended.
+
+use alloc::vec::Vec;
+
+use crate::ccm;
+use crate::cbc::{self, IV_LEN};
+use crate::ctr::{self, Endian};
+use crate::{aes, ct_eq, BlockCipher, Error};
+
+/// Nonce length: source address || frame counter || security level
+pub const NONCE_LEN: usize = 13;
+
+/// Extended source address length
+pub const ADDR_LEN: usize = 8;
+
+/// No security
+pub const SEC_NONE: u8 = 0x00;
+/// Authentication only, 4 byte MIC
+pub const SEC_MIC_32: u8 = 0x01;
+/// Authentication only, 8 byte MIC
+pub const SEC_MIC_64: u8 = 0x02;
+/// Authentication only, 16 byte MIC
+pub const SEC_MIC_128: u8 = 0x03;
+/// Encryption only
+pub const SEC_ENC: u8 = 0x04;
+/// Encryption, 4 byte MIC
+pub const SEC_ENC_MIC_32: u8 = 0x05;
+/// Encryption, 8 byte MIC
+pub const SEC_ENC_MIC_64: u8 = 0x06;
+/// Encryption, 16 byte MIC
+pub const SEC_ENC_MIC_128: u8 = 0x07;
+
+// Maximum payload length with L = 2 length bytes
+const MAX_MSG_LEN: usize = 0xffff;
+
+/// Build the CCM* nonce for a frame
+///
+/// @param src_addr: Extended source address, most significant byte first
+/// @param frame_counter: Frame counter
+/// @param level: Security level
+pub fn nonce(src_addr: &[u8; ADDR_LEN], frame_counter: u32, level: u8) -> [u8; NONCE_LEN] {
+    let mut nonce = [0_u8; NONCE_LEN];
+    nonce[..ADDR_LEN].copy_from_slice(src_addr);
+    nonce[ADDR_LEN..NONCE_LEN - 1].copy_from_slice(&frame_counter.to_be_bytes());
+    nonce[NONCE_LEN - 1] = level;
+    nonce
+}
+
+/// Secure a frame payload in CCM* mode
+///
+/// The header is always authenticated, the payload is encrypted for levels with ENC,
+/// and authenticated for levels with a MIC
+///
+/// Returns the secured payload with the MIC appended
+pub fn encrypt<C: BlockCipher>(
+    payload: &[u8],
+    cipher: &C,
+    src_addr: &[u8; ADDR_LEN],
+    frame_counter: u32,
+    level: u8,
+    header: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let mic_len = mic_len(level)?;
+
+    if payload.len() > MAX_MSG_LEN {
+        return Err(Error::InvalidLength);
+    }
+
+    let nonce = nonce(src_addr, frame_counter, level);
+    let (ctr_nonce, a0_count) = ccm::counter_block(&nonce);
+
+    let mut res = if is_encrypted(level) {
+        let mut count = a0_count + 1;
+        ctr::encrypt(payload, cipher, ctr_nonce, &mut count, &Endian::Big)
+    } else {
+        payload.to_vec()
+    };
+
+    if mic_len != 0 {
+        let tag = mic(cipher, &nonce, payload, header, level, mic_len)?;
+        res.extend_from_slice(&ccm::encrypt_tag(cipher, ctr_nonce, a0_count, &tag[..mic_len]));
+    }
+
+    Ok(res)
+}
+
+/// Verify and unsecure a frame payload in CCM* mode
+///
+/// Secured payload must have the MIC for the security level appended
+///
+/// No payload is returned unless the MIC is valid
+pub fn decrypt<C: BlockCipher>(
+    secured: &[u8],
+    cipher: &C,
+    src_addr: &[u8; ADDR_LEN],
+    frame_counter: u32,
+    level: u8,
+    header: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let mic_len = mic_len(level)?;
+
+    if secured.len() < mic_len || secured.len() - mic_len > MAX_MSG_LEN {
+        return Err(Error::InvalidLength);
+    }
+
+    let (secured, enc_tag) = secured.split_at(secured.len() - mic_len);
+
+    let nonce = nonce(src_addr, frame_counter, level);
+    let (ctr_nonce, a0_count) = ccm::counter_block(&nonce);
+
+    let payload = if is_encrypted(level) {
+        let mut count = a0_count + 1;
+        ctr::decrypt(secured, cipher, ctr_nonce, &mut count, &Endian::Big)
+    } else {
+        secured.to_vec()
+    };
+
+    if mic_len != 0 {
+        let tag = ccm::encrypt_tag(cipher, ctr_nonce, a0_count, enc_tag);
+        let exp_tag = mic(cipher, &nonce, &payload, header, level, mic_len)?;
+
+        if !ct_eq(&exp_tag[..mic_len], &tag) {
+            return Err(Error::InvalidTag);
+        }
+    }
+
+    Ok(payload)
+}
+
+// MIC length M for the security level
+fn mic_len(level: u8) -> Result<usize, Error> {
+    match level {
+        SEC_NONE | SEC_ENC => Ok(0),
+        SEC_MIC_32 | SEC_ENC_MIC_32 => Ok(4),
+        SEC_MIC_64 | SEC_ENC_MIC_64 => Ok(8),
+        SEC_MIC_128 | SEC_ENC_MIC_128 => Ok(16),
+        _ => Err(Error::InvalidSecurityLevel),
+    }
+}
+
+fn is_encrypted(level: u8) -> bool {
+    level & SEC_ENC != 0
+}
+
+// T = last block of CBC( B , key ) with a zero IV
+//
+// a = header, m = payload      with encryption
+// a = header || payload, m = {}  without encryption
+fn mic<C: BlockCipher>(
+    cipher: &C,
+    nonce: &[u8],
+    payload: &[u8],
+    header: &[u8],
+    level: u8,
+    mic_len: usize,
+) -> Result<Vec<u8>, Error> {
+    let b = if is_encrypted(level) {
+        ccm::format_input(payload, nonce, header, mic_len)
+    } else {
+        ccm::format_input(&[], nonce, &[header, payload].concat(), mic_len)
+    };
+
+    let chain = cbc::encrypt(&b, cipher, &[0_u8; IV_LEN])?;
+
+    Ok(chain[chain.len() - aes::BLOCK_LEN..].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // IEEE 802.15.4-2006 Annex C.2 frames share the key, source address and frame counter
+    const KEY: [u8; aes::KEY_LEN_128] = [
+        0xc0, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xcb, 0xcc, 0xcd, 0xce, 0xcf,
+    ];
+    const SRC_ADDR: [u8; ADDR_LEN] = [0xac, 0xde, 0x48, 0x00, 0x00, 0x00, 0x00, 0x01];
+    const FRAME_COUNTER: u32 = 5;
+
+    #[test]
+    fn check_nonce() {
+        let exp_nonce = [0xac, 0xde, 0x48, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x05, 0x02];
+        assert_eq!(nonce(&SRC_ADDR, FRAME_COUNTER, SEC_MIC_64), exp_nonce);
+    }
+
+    // IEEE 802.15.4-2006 C.2.1: MAC beacon frame, MIC-64
+    #[test]
+    fn ieee_beacon_frame() {
+        let header = [
+            0x08, 0xd0, 0x84, 0x21, 0x43, 0x01, 0x00, 0x00, 0x00, 0x00, 0x48, 0xde, 0xac, 0x02, 0x05, 0x00,
+            0x00, 0x00, 0x55, 0xcf, 0x00, 0x00, 0x51, 0x52, 0x53, 0x54,
+        ];
+        let exp_mic = [0x22, 0x3b, 0xc1, 0xec, 0x84, 0x1a, 0xb5, 0x53];
+
+        for aes in [aes::Aes128::new_portable(&KEY), aes::Aes128::new(&KEY)].iter() {
+            let res = encrypt(&[], aes, &SRC_ADDR, FRAME_COUNTER, SEC_MIC_64, &header).unwrap();
+            assert_eq!(res, exp_mic);
+
+            assert!(decrypt(&res, aes, &SRC_ADDR, FRAME_COUNTER, SEC_MIC_64, &header).unwrap().is_empty());
+            assert!(decrypt(&res, aes, &SRC_ADDR, FRAME_COUNTER + 1, SEC_MIC_64, &header).is_err());
+        }
+    }
+
+    // IEEE 802.15.4-2006 C.2.2: MAC data frame, ENC
+    #[test]
+    fn ieee_data_frame() {
+        let header = [
+            0x69, 0xdc, 0x84, 0x21, 0x43, 0x02, 0x00, 0x00, 0x00, 0x00, 0x48, 0xde, 0xac, 0x01, 0x00, 0x00,
+            0x00, 0x00, 0x48, 0xde, 0xac, 0x04, 0x05, 0x00, 0x00, 0x00,
+        ];
+        let payload = [0x61, 0x62, 0x63, 0x64];
+        let exp_secured = [0xd4, 0x3e, 0x02, 0x2b];
+
+        let aes = aes::Aes128::new(&KEY);
+
+        let res = encrypt(&payload, &aes, &SRC_ADDR, FRAME_COUNTER, SEC_ENC, &header).unwrap();
+        assert_eq!(res, exp_secured);
+
+        assert_eq!(decrypt(&res, &aes, &SRC_ADDR, FRAME_COUNTER, SEC_ENC, &header).unwrap(), payload);
+    }
+
+    // IEEE 802.15.4-2006 C.2.3: MAC command frame, ENC-MIC-64
+    #[test]
+    fn ieee_command_frame() {
+        let header = [
+            0x2b, 0xdc, 0x84, 0x21, 0x43, 0x02, 0x00, 0x00, 0x00, 0x00, 0x48, 0xde, 0xac, 0xff, 0xff, 0x01,
+            0x00, 0x00, 0x00, 0x00, 0x48, 0xde, 0xac, 0x06, 0x05, 0x00, 0x00, 0x00, 0x01,
+        ];
+        let payload = [0xce];
+        let exp_secured = [0xd8, 0x4f, 0xde, 0x52, 0x90, 0x61, 0xf9, 0xc6, 0xf1];
+
+        let aes = aes::Aes128::new(&KEY);
+
+        let res = encrypt(&payload, &aes, &SRC_ADDR, FRAME_COUNTER, SEC_ENC_MIC_64, &header).unwrap();
+        assert_eq!(res, exp_secured);
+
+        assert_eq!(decrypt(&res, &aes, &SRC_ADDR, FRAME_COUNTER, SEC_ENC_MIC_64, &header).unwrap(), payload);
+
+        // tampered header, payload or MIC must fail verification
+        assert!(decrypt(&res, &aes, &SRC_ADDR, FRAME_COUNTER, SEC_ENC_MIC_64, &header[1..]).is_err());
+
+        let mut tampered = res;
+        tampered[0] ^= 0x01;
+        assert!(decrypt(&tampered, &aes, &SRC_ADDR, FRAME_COUNTER, SEC_ENC_MIC_64, &header).is_err());
+
+        tampered[0] ^= 0x01;
+        tampered[8] ^= 0x80;
+        assert!(decrypt(&tampered, &aes, &SRC_ADDR, FRAME_COUNTER, SEC_ENC_MIC_64, &header).is_err());
+    }
+
+    #[test]
+    fn check_security_levels() {
+        let aes = aes::Aes128::new(&KEY);
+        let header = [0x41, 0xd8, 0x01];
+        let payload = [0x5a_u8; 21];
+
+        for level in SEC_NONE..=SEC_ENC_MIC_128 {
+            let res = encrypt(&payload, &aes, &SRC_ADDR, FRAME_COUNTER, level, &header).unwrap();
+            assert_eq!(res.len(), payload.len() + mic_len(level).unwrap());
+
+            // MIC-only levels leave the payload in the clear
+            assert_eq!(res[..payload.len()] == payload[..], !is_encrypted(level));
+
+            let pt = decrypt(&res, &aes, &SRC_ADDR, FRAME_COUNTER, level, &header).unwrap();
+            assert_eq!(pt, payload);
+        }
+
+        assert!(encrypt(&payload, &aes, &SRC_ADDR, FRAME_COUNTER, 0x08, &header).is_err());
+        assert!(decrypt(&payload, &aes, &SRC_ADDR, FRAME_COUNTER, 0xff, &header).is_err());
+    }
+}
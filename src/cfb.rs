@@ -0,0 +1,345 @@
+//! Cipher Feedback mode (NIST SP 800-38A Section 6.3)
+//!
+//! The cipher output is XORed with the plaintext one segment at a time,
+//! and each cipher segment is shifted back into the input block.
+//!
+//! Supported segment sizes are 1 bit (CFB-1), 8 bits (CFB-8) and 128 bits (CFB-128).
+//! CFB-1 processes each byte most significant bit first.
+//! A partial final CFB-128 segment is truncated, so no padding is required.
+
+use alloc::vec::Vec;
+
+use crate::{aes, to_blocks, xor, BlockCipher};
+
+/// Initialization vector length for AES-CFB
+pub const IV_LEN: usize = 16_usize;
+
+/// Segment size for feeding back cipher output
+#[derive(Debug, PartialEq)]
+pub enum Segment {
+    /// CFB-1: one bit per cipher invocation
+    Bit,
+    /// CFB-8: one byte per cipher invocation
+    Byte,
+    /// CFB-128: one full block per cipher invocation
+    Block,
+}
+
+/// Encrypt a message in CFB mode
+///
+/// Message can be any length, the segment size determines the CFB variant
+pub fn encrypt<C: BlockCipher>(
+    msg: &[u8],
+    cipher: &C,
+    iv: &[u8; IV_LEN],
+    segment: &Segment,
+) -> Vec<u8> {
+    match segment {
+        Segment::Bit => cfb_1(msg, cipher, iv, false),
+        Segment::Byte => cfb_8(msg, cipher, iv, false),
+        Segment::Block => encrypt_128(msg, cipher, iv),
+    }
+}
+
+/// Decrypt a message in CFB mode
+///
+/// Ciphertext can be any length, the segment size determines the CFB variant
+pub fn decrypt<C: BlockCipher>(
+    ciphertext: &[u8],
+    cipher: &C,
+    iv: &[u8; IV_LEN],
+    segment: &Segment,
+) -> Vec<u8> {
+    match segment {
+        Segment::Bit => cfb_1(ciphertext, cipher, iv, true),
+        Segment::Byte => cfb_8(ciphertext, cipher, iv, true),
+        Segment::Block => decrypt_128(ciphertext, cipher, iv),
+    }
+}
+
+// C_j = P_j ^ MSB_s( CIPHER( I_j , key ) )
+// I_j+1 = C_j                                  s = 128
+fn encrypt_128<C: BlockCipher>(msg: &[u8], cipher: &C, iv: &[u8; IV_LEN]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(msg.len());
+    let mut input = *iv;
+
+    for block in msg.chunks(aes::BLOCK_LEN) {
+        let output = cipher.encrypt_block(&input);
+
+        // output is at least as long as the block, safe to just unwrap here
+        let cipher_block = xor(block, &output[..block.len()]).unwrap();
+        res.extend_from_slice(&cipher_block);
+
+        // only the final segment can be partial, and it is never fed back
+        if block.len() == aes::BLOCK_LEN {
+            input.copy_from_slice(&cipher_block);
+        }
+    }
+
+    res
+}
+
+// P_j = C_j ^ MSB_s( CIPHER( C_j-1 , key ) )   s = 128
+fn decrypt_128<C: BlockCipher>(ciphertext: &[u8], cipher: &C, iv: &[u8; IV_LEN]) -> Vec<u8> {
+    let len = ciphertext.len();
+    if len == 0 {
+        return Vec::new();
+    }
+
+    // inputs are the IV and all previous cipher blocks, encrypt them all at once
+    let prev_len = (len - 1) / aes::BLOCK_LEN * aes::BLOCK_LEN;
+    let mut inputs = Vec::with_capacity(prev_len / aes::BLOCK_LEN + 1);
+    inputs.push(*iv);
+    inputs.extend_from_slice(&to_blocks(&ciphertext[..prev_len]));
+
+    cipher.encrypt_blocks(&mut inputs);
+
+    let mut res = Vec::with_capacity(len);
+
+    for (block, output) in ciphertext.chunks(aes::BLOCK_LEN).zip(inputs.iter()) {
+        // output is at least as long as the block, safe to just unwrap here
+        res.extend_from_slice(&xor(block, &output[..block.len()]).unwrap());
+    }
+
+    res
+}
+
+// O_j = CIPHER( I_j , key )
+// I_j+1 = LSB_120( I_j ) || C_j                s = 8
+fn cfb_8<C: BlockCipher>(text: &[u8], cipher: &C, iv: &[u8; IV_LEN], decrypt: bool) -> Vec<u8> {
+    let mut res = Vec::with_capacity(text.len());
+    let mut input = *iv;
+
+    for &byte in text.iter() {
+        let output = cipher.encrypt_block(&input)[0] ^ byte;
+        let feedback = if decrypt { byte } else { output };
+
+        input.copy_within(1.., 0);
+        input[aes::BLOCK_LEN - 1] = feedback;
+
+        res.push(output);
+    }
+
+    res
+}
+
+// O_j = CIPHER( I_j , key )
+// I_j+1 = LSB_127( I_j ) || C_j                s = 1
+fn cfb_1<C: BlockCipher>(text: &[u8], cipher: &C, iv: &[u8; IV_LEN], decrypt: bool) -> Vec<u8> {
+    let mut res = Vec::with_capacity(text.len());
+    let mut input = u128::from_be_bytes(*iv);
+
+    for &byte in text.iter() {
+        let mut out_byte = 0_u8;
+
+        for shift in (0..8).rev() {
+            let in_bit = (byte >> shift) & 1;
+            let out_bit = (cipher.encrypt_block(&input.to_be_bytes())[0] >> 7) ^ in_bit;
+            let feedback = if decrypt { in_bit } else { out_bit };
+
+            input = (input << 1) | feedback as u128;
+            out_byte |= out_bit << shift;
+        }
+
+        res.push(out_byte);
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_128: [u8; aes::KEY_LEN_128] = [
+        0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,
+    ];
+
+    const KEY_192: [u8; aes::KEY_LEN_192] = [
+        0x8e, 0x73, 0xb0, 0xf7, 0xda, 0x0e, 0x64, 0x52, 0xc8, 0x10, 0xf3, 0x2b, 0x80, 0x90, 0x79, 0xe5,
+        0x62, 0xf8, 0xea, 0xd2, 0x52, 0x2c, 0x6b, 0x7b,
+    ];
+
+    const KEY_256: [u8; aes::KEY_LEN_256] = [
+        0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d, 0x77, 0x81,
+        0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3, 0x09, 0x14, 0xdf, 0xf4,
+    ];
+
+    const IV: [u8; IV_LEN] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    ];
+
+    // NIST SP 800-38A plaintext
+    const MSG: [u8; 64] = [
+        0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a,
+        0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51,
+        0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef,
+        0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10,
+    ];
+
+    // NIST SP 800-38A F.3.1 and F.3.2: CFB1-AES128
+    #[test]
+    fn nist_cfb_1_aes_128() {
+        let exp_cipher = [0x68, 0xb3];
+
+        for aes in [aes::Aes128::new_portable(&KEY_128), aes::Aes128::new(&KEY_128)].iter() {
+            let cipher = encrypt(&MSG[..2], aes, &IV, &Segment::Bit);
+            assert_eq!(cipher, exp_cipher);
+
+            assert_eq!(decrypt(&cipher, aes, &IV, &Segment::Bit), MSG[..2]);
+        }
+    }
+
+    // NIST SP 800-38A F.3.3 and F.3.4: CFB1-AES192
+    #[test]
+    fn nist_cfb_1_aes_192() {
+        let exp_cipher = [0x93, 0x59];
+
+        let aes = aes::Aes192::new(&KEY_192);
+
+        let cipher = encrypt(&MSG[..2], &aes, &IV, &Segment::Bit);
+        assert_eq!(cipher, exp_cipher);
+
+        assert_eq!(decrypt(&cipher, &aes, &IV, &Segment::Bit), MSG[..2]);
+    }
+
+    // NIST SP 800-38A F.3.5 and F.3.6: CFB1-AES256
+    #[test]
+    fn nist_cfb_1_aes_256() {
+        let exp_cipher = [0x90, 0x29];
+
+        let aes = aes::Aes256::new(&KEY_256);
+
+        let cipher = encrypt(&MSG[..2], &aes, &IV, &Segment::Bit);
+        assert_eq!(cipher, exp_cipher);
+
+        assert_eq!(decrypt(&cipher, &aes, &IV, &Segment::Bit), MSG[..2]);
+    }
+
+    // NIST SP 800-38A F.3.7 and F.3.8: CFB8-AES128
+    #[test]
+    fn nist_cfb_8_aes_128() {
+        let exp_cipher = [
+            0x3b, 0x79, 0x42, 0x4c, 0x9c, 0x0d, 0xd4, 0x36, 0xba, 0xce, 0x9e, 0x0e, 0xd4, 0x58, 0x6a, 0x4f,
+            0x32, 0xb9,
+        ];
+
+        for aes in [aes::Aes128::new_portable(&KEY_128), aes::Aes128::new(&KEY_128)].iter() {
+            let cipher = encrypt(&MSG[..18], aes, &IV, &Segment::Byte);
+            assert_eq!(cipher, exp_cipher);
+
+            assert_eq!(decrypt(&cipher, aes, &IV, &Segment::Byte), MSG[..18]);
+        }
+    }
+
+    // NIST SP 800-38A F.3.9 and F.3.10: CFB8-AES192
+    #[test]
+    fn nist_cfb_8_aes_192() {
+        let exp_cipher = [
+            0xcd, 0xa2, 0x52, 0x1e, 0xf0, 0xa9, 0x05, 0xca, 0x44, 0xcd, 0x05, 0x7c, 0xbf, 0x0d, 0x47, 0xa0,
+            0x67, 0x8a,
+        ];
+
+        let aes = aes::Aes192::new(&KEY_192);
+
+        let cipher = encrypt(&MSG[..18], &aes, &IV, &Segment::Byte);
+        assert_eq!(cipher, exp_cipher);
+
+        assert_eq!(decrypt(&cipher, &aes, &IV, &Segment::Byte), MSG[..18]);
+    }
+
+    // NIST SP 800-38A F.3.11 and F.3.12: CFB8-AES256
+    #[test]
+    fn nist_cfb_8_aes_256() {
+        let exp_cipher = [
+            0xdc, 0x1f, 0x1a, 0x85, 0x20, 0xa6, 0x4d, 0xb5, 0x5f, 0xcc, 0x8a, 0xc5, 0x54, 0x84, 0x4e, 0x88,
+            0x97, 0x00,
+        ];
+
+        let aes = aes::Aes256::new(&KEY_256);
+
+        let cipher = encrypt(&MSG[..18], &aes, &IV, &Segment::Byte);
+        assert_eq!(cipher, exp_cipher);
+
+        assert_eq!(decrypt(&cipher, &aes, &IV, &Segment::Byte), MSG[..18]);
+    }
+
+    // NIST SP 800-38A F.3.13 and F.3.14: CFB128-AES128
+    #[test]
+    fn nist_cfb_128_aes_128() {
+        let exp_cipher = [
+            0x3b, 0x3f, 0xd9, 0x2e, 0xb7, 0x2d, 0xad, 0x20, 0x33, 0x34, 0x49, 0xf8, 0xe8, 0x3c, 0xfb, 0x4a,
+            0xc8, 0xa6, 0x45, 0x37, 0xa0, 0xb3, 0xa9, 0x3f, 0xcd, 0xe3, 0xcd, 0xad, 0x9f, 0x1c, 0xe5, 0x8b,
+            0x26, 0x75, 0x1f, 0x67, 0xa3, 0xcb, 0xb1, 0x40, 0xb1, 0x80, 0x8c, 0xf1, 0x87, 0xa4, 0xf4, 0xdf,
+            0xc0, 0x4b, 0x05, 0x35, 0x7c, 0x5d, 0x1c, 0x0e, 0xea, 0xc4, 0xc6, 0x6f, 0x9f, 0xf7, 0xf2, 0xe6,
+        ];
+
+        for aes in [aes::Aes128::new_portable(&KEY_128), aes::Aes128::new(&KEY_128)].iter() {
+            let cipher = encrypt(&MSG, aes, &IV, &Segment::Block);
+            assert_eq!(cipher, exp_cipher);
+
+            assert_eq!(decrypt(&cipher, aes, &IV, &Segment::Block), MSG);
+        }
+    }
+
+    // NIST SP 800-38A F.3.15 and F.3.16: CFB128-AES192
+    #[test]
+    fn nist_cfb_128_aes_192() {
+        let exp_cipher = [
+            0xcd, 0xc8, 0x0d, 0x6f, 0xdd, 0xf1, 0x8c, 0xab, 0x34, 0xc2, 0x59, 0x09, 0xc9, 0x9a, 0x41, 0x74,
+            0x67, 0xce, 0x7f, 0x7f, 0x81, 0x17, 0x36, 0x21, 0x96, 0x1a, 0x2b, 0x70, 0x17, 0x1d, 0x3d, 0x7a,
+            0x2e, 0x1e, 0x8a, 0x1d, 0xd5, 0x9b, 0x88, 0xb1, 0xc8, 0xe6, 0x0f, 0xed, 0x1e, 0xfa, 0xc4, 0xc9,
+            0xc0, 0x5f, 0x9f, 0x9c, 0xa9, 0x83, 0x4f, 0xa0, 0x42, 0xae, 0x8f, 0xba, 0x58, 0x4b, 0x09, 0xff,
+        ];
+
+        for aes in [aes::Aes192::new_portable(&KEY_192), aes::Aes192::new(&KEY_192)].iter() {
+            let cipher = encrypt(&MSG, aes, &IV, &Segment::Block);
+            assert_eq!(cipher, exp_cipher);
+
+            assert_eq!(decrypt(&cipher, aes, &IV, &Segment::Block), MSG);
+        }
+    }
+
+    // NIST SP 800-38A F.3.17 and F.3.18: CFB128-AES256
+    #[test]
+    fn nist_cfb_128_aes_256() {
+        let exp_cipher = [
+            0xdc, 0x7e, 0x84, 0xbf, 0xda, 0x79, 0x16, 0x4b, 0x7e, 0xcd, 0x84, 0x86, 0x98, 0x5d, 0x38, 0x60,
+            0x39, 0xff, 0xed, 0x14, 0x3b, 0x28, 0xb1, 0xc8, 0x32, 0x11, 0x3c, 0x63, 0x31, 0xe5, 0x40, 0x7b,
+            0xdf, 0x10, 0x13, 0x24, 0x15, 0xe5, 0x4b, 0x92, 0xa1, 0x3e, 0xd0, 0xa8, 0x26, 0x7a, 0xe2, 0xf9,
+            0x75, 0xa3, 0x85, 0x74, 0x1a, 0xb9, 0xce, 0xf8, 0x20, 0x31, 0x62, 0x3d, 0x55, 0xb1, 0xe4, 0x71,
+        ];
+
+        for aes in [aes::Aes256::new_portable(&KEY_256), aes::Aes256::new(&KEY_256)].iter() {
+            let cipher = encrypt(&MSG, aes, &IV, &Segment::Block);
+            assert_eq!(cipher, exp_cipher);
+
+            assert_eq!(decrypt(&cipher, aes, &IV, &Segment::Block), MSG);
+        }
+    }
+
+    #[test]
+    fn check_partial_segment() {
+        let aes = aes::Aes128::new(&KEY_128);
+        let full = encrypt(&MSG, &aes, &IV, &Segment::Block);
+
+        // a truncated message encrypts to the truncated ciphertext
+        for len in 0..=MSG.len() {
+            let cipher = encrypt(&MSG[..len], &aes, &IV, &Segment::Block);
+            assert_eq!(cipher, full[..len]);
+
+            assert_eq!(decrypt(&cipher, &aes, &IV, &Segment::Block), MSG[..len]);
+        }
+    }
+
+    #[test]
+    fn check_segments_round_trip() {
+        let aes = aes::Aes128::new(&KEY_128);
+
+        for segment in [Segment::Bit, Segment::Byte, Segment::Block].iter() {
+            let cipher = encrypt(&MSG[..37], &aes, &IV, segment);
+            assert_eq!(cipher.len(), 37);
+            assert_eq!(decrypt(&cipher, &aes, &IV, segment), MSG[..37]);
+        }
+    }
+}
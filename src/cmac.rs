@@ -0,0 +1,296 @@
+//! Cipher-based Message Authentication Code (RFC 4493, NIST SP 800-38B)
+//!
+//! CBC-MAC over the message, with the final block masked by a subkey derived from the cipher:
+//! K1 for a complete final block, K2 for a padded final block.
+
+use crate::{aes, ct_eq, xor_equals, BlockCipher, Error};
+
+/// Full tag length
+pub const TAG_LEN: usize = 16;
+
+/// Minimum truncated tag length accepted by `verify`
+pub const MIN_TAG_LEN: usize = 4;
+
+// R_128 = 0^120 || 10000111
+const R_128: u8 = 0x87;
+
+/// Streaming CMAC computation
+///
+/// The final block is buffered until `finalize`, since its masking depends on it being complete
+pub struct Cmac<'a, C: BlockCipher> {
+    cipher: &'a C,
+    k1: [u8; aes::BLOCK_LEN],
+    k2: [u8; aes::BLOCK_LEN],
+    core: CbcCore,
+}
+
+impl<'a, C: BlockCipher> Cmac<'a, C> {
+    /// Start a CMAC computation, generating the subkeys from the cipher
+    pub fn new(cipher: &'a C) -> Self {
+        let (k1, k2) = subkeys(cipher);
+
+        Self {
+            cipher,
+            k1,
+            k2,
+            core: CbcCore::new(),
+        }
+    }
+
+    /// Absorb more of the message
+    pub fn update(&mut self, data: &[u8]) {
+        self.core.update(self.cipher, data);
+    }
+
+    /// Finish the computation, and return the full tag
+    pub fn finalize(self) -> [u8; TAG_LEN] {
+        // K1 masks a complete final block, K2 a padded one
+        self.core.finalize(self.cipher, &self.k1, &self.k2)
+    }
+
+    /// Finish the computation, and compare against a (possibly truncated) tag in constant time
+    ///
+    /// Tags must be at least MIN_TAG_LEN bytes, and are compared against the leftmost bytes of the full tag
+    pub fn verify(self, tag: &[u8]) -> Result<(), Error> {
+        if tag.len() < MIN_TAG_LEN || tag.len() > TAG_LEN {
+            return Err(Error::InvalidLength);
+        }
+
+        let exp_tag = self.finalize();
+
+        if ct_eq(&exp_tag[..tag.len()], tag) {
+            Ok(())
+        } else {
+            Err(Error::InvalidTag)
+        }
+    }
+}
+
+/// Compute the CMAC of a message
+pub fn mac<C: BlockCipher>(cipher: &C, msg: &[u8]) -> [u8; TAG_LEN] {
+    let mut cmac = Cmac::new(cipher);
+    cmac.update(msg);
+    cmac.finalize()
+}
+
+/// Verify a (possibly truncated) CMAC tag of a message in constant time
+pub fn verify<C: BlockCipher>(cipher: &C, msg: &[u8], tag: &[u8]) -> Result<(), Error> {
+    let mut cmac = Cmac::new(cipher);
+    cmac.update(msg);
+    cmac.verify(tag)
+}
+
+/// AES-CMAC from RFC 4493
+///
+/// @param key: AES-128 key
+/// @param msg: Message to be authenticated
+pub fn aes_cmac(key: &[u8; aes::KEY_LEN_128], msg: &[u8]) -> [u8; TAG_LEN] {
+    mac(&aes::Aes128::new(key), msg)
+}
+
+// Streaming CBC-MAC with a masked final block, shared by CMAC and XCBC
+//
+// The cipher is passed in on every call, so owners can keep it alongside the state
+pub(crate) struct CbcCore {
+    x: [u8; aes::BLOCK_LEN],
+    buf: [u8; aes::BLOCK_LEN],
+    buf_len: usize,
+}
+
+impl CbcCore {
+    pub(crate) fn new() -> Self {
+        Self {
+            x: [0_u8; aes::BLOCK_LEN],
+            buf: [0_u8; aes::BLOCK_LEN],
+            buf_len: 0,
+        }
+    }
+
+    pub(crate) fn update<C: BlockCipher>(&mut self, cipher: &C, mut data: &[u8]) {
+        while !data.is_empty() {
+            // only process a full buffer once more data arrives, it may be the final block
+            if self.buf_len == aes::BLOCK_LEN {
+                // X = CIPHER( X ^ M_i , key )
+                xor_equals(&mut self.x, &self.buf).unwrap();
+                self.x = cipher.encrypt_block(&self.x);
+                self.buf_len = 0;
+            }
+
+            let take = core::cmp::min(aes::BLOCK_LEN - self.buf_len, data.len());
+            self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&data[..take]);
+            self.buf_len += take;
+            data = &data[take..];
+        }
+    }
+
+    pub(crate) fn finalize<C: BlockCipher>(
+        mut self,
+        cipher: &C,
+        k_complete: &[u8; aes::BLOCK_LEN],
+        k_padded: &[u8; aes::BLOCK_LEN],
+    ) -> [u8; aes::BLOCK_LEN] {
+        // M_last = M_n ^ k_complete                  if M_n is complete
+        // M_last = ( M_n || 10^i ) ^ k_padded        otherwise
+        if self.buf_len == aes::BLOCK_LEN {
+            xor_equals(&mut self.buf, k_complete).unwrap();
+        } else {
+            self.buf[self.buf_len] = 0x80;
+            for byte in self.buf[self.buf_len + 1..].iter_mut() {
+                *byte = 0;
+            }
+            xor_equals(&mut self.buf, k_padded).unwrap();
+        }
+
+        // T = CIPHER( X ^ M_last , key )
+        xor_equals(&mut self.x, &self.buf).unwrap();
+        cipher.encrypt_block(&self.x)
+    }
+}
+
+// L = CIPHER( 0^128 , key ), K1 = dbl( L ), K2 = dbl( K1 )
+fn subkeys<C: BlockCipher>(cipher: &C) -> ([u8; aes::BLOCK_LEN], [u8; aes::BLOCK_LEN]) {
+    let l = cipher.encrypt_block(&[0_u8; aes::BLOCK_LEN]);
+    let k1 = dbl(&l);
+    let k2 = dbl(&k1);
+    (k1, k2)
+}
+
+// Doubling in GF(2^128): block << 1, reduced by R_128 if the MSB was set
+//
+// Uses a mask instead of a branch, so runtime does not depend on the (secret) block
+pub(crate) fn dbl(block: &[u8; aes::BLOCK_LEN]) -> [u8; aes::BLOCK_LEN] {
+    let mut out = [0_u8; aes::BLOCK_LEN];
+
+    for i in 0..aes::BLOCK_LEN - 1 {
+        out[i] = (block[i] << 1) | (block[i + 1] >> 7);
+    }
+
+    let msb_mask = 0_u8.wrapping_sub(block[0] >> 7);
+    out[aes::BLOCK_LEN - 1] = (block[aes::BLOCK_LEN - 1] << 1) ^ (R_128 & msb_mask);
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_128: [u8; aes::KEY_LEN_128] = [
+        0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,
+    ];
+
+    // RFC 4493 and SP 800-38B examples use prefixes of this message
+    const MSG: [u8; 64] = [
+        0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a,
+        0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51,
+        0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef,
+        0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10,
+    ];
+
+    #[test]
+    fn check_subkeys() {
+        // RFC 4493 Section 4: Subkey Generation
+        let aes = aes::Aes128::new(&KEY_128);
+        let exp_k1 = [0xfb, 0xee, 0xd6, 0x18, 0x35, 0x71, 0x33, 0x66, 0x7c, 0x85, 0xe0, 0x8f, 0x72, 0x36, 0xa8, 0xde];
+        let exp_k2 = [0xf7, 0xdd, 0xac, 0x30, 0x6a, 0xe2, 0x66, 0xcc, 0xf9, 0x0b, 0xc1, 0x1e, 0xe4, 0x6d, 0x51, 0x3b];
+
+        let (k1, k2) = subkeys(&aes);
+        assert_eq!(k1, exp_k1);
+        assert_eq!(k2, exp_k2);
+    }
+
+    #[test]
+    fn check_dbl() {
+        let mut block = [0_u8; aes::BLOCK_LEN];
+        block[aes::BLOCK_LEN - 1] = 0x01;
+        assert_eq!(dbl(&block)[aes::BLOCK_LEN - 1], 0x02);
+
+        // carry out of the MSB reduces by R_128
+        block = [0_u8; aes::BLOCK_LEN];
+        block[0] = 0x80;
+
+        let mut exp_block = [0_u8; aes::BLOCK_LEN];
+        exp_block[aes::BLOCK_LEN - 1] = R_128;
+        assert_eq!(dbl(&block), exp_block);
+    }
+
+    // RFC 4493 Section 4: Examples 1-4
+    #[test]
+    fn rfc_test_vectors() {
+        let exp_tags = [
+            (0, [0xbb, 0x1d, 0x69, 0x29, 0xe9, 0x59, 0x37, 0x28, 0x7f, 0xa3, 0x7d, 0x12, 0x9b, 0x75, 0x67, 0x46]),
+            (16, [0x07, 0x0a, 0x16, 0xb4, 0x6b, 0x4d, 0x41, 0x44, 0xf7, 0x9b, 0xdd, 0x9d, 0xd0, 0x4a, 0x28, 0x7c]),
+            (40, [0xdf, 0xa6, 0x67, 0x47, 0xde, 0x9a, 0xe6, 0x30, 0x30, 0xca, 0x32, 0x61, 0x14, 0x97, 0xc8, 0x27]),
+            (64, [0x51, 0xf0, 0xbe, 0xbf, 0x7e, 0x3b, 0x9d, 0x92, 0xfc, 0x49, 0x74, 0x17, 0x79, 0x36, 0x3c, 0xfe]),
+        ];
+
+        for aes in [aes::Aes128::new_portable(&KEY_128), aes::Aes128::new(&KEY_128)].iter() {
+            for (len, exp_tag) in exp_tags.iter() {
+                assert_eq!(mac(aes, &MSG[..*len]), *exp_tag);
+                assert!(verify(aes, &MSG[..*len], exp_tag).is_ok());
+            }
+        }
+
+        assert_eq!(aes_cmac(&KEY_128, &MSG[..40]), exp_tags[2].1);
+    }
+
+    // NIST SP 800-38B D.3: CMAC-AES256 Examples 9-12
+    #[test]
+    fn nist_test_vectors_aes_256() {
+        let key = [
+            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d, 0x77, 0x81,
+            0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3, 0x09, 0x14, 0xdf, 0xf4,
+        ];
+        let exp_tags = [
+            (0, [0x02, 0x89, 0x62, 0xf6, 0x1b, 0x7b, 0xf8, 0x9e, 0xfc, 0x6b, 0x55, 0x1f, 0x46, 0x67, 0xd9, 0x83]),
+            (16, [0x28, 0xa7, 0x02, 0x3f, 0x45, 0x2e, 0x8f, 0x82, 0xbd, 0x4b, 0xf2, 0x8d, 0x8c, 0x37, 0xc3, 0x5c]),
+            (40, [0xaa, 0xf3, 0xd8, 0xf1, 0xde, 0x56, 0x40, 0xc2, 0x32, 0xf5, 0xb1, 0x69, 0xb9, 0xc9, 0x11, 0xe6]),
+            (64, [0xe1, 0x99, 0x21, 0x90, 0x54, 0x9f, 0x6e, 0xd5, 0x69, 0x6a, 0x2c, 0x05, 0x6c, 0x31, 0x54, 0x10]),
+        ];
+
+        let aes = aes::Aes256::new(&key);
+
+        for (len, exp_tag) in exp_tags.iter() {
+            assert_eq!(mac(&aes, &MSG[..*len]), *exp_tag);
+        }
+    }
+
+    #[test]
+    fn check_streaming() {
+        let aes = aes::Aes128::new(&KEY_128);
+        let exp_tag = mac(&aes, &MSG);
+
+        // split at every offset, including block boundaries and empty updates
+        for split in 0..=MSG.len() {
+            let mut cmac = Cmac::new(&aes);
+            cmac.update(&MSG[..split]);
+            cmac.update(&[]);
+            cmac.update(&MSG[split..]);
+            assert_eq!(cmac.finalize(), exp_tag);
+        }
+
+        let mut cmac = Cmac::new(&aes);
+        for byte in MSG.chunks(1) {
+            cmac.update(byte);
+        }
+        assert_eq!(cmac.finalize(), exp_tag);
+    }
+
+    #[test]
+    fn check_truncated_verify() {
+        let aes = aes::Aes128::new(&KEY_128);
+        let tag = mac(&aes, &MSG[..40]);
+
+        for len in MIN_TAG_LEN..=TAG_LEN {
+            assert!(verify(&aes, &MSG[..40], &tag[..len]).is_ok());
+            assert!(verify(&aes, &MSG[..39], &tag[..len]).is_err());
+        }
+
+        // too short to be meaningful
+        assert!(verify(&aes, &MSG[..40], &tag[..MIN_TAG_LEN - 1]).is_err());
+
+        let mut bad_tag = tag;
+        bad_tag[TAG_LEN - 1] ^= 0x01;
+        assert!(verify(&aes, &MSG[..40], &bad_tag).is_err());
+    }
+}
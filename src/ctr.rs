@@ -1,6 +1,6 @@
 use alloc::vec::Vec;
 
-use crate::{aes, xor};
+use crate::{aes, xor, BlockCipher, PAR_BLOCKS};
 
 /// Nonce length for key stream input
 pub const NONCE_LEN: usize = 8;
@@ -15,35 +15,35 @@ pub enum Endian {
     Little,
 }
 
-/// Encrypt a given plaintext using AES-128-CTR
+/// Encrypt a given plaintext using CTR mode
 /// Supply the nonce and initial count for encryption
 /// The mode determines the Endian interpretation of the nonce and counter
-pub fn encrypt(
+pub fn encrypt<C: BlockCipher>(
     plaintext: &[u8],
-    key: &[u8; aes::KEY_LEN_128],
+    cipher: &C,
     nonce: u64,
     count: &mut u64,
     mode: &Endian,
     ) -> Vec<u8> {
-    ctr_inner(&plaintext, &key, nonce, count, &mode)
+    ctr_inner(plaintext, cipher, nonce, count, mode)
 }
 
-/// Decrypt a given ciphertext using AES-128-CTR
+/// Decrypt a given ciphertext using CTR mode
 /// Supply the nonce and initial count for decryption
 /// The mode determines the Endian interpretation of the nonce and counter
-pub fn decrypt(
+pub fn decrypt<C: BlockCipher>(
     ciphertext: &[u8],
-    key: &[u8; aes::KEY_LEN_128],
+    cipher: &C,
     nonce: u64,
     count: &mut u64,
     mode: &Endian,
     ) -> Vec<u8> {
-    ctr_inner(&ciphertext, &key, nonce, count, &mode)
+    ctr_inner(ciphertext, cipher, nonce, count, mode)
 }
 
-fn ctr_inner(
+fn ctr_inner<C: BlockCipher>(
     text: &[u8],
-    key: &[u8; aes::KEY_LEN_128],
+    cipher: &C,
     nonce: u64,
     count: &mut u64,
     mode: &Endian,
@@ -60,12 +60,52 @@ fn ctr_inner(
     let mut input = [0_u8; aes::BLOCK_LEN];
     input[..NONCE_LEN].copy_from_slice(&nonce_bytes);
 
-    for block in text.chunks(aes::BLOCK_LEN) {
-        let stream = ctr_inner_cipher(&mut input, &key, *count, &mode);
-        // block and stream guaranteed to be the same length, safe to just unwrap here
-        let output = xor(block, &stream[..block.len()]).unwrap();
-        res.extend_from_slice(&output);
-        *count += 1;
+    let mut stream = [[0_u8; aes::BLOCK_LEN]; PAR_BLOCKS];
+
+    for chunk in text.chunks(aes::BLOCK_LEN * PAR_BLOCKS) {
+        let n_block = get_block_count(chunk.len()) as usize;
+
+        // counter blocks are independent, encrypt them together
+        for stream_block in stream[..n_block].iter_mut() {
+            set_count(&mut input, *count, mode);
+            *stream_block = input;
+            *count += 1;
+        }
+
+        cipher.encrypt_blocks(&mut stream[..n_block]);
+
+        for (block, stream_block) in chunk.chunks(aes::BLOCK_LEN).zip(stream.iter()) {
+            // block and stream guaranteed to be the same length, safe to just unwrap here
+            let output = xor(block, &stream_block[..block.len()]).unwrap();
+            res.extend_from_slice(&output);
+        }
+    }
+
+    res
+}
+
+// CTR mode with the whole block as a 128-bit big-endian counter, wrapping modulo 2^128
+//
+// Used by modes that derive the initial counter block from a MAC (EAX, SIV)
+pub(crate) fn ctr_128<C: BlockCipher>(cipher: &C, mut counter: u128, text: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(text.len());
+    let mut stream = [[0_u8; aes::BLOCK_LEN]; PAR_BLOCKS];
+
+    for chunk in text.chunks(aes::BLOCK_LEN * PAR_BLOCKS) {
+        let n_block = get_block_count(chunk.len()) as usize;
+
+        // counter blocks are independent, encrypt them together
+        for stream_block in stream[..n_block].iter_mut() {
+            *stream_block = counter.to_be_bytes();
+            counter = counter.wrapping_add(1);
+        }
+
+        cipher.encrypt_blocks(&mut stream[..n_block]);
+
+        for (block, stream_block) in chunk.chunks(aes::BLOCK_LEN).zip(stream.iter()) {
+            // block is at most as long as the stream, safe to just unwrap here
+            res.extend_from_slice(&xor(block, &stream_block[..block.len()]).unwrap());
+        }
     }
 
     res
@@ -73,24 +113,27 @@ fn ctr_inner(
 
 /// Get the next block of the keystream
 // NOTE: separated into own function to help debugging
-pub fn ctr_inner_cipher(
+pub fn ctr_inner_cipher<C: BlockCipher>(
     input: &mut [u8; aes::BLOCK_LEN],
-    key: &[u8; aes::KEY_LEN_128],
+    cipher: &C,
     count: u64,
     mode: &Endian) -> [u8; aes::BLOCK_LEN] {
+    set_count(input, count, mode);
+
+    cipher.encrypt_block(input)
+}
+
+// Write the counter into the last COUNT_LEN bytes of the key stream input
+fn set_count(input: &mut [u8; aes::BLOCK_LEN], count: u64, mode: &Endian) {
     let count_bytes = match mode {
         Endian::Big => count.to_be_bytes(),
         Endian::Little => count.to_le_bytes(),
     };
 
     input[COUNT_LEN..].copy_from_slice(&count_bytes);
-
-    aes::aes_128(&input, &key)
 }
 
 // Get the number of blocks of key stream needed for a give text length
-// Useful for debugging purposes
-#[allow(dead_code)]
 fn get_block_count(len: usize) -> u64 {
     ((len / aes::BLOCK_LEN) + ((len % aes::BLOCK_LEN) != 0) as usize) as u64
 }
@@ -102,6 +145,12 @@ mod tests {
     #[test]
     fn nist_encryption_test_vectors() {
         let key = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+
+        nist_encryption_test_vectors_with(&aes::Aes128::new_portable(&key));
+        nist_encryption_test_vectors_with(&aes::Aes128::new(&key));
+    }
+
+    fn nist_encryption_test_vectors_with(aes: &aes::Aes128) {
         let nonce = u64::from_be_bytes([0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7]); 
         let mut init_count = u64::from_be_bytes([0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff]);
         let mut count = init_count;
@@ -114,11 +163,11 @@ mod tests {
         let mut input = [0_u8; aes::BLOCK_LEN];
         input[..NONCE_LEN].copy_from_slice(&nonce.to_be_bytes());
 
-        let mut output = ctr_inner_cipher(&mut input, &key, count, &Endian::Big);
+        let mut output = ctr_inner_cipher(&mut input, aes, count, &Endian::Big);
         assert_eq!(in_block_1, input);
         assert_eq!(out_block_1, output);
 
-        let mut ciphertext = encrypt(&plaintext_1, &key, nonce, &mut count, &Endian::Big);
+        let mut ciphertext = encrypt(&plaintext_1, aes, nonce, &mut count, &Endian::Big);
 
         assert_eq!(ciphertext_1, ciphertext[..]);
         assert_eq!(count, init_count + get_block_count(plaintext_1.len()));
@@ -128,12 +177,12 @@ mod tests {
         let plaintext_2 = [0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51];
         let ciphertext_2 = [0x98, 0x06, 0xf6, 0x6b, 0x79, 0x70, 0xfd, 0xff, 0x86, 0x17, 0x18, 0x7b, 0xb9, 0xff, 0xfd, 0xff];
 
-        output = ctr_inner_cipher(&mut input, &key, count, &Endian::Big);
+        output = ctr_inner_cipher(&mut input, aes, count, &Endian::Big);
         assert_eq!(in_block_2, input);
         assert_eq!(out_block_2, output);
 
         init_count = count;
-        ciphertext = encrypt(&plaintext_2, &key, nonce, &mut count, &Endian::Big);
+        ciphertext = encrypt(&plaintext_2, aes, nonce, &mut count, &Endian::Big);
 
         assert_eq!(ciphertext_2, ciphertext[..]);
         assert_eq!(count, init_count + get_block_count(plaintext_2.len()));
@@ -143,12 +192,12 @@ mod tests {
         let plaintext_3 = [0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef];
         let ciphertext_3 = [0x5a, 0xe4, 0xdf, 0x3e, 0xdb, 0xd5, 0xd3, 0x5e, 0x5b, 0x4f, 0x09, 0x02, 0x0d, 0xb0, 0x3e, 0xab];
 
-        output = ctr_inner_cipher(&mut input, &key, count, &Endian::Big);
+        output = ctr_inner_cipher(&mut input, aes, count, &Endian::Big);
         assert_eq!(in_block_3, input);
         assert_eq!(out_block_3, output);
 
         init_count = count;
-        ciphertext = encrypt(&plaintext_3, &key, nonce, &mut count, &Endian::Big);
+        ciphertext = encrypt(&plaintext_3, aes, nonce, &mut count, &Endian::Big);
 
         assert_eq!(ciphertext_3, ciphertext[..]);
         assert_eq!(count, init_count + get_block_count(plaintext_3.len()));
@@ -158,12 +207,12 @@ mod tests {
         let plaintext_4 = [0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10];
         let ciphertext_4 = [0x1e, 0x03, 0x1d, 0xda, 0x2f, 0xbe, 0x03, 0xd1, 0x79, 0x21, 0x70, 0xa0, 0xf3, 0x00, 0x9c, 0xee];
 
-        output = ctr_inner_cipher(&mut input, &key, count, &Endian::Big);
+        output = ctr_inner_cipher(&mut input, aes, count, &Endian::Big);
         assert_eq!(in_block_4, input);
         assert_eq!(out_block_4, output);
 
         init_count = count;
-        ciphertext = encrypt(&plaintext_4, &key, nonce, &mut count, &Endian::Big);
+        ciphertext = encrypt(&plaintext_4, aes, nonce, &mut count, &Endian::Big);
 
         assert_eq!(ciphertext_4, ciphertext[..]);
         assert_eq!(count, init_count + get_block_count(plaintext_4.len()));
@@ -172,6 +221,12 @@ mod tests {
     #[test]
     fn nist_decryption_test_vectors() {
         let key = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+
+        nist_decryption_test_vectors_with(&aes::Aes128::new_portable(&key));
+        nist_decryption_test_vectors_with(&aes::Aes128::new(&key));
+    }
+
+    fn nist_decryption_test_vectors_with(aes: &aes::Aes128) {
         let nonce = u64::from_be_bytes([0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7]); 
         let mut init_count = u64::from_be_bytes([0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff]);
         let mut count = init_count;
@@ -184,11 +239,11 @@ mod tests {
         let mut input = [0_u8; aes::BLOCK_LEN];
         input[..NONCE_LEN].copy_from_slice(&nonce.to_be_bytes());
 
-        let mut output = ctr_inner_cipher(&mut input, &key, count, &Endian::Big);
+        let mut output = ctr_inner_cipher(&mut input, aes, count, &Endian::Big);
         assert_eq!(in_block_1, input);
         assert_eq!(out_block_1, output);
 
-        let mut plaintext = decrypt(&ciphertext_1, &key, nonce, &mut count, &Endian::Big);
+        let mut plaintext = decrypt(&ciphertext_1, aes, nonce, &mut count, &Endian::Big);
 
         assert_eq!(plaintext_1, plaintext[..]);
         assert_eq!(count, init_count + get_block_count(ciphertext_1.len()));
@@ -198,12 +253,12 @@ mod tests {
         let plaintext_2 = [0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51];
         let ciphertext_2 = [0x98, 0x06, 0xf6, 0x6b, 0x79, 0x70, 0xfd, 0xff, 0x86, 0x17, 0x18, 0x7b, 0xb9, 0xff, 0xfd, 0xff];
 
-        output = ctr_inner_cipher(&mut input, &key, count, &Endian::Big);
+        output = ctr_inner_cipher(&mut input, aes, count, &Endian::Big);
         assert_eq!(in_block_2, input);
         assert_eq!(out_block_2, output);
 
         init_count = count;
-        plaintext = decrypt(&ciphertext_2, &key, nonce, &mut count, &Endian::Big);
+        plaintext = decrypt(&ciphertext_2, aes, nonce, &mut count, &Endian::Big);
 
         assert_eq!(plaintext_2, plaintext[..]);
         assert_eq!(count, init_count + get_block_count(ciphertext_2.len()));
@@ -213,12 +268,12 @@ mod tests {
         let plaintext_3 = [0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef];
         let ciphertext_3 = [0x5a, 0xe4, 0xdf, 0x3e, 0xdb, 0xd5, 0xd3, 0x5e, 0x5b, 0x4f, 0x09, 0x02, 0x0d, 0xb0, 0x3e, 0xab];
 
-        output = ctr_inner_cipher(&mut input, &key, count, &Endian::Big);
+        output = ctr_inner_cipher(&mut input, aes, count, &Endian::Big);
         assert_eq!(in_block_3, input);
         assert_eq!(out_block_3, output);
 
         init_count = count;
-        plaintext = decrypt(&ciphertext_3, &key, nonce, &mut count, &Endian::Big);
+        plaintext = decrypt(&ciphertext_3, aes, nonce, &mut count, &Endian::Big);
 
         assert_eq!(plaintext_3, plaintext[..]);
         assert_eq!(count, init_count + get_block_count(ciphertext_3.len()));
@@ -228,14 +283,108 @@ mod tests {
         let plaintext_4 = [0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10];
         let ciphertext_4 = [0x1e, 0x03, 0x1d, 0xda, 0x2f, 0xbe, 0x03, 0xd1, 0x79, 0x21, 0x70, 0xa0, 0xf3, 0x00, 0x9c, 0xee];
 
-        output = ctr_inner_cipher(&mut input, &key, count, &Endian::Big);
+        output = ctr_inner_cipher(&mut input, aes, count, &Endian::Big);
         assert_eq!(in_block_4, input);
         assert_eq!(out_block_4, output);
 
         init_count = count;
-        plaintext = decrypt(&ciphertext_4, &key, nonce, &mut count, &Endian::Big);
+        plaintext = decrypt(&ciphertext_4, aes, nonce, &mut count, &Endian::Big);
 
         assert_eq!(plaintext_4, plaintext[..]);
         assert_eq!(count, init_count + get_block_count(ciphertext_4.len()));
     }
+
+    // NIST SP 800-38A F.5.5: CTR-AES256.Encrypt
+    #[test]
+    fn nist_aes_256_test_vectors() {
+        let key = [
+            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d, 0x77, 0x81,
+            0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3, 0x09, 0x14, 0xdf, 0xf4,
+        ];
+        let nonce = u64::from_be_bytes([0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7]);
+        let init_count = u64::from_be_bytes([0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff]);
+
+        let plaintext = [
+            0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a,
+            0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51,
+        ];
+        let ciphertext = [
+            0x60, 0x1e, 0xc3, 0x13, 0x77, 0x57, 0x89, 0xa5, 0xb7, 0xa7, 0xf5, 0x04, 0xbb, 0xf3, 0xd2, 0x28,
+            0xf4, 0x43, 0xe3, 0xca, 0x4d, 0x62, 0xb5, 0x9a, 0xca, 0x84, 0xe9, 0x90, 0xca, 0xca, 0xf5, 0xc5,
+        ];
+
+        for aes in [aes::Aes256::new_portable(&key), aes::Aes256::new(&key)].iter() {
+            let mut count = init_count;
+            let output = encrypt(&plaintext, aes, nonce, &mut count, &Endian::Big);
+            assert_eq!(ciphertext, output[..]);
+            assert_eq!(count, init_count + get_block_count(plaintext.len()));
+
+            count = init_count;
+            let output = decrypt(&ciphertext, aes, nonce, &mut count, &Endian::Big);
+            assert_eq!(plaintext, output[..]);
+        }
+    }
+
+    #[test]
+    fn check_multi_block_stream() {
+        let key = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+        let nonce = 0xf0f1f2f3f4f5f6f7;
+        // counter wraps the low byte mid-chunk
+        let init_count = 0xf8f9fafbfcfdfff5;
+
+        // several parallel chunks, with a partial final block
+        let plaintext = [0x5a_u8; aes::BLOCK_LEN * (2 * PAR_BLOCKS + 3) + 5];
+
+        for aes in [aes::Aes128::new_portable(&key), aes::Aes128::new(&key)].iter() {
+            for mode in [Endian::Big, Endian::Little].iter() {
+                let mut count = init_count;
+                let ciphertext = encrypt(&plaintext, aes, nonce, &mut count, mode);
+                assert_eq!(count, init_count + get_block_count(plaintext.len()));
+
+                let mut input = [0_u8; aes::BLOCK_LEN];
+                match mode {
+                    Endian::Big => input[..NONCE_LEN].copy_from_slice(&nonce.to_be_bytes()),
+                    Endian::Little => input[..NONCE_LEN].copy_from_slice(&nonce.to_le_bytes()),
+                }
+
+                for (i, (block, cipher_block)) in plaintext
+                    .chunks(aes::BLOCK_LEN)
+                    .zip(ciphertext.chunks(aes::BLOCK_LEN))
+                    .enumerate()
+                {
+                    let stream = ctr_inner_cipher(&mut input, aes, init_count + i as u64, mode);
+                    assert_eq!(xor(block, &stream[..block.len()]).unwrap(), cipher_block);
+                }
+
+                count = init_count;
+                assert_eq!(decrypt(&ciphertext, aes, nonce, &mut count, mode), plaintext[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn check_ctr_128() {
+        let aes = aes::Aes128::new(b"YELLOW SUBMARINE");
+
+        // low 64 bits of the counter wrap mid-chunk, and carry into the high half
+        let init_count = 0x0101_0101_0101_0101_ffff_ffff_ffff_fffd_u128;
+        let plaintext = [0x5a_u8; aes::BLOCK_LEN * (2 * PAR_BLOCKS + 3) + 5];
+
+        let ciphertext = ctr_128(&aes, init_count, &plaintext);
+        assert_eq!(ciphertext.len(), plaintext.len());
+
+        for (i, (block, cipher_block)) in plaintext
+            .chunks(aes::BLOCK_LEN)
+            .zip(ciphertext.chunks(aes::BLOCK_LEN))
+            .enumerate()
+        {
+            let stream = aes.encrypt_block(&(init_count + i as u128).to_be_bytes());
+            assert_eq!(xor(block, &stream[..block.len()]).unwrap(), cipher_block);
+        }
+
+        // the whole block wraps modulo 2^128
+        let stream = ctr_128(&aes, u128::MAX, &[0_u8; 2 * aes::BLOCK_LEN]);
+        assert_eq!(stream[..aes::BLOCK_LEN], aes.encrypt_block(&[0xff; aes::BLOCK_LEN]));
+        assert_eq!(stream[aes::BLOCK_LEN..], aes.encrypt_block(&[0x00; aes::BLOCK_LEN]));
+    }
 }
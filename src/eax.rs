@@ -0,0 +1,298 @@
+//! EAX authenticated encryption mode (Bellare, Rogaway, Wagner)
+//!
+//! Two-pass AEAD composing CTR mode with OMAC (CMAC over a tweak block prefix),
+//! using only the forward direction of the block cipher:
+//!
+//! N = OMAC_0( nonce ),  H = OMAC_1( header ),  C = CTR_N( M ),  Tag = N ^ H ^ OMAC_2( C )
+//!
+//! Nonces and headers can be any length. Ciphertexts are returned with the authentication tag appended.
+
+use alloc::vec::Vec;
+
+use crate::cmac::Cmac;
+use crate::ctr;
+use crate::{aes, ct_eq, xor_equals, BlockCipher, Error};
+
+/// Full authentication tag length
+pub const TAG_LEN: usize = 16;
+
+/// Minimum truncated authentication tag length
+pub const MIN_TAG_LEN: usize = 4;
+
+// OMAC tweaks, encoded as the last byte of the prefix block
+const NONCE_TWEAK: u8 = 0;
+const HEADER_TWEAK: u8 = 1;
+const CIPHER_TWEAK: u8 = 2;
+
+/// Encrypt and authenticate a message in EAX mode
+///
+/// Tag length must be MIN_TAG_LEN to TAG_LEN bytes
+pub fn encrypt<C: BlockCipher>(
+    msg: &[u8],
+    cipher: &C,
+    nonce: &[u8],
+    header: &[u8],
+    tag_len: usize,
+) -> Result<Vec<u8>, Error> {
+    check_tag_len(tag_len)?;
+
+    let n = omac(cipher, NONCE_TWEAK, nonce);
+    let h = omac(cipher, HEADER_TWEAK, header);
+
+    let mut res = ctr::ctr_128(cipher, u128::from_be_bytes(n), msg);
+    let tag = compute_tag(cipher, &n, &h, &res);
+
+    res.extend_from_slice(&tag[..tag_len]);
+
+    Ok(res)
+}
+
+/// Verify and decrypt a message in EAX mode
+///
+/// Ciphertext must have the `tag_len` byte authentication tag appended
+///
+/// No plaintext is returned unless the tag is valid
+pub fn decrypt<C: BlockCipher>(
+    ciphertext: &[u8],
+    cipher: &C,
+    nonce: &[u8],
+    header: &[u8],
+    tag_len: usize,
+) -> Result<Vec<u8>, Error> {
+    check_tag_len(tag_len)?;
+
+    if ciphertext.len() < tag_len {
+        return Err(Error::InvalidLength);
+    }
+
+    let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - tag_len);
+
+    let n = omac(cipher, NONCE_TWEAK, nonce);
+    let h = omac(cipher, HEADER_TWEAK, header);
+
+    let exp_tag = compute_tag(cipher, &n, &h, ciphertext);
+
+    if !ct_eq(&exp_tag[..tag_len], tag) {
+        return Err(Error::InvalidTag);
+    }
+
+    Ok(ctr::ctr_128(cipher, u128::from_be_bytes(n), ciphertext))
+}
+
+fn check_tag_len(tag_len: usize) -> Result<(), Error> {
+    if (MIN_TAG_LEN..=TAG_LEN).contains(&tag_len) {
+        Ok(())
+    } else {
+        Err(Error::InvalidLength)
+    }
+}
+
+// OMAC_t( M ) = CMAC( [t]_128 || M , key )
+fn omac<C: BlockCipher>(cipher: &C, tweak: u8, msg: &[u8]) -> [u8; aes::BLOCK_LEN] {
+    let mut prefix = [0_u8; aes::BLOCK_LEN];
+    prefix[aes::BLOCK_LEN - 1] = tweak;
+
+    let mut cmac = Cmac::new(cipher);
+    cmac.update(&prefix);
+    cmac.update(msg);
+    cmac.finalize()
+}
+
+// Tag = N ^ H ^ OMAC_2( C )
+fn compute_tag<C: BlockCipher>(
+    cipher: &C,
+    n: &[u8; aes::BLOCK_LEN],
+    h: &[u8; aes::BLOCK_LEN],
+    ciphertext: &[u8],
+) -> [u8; aes::BLOCK_LEN] {
+    let mut tag = omac(cipher, CIPHER_TWEAK, ciphertext);
+
+    // blocks guaranteed to be the same length, safe to just unwrap here
+    xor_equals(&mut tag, n).unwrap();
+    xor_equals(&mut tag, h).unwrap();
+
+    tag
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryInto;
+
+    // (key, nonce, header, msg, cipher || tag)
+    type Vector<'a> = (&'a [u8], &'a [u8], &'a [u8], &'a [u8], &'a [u8]);
+
+    // EAX paper (Bellare, Rogaway, Wagner) Appendix: test vectors with 16 byte tags
+    #[test]
+    fn paper_test_vectors() {
+        let vectors: [Vector; 10] = [
+            (
+                &[0x23, 0x39, 0x52, 0xde, 0xe4, 0xd5, 0xed, 0x5f, 0x9b, 0x9c, 0x6d, 0x6f, 0xf8, 0x0f, 0xf4, 0x78],
+                &[0x62, 0xec, 0x67, 0xf9, 0xc3, 0xa4, 0xa4, 0x07, 0xfc, 0xb2, 0xa8, 0xc4, 0x90, 0x31, 0xa8, 0xb3],
+                &[0x6b, 0xfb, 0x91, 0x4f, 0xd0, 0x7e, 0xae, 0x6b],
+                &[],
+                &[0xe0, 0x37, 0x83, 0x0e, 0x83, 0x89, 0xf2, 0x7b, 0x02, 0x5a, 0x2d, 0x65, 0x27, 0xe7, 0x9d, 0x01],
+            ),
+            (
+                &[0x91, 0x94, 0x5d, 0x3f, 0x4d, 0xcb, 0xee, 0x0b, 0xf4, 0x5e, 0xf5, 0x22, 0x55, 0xf0, 0x95, 0xa4],
+                &[0xbe, 0xca, 0xf0, 0x43, 0xb0, 0xa2, 0x3d, 0x84, 0x31, 0x94, 0xba, 0x97, 0x2c, 0x66, 0xde, 0xbd],
+                &[0xfa, 0x3b, 0xfd, 0x48, 0x06, 0xeb, 0x53, 0xfa],
+                &[0xf7, 0xfb],
+                &[
+                    0x19, 0xdd, 0x5c, 0x4c, 0x93, 0x31, 0x04, 0x9d, 0x0b, 0xda, 0xb0, 0x27, 0x74, 0x08, 0xf6, 0x79,
+                    0x67, 0xe5,
+                ],
+            ),
+            (
+                &[0x01, 0xf7, 0x4a, 0xd6, 0x40, 0x77, 0xf2, 0xe7, 0x04, 0xc0, 0xf6, 0x0a, 0xda, 0x3d, 0xd5, 0x23],
+                &[0x70, 0xc3, 0xdb, 0x4f, 0x0d, 0x26, 0x36, 0x84, 0x00, 0xa1, 0x0e, 0xd0, 0x5d, 0x2b, 0xff, 0x5e],
+                &[0x23, 0x4a, 0x34, 0x63, 0xc1, 0x26, 0x4a, 0xc6],
+                &[0x1a, 0x47, 0xcb, 0x49, 0x33],
+                &[
+                    0xd8, 0x51, 0xd5, 0xba, 0xe0, 0x3a, 0x59, 0xf2, 0x38, 0xa2, 0x3e, 0x39, 0x19, 0x9d, 0xc9, 0x26,
+                    0x66, 0x26, 0xc4, 0x0f, 0x80,
+                ],
+            ),
+            (
+                &[0xd0, 0x7c, 0xf6, 0xcb, 0xb7, 0xf3, 0x13, 0xbd, 0xde, 0x66, 0xb7, 0x27, 0xaf, 0xd3, 0xc5, 0xe8],
+                &[0x84, 0x08, 0xdf, 0xff, 0x3c, 0x1a, 0x2b, 0x12, 0x92, 0xdc, 0x19, 0x9e, 0x46, 0xb7, 0xd6, 0x17],
+                &[0x33, 0xcc, 0xe2, 0xea, 0xbf, 0xf5, 0xa7, 0x9d],
+                &[0x48, 0x1c, 0x9e, 0x39, 0xb1],
+                &[
+                    0x63, 0x2a, 0x9d, 0x13, 0x1a, 0xd4, 0xc1, 0x68, 0xa4, 0x22, 0x5d, 0x8e, 0x1f, 0xf7, 0x55, 0x93,
+                    0x99, 0x74, 0xa7, 0xbe, 0xde,
+                ],
+            ),
+            (
+                &[0x35, 0xb6, 0xd0, 0x58, 0x00, 0x05, 0xbb, 0xc1, 0x2b, 0x05, 0x87, 0x12, 0x45, 0x57, 0xd2, 0xc2],
+                &[0xfd, 0xb6, 0xb0, 0x66, 0x76, 0xee, 0xdc, 0x5c, 0x61, 0xd7, 0x42, 0x76, 0xe1, 0xf8, 0xe8, 0x16],
+                &[0xae, 0xb9, 0x6e, 0xae, 0xbe, 0x29, 0x70, 0xe9],
+                &[0x40, 0xd0, 0xc0, 0x7d, 0xa5, 0xe4],
+                &[
+                    0x07, 0x1d, 0xfe, 0x16, 0xc6, 0x75, 0xcb, 0x06, 0x77, 0xe5, 0x36, 0xf7, 0x3a, 0xfe, 0x6a, 0x14,
+                    0xb7, 0x4e, 0xe4, 0x98, 0x44, 0xdd,
+                ],
+            ),
+            (
+                &[0xbd, 0x8e, 0x6e, 0x11, 0x47, 0x5e, 0x60, 0xb2, 0x68, 0x78, 0x4c, 0x38, 0xc6, 0x2f, 0xeb, 0x22],
+                &[0x6e, 0xac, 0x5c, 0x93, 0x07, 0x2d, 0x8e, 0x85, 0x13, 0xf7, 0x50, 0x93, 0x5e, 0x46, 0xda, 0x1b],
+                &[0xd4, 0x48, 0x2d, 0x1c, 0xa7, 0x8d, 0xce, 0x0f],
+                &[0x4d, 0xe3, 0xb3, 0x5c, 0x3f, 0xc0, 0x39, 0x24, 0x5b, 0xd1, 0xfb, 0x7d],
+                &[
+                    0x83, 0x5b, 0xb4, 0xf1, 0x5d, 0x74, 0x3e, 0x35, 0x0e, 0x72, 0x84, 0x14, 0xab, 0xb8, 0x64, 0x4f,
+                    0xd6, 0xcc, 0xb8, 0x69, 0x47, 0xc5, 0xe1, 0x05, 0x90, 0x21, 0x0a, 0x4f,
+                ],
+            ),
+            (
+                &[0x7c, 0x77, 0xd6, 0xe8, 0x13, 0xbe, 0xd5, 0xac, 0x98, 0xba, 0xa4, 0x17, 0x47, 0x7a, 0x2e, 0x7d],
+                &[0x1a, 0x8c, 0x98, 0xdc, 0xd7, 0x3d, 0x38, 0x39, 0x3b, 0x2b, 0xf1, 0x56, 0x9d, 0xee, 0xfc, 0x19],
+                &[0x65, 0xd2, 0x01, 0x79, 0x90, 0xd6, 0x25, 0x28],
+                &[
+                    0x8b, 0x0a, 0x79, 0x30, 0x6c, 0x9c, 0xe7, 0xed, 0x99, 0xda, 0xe4, 0xf8, 0x7f, 0x8d, 0xd6, 0x16,
+                    0x36,
+                ],
+                &[
+                    0x02, 0x08, 0x3e, 0x39, 0x79, 0xda, 0x01, 0x48, 0x12, 0xf5, 0x9f, 0x11, 0xd5, 0x26, 0x30, 0xda,
+                    0x30, 0x13, 0x73, 0x27, 0xd1, 0x06, 0x49, 0xb0, 0xaa, 0x6e, 0x1c, 0x18, 0x1d, 0xb6, 0x17, 0xd7,
+                    0xf2,
+                ],
+            ),
+            (
+                &[0x5f, 0xff, 0x20, 0xca, 0xfa, 0xb1, 0x19, 0xca, 0x2f, 0xc7, 0x35, 0x49, 0xe2, 0x0f, 0x5b, 0x0d],
+                &[0xdd, 0xe5, 0x9b, 0x97, 0xd7, 0x22, 0x15, 0x6d, 0x4d, 0x9a, 0xff, 0x2b, 0xc7, 0x55, 0x98, 0x26],
+                &[0x54, 0xb9, 0xf0, 0x4e, 0x6a, 0x09, 0x18, 0x9a],
+                &[
+                    0x1b, 0xda, 0x12, 0x2b, 0xce, 0x8a, 0x8d, 0xba, 0xf1, 0x87, 0x7d, 0x96, 0x2b, 0x85, 0x92, 0xdd,
+                    0x2d, 0x56,
+                ],
+                &[
+                    0x2e, 0xc4, 0x7b, 0x2c, 0x49, 0x54, 0xa4, 0x89, 0xaf, 0xc7, 0xba, 0x48, 0x97, 0xed, 0xcd, 0xae,
+                    0x8c, 0xc3, 0x3b, 0x60, 0x45, 0x05, 0x99, 0xbd, 0x02, 0xc9, 0x63, 0x82, 0x90, 0x2a, 0xef, 0x7f,
+                    0x83, 0x2a,
+                ],
+            ),
+            (
+                &[0xa4, 0xa4, 0x78, 0x2b, 0xcf, 0xfd, 0x3e, 0xc5, 0xe7, 0xef, 0x6d, 0x8c, 0x34, 0xa5, 0x61, 0x23],
+                &[0xb7, 0x81, 0xfc, 0xf2, 0xf7, 0x5f, 0xa5, 0xa8, 0xde, 0x97, 0xa9, 0xca, 0x48, 0xe5, 0x22, 0xec],
+                &[0x89, 0x9a, 0x17, 0x58, 0x97, 0x56, 0x1d, 0x7e],
+                &[
+                    0x6c, 0xf3, 0x67, 0x20, 0x87, 0x2b, 0x85, 0x13, 0xf6, 0xea, 0xb1, 0xa8, 0xa4, 0x44, 0x38, 0xd5,
+                    0xef, 0x11,
+                ],
+                &[
+                    0x0d, 0xe1, 0x8f, 0xd0, 0xfd, 0xd9, 0x1e, 0x7a, 0xf1, 0x9f, 0x1d, 0x8e, 0xe8, 0x73, 0x39, 0x38,
+                    0xb1, 0xe8, 0xe7, 0xf6, 0xd2, 0x23, 0x16, 0x18, 0x10, 0x2f, 0xdb, 0x7f, 0xe5, 0x5f, 0xf1, 0x99,
+                    0x17, 0x00,
+                ],
+            ),
+            (
+                &[0x83, 0x95, 0xfc, 0xf1, 0xe9, 0x5b, 0xeb, 0xd6, 0x97, 0xbd, 0x01, 0x0b, 0xc7, 0x66, 0xaa, 0xc3],
+                &[0x22, 0xe7, 0xad, 0xd9, 0x3c, 0xfc, 0x63, 0x93, 0xc5, 0x7e, 0xc0, 0xb3, 0xc1, 0x7d, 0x6b, 0x44],
+                &[0x12, 0x67, 0x35, 0xfc, 0xc3, 0x20, 0xd2, 0x5a],
+                &[
+                    0xca, 0x40, 0xd7, 0x44, 0x6e, 0x54, 0x5f, 0xfa, 0xed, 0x3b, 0xd1, 0x2a, 0x74, 0x0a, 0x65, 0x9f,
+                    0xfb, 0xbb, 0x3c, 0xea, 0xb7,
+                ],
+                &[
+                    0xcb, 0x89, 0x20, 0xf8, 0x7a, 0x6c, 0x75, 0xcf, 0xf3, 0x96, 0x27, 0xb5, 0x6e, 0x3e, 0xd1, 0x97,
+                    0xc5, 0x52, 0xd2, 0x95, 0xa7, 0xcf, 0xc4, 0x6a, 0xfc, 0x25, 0x3b, 0x46, 0x52, 0xb1, 0xaf, 0x37,
+                    0x95, 0xb1, 0x24, 0xab, 0x6e,
+                ],
+            ),
+        ];
+
+        for (key, nonce, header, msg, exp_cipher) in vectors.iter() {
+            let key: [u8; aes::KEY_LEN_128] = (*key).try_into().unwrap();
+
+            for aes in [aes::Aes128::new_portable(&key), aes::Aes128::new(&key)].iter() {
+                let cipher = encrypt(msg, aes, nonce, header, TAG_LEN).unwrap();
+                assert_eq!(cipher[..], exp_cipher[..]);
+
+                assert_eq!(decrypt(&cipher, aes, nonce, header, TAG_LEN).unwrap(), *msg);
+            }
+        }
+    }
+
+    #[test]
+    fn check_truncated_tag() {
+        let aes = aes::Aes128::new(b"YELLOW SUBMARINE");
+        let nonce = [0x42_u8; 7];
+        let header = b"header";
+        let msg = [0x5a_u8; 37];
+
+        let full = encrypt(&msg, &aes, &nonce, header, TAG_LEN).unwrap();
+
+        for tag_len in MIN_TAG_LEN..=TAG_LEN {
+            let cipher = encrypt(&msg, &aes, &nonce, header, tag_len).unwrap();
+            assert_eq!(cipher[..], full[..msg.len() + tag_len]);
+
+            assert_eq!(decrypt(&cipher, &aes, &nonce, header, tag_len).unwrap(), msg[..]);
+        }
+
+        assert!(encrypt(&msg, &aes, &nonce, header, MIN_TAG_LEN - 1).is_err());
+        assert!(encrypt(&msg, &aes, &nonce, header, TAG_LEN + 1).is_err());
+        assert!(decrypt(&full[..TAG_LEN - 1], &aes, &nonce, header, TAG_LEN).is_err());
+    }
+
+    #[test]
+    fn check_invalid_tag() {
+        let aes = aes::Aes128::new(b"YELLOW SUBMARINE");
+        let nonce = [0x42_u8; 16];
+        let header = b"header";
+        let msg = [0x5a_u8; 21];
+
+        let mut cipher = encrypt(&msg, &aes, &nonce, header, TAG_LEN).unwrap();
+
+        // tampered nonce, header, ciphertext or tag must fail verification
+        assert!(decrypt(&cipher, &aes, &nonce[1..], header, TAG_LEN).is_err());
+        assert!(decrypt(&cipher, &aes, &nonce, &header[1..], TAG_LEN).is_err());
+
+        cipher[0] ^= 0x01;
+        assert!(decrypt(&cipher, &aes, &nonce, header, TAG_LEN).is_err());
+
+        cipher[0] ^= 0x01;
+        let last = cipher.len() - 1;
+        cipher[last] ^= 0x80;
+        assert!(decrypt(&cipher, &aes, &nonce, header, TAG_LEN).is_err());
+    }
+}
@@ -1,37 +1,32 @@
 use alloc::vec::Vec;
-use core::convert::TryInto;
 
-use crate::aes;
-use crate::Error;
+use crate::{aes, to_blocks};
+use crate::{BlockCipher, Error};
 
-/// Encrypt a message using AES-128-ECB
-pub fn encrypt(plaintext: &[u8], key: &[u8; aes::KEY_LEN_128]) -> Result<Vec<u8>, Error> {
+/// Encrypt a message using ECB mode
+pub fn encrypt<C: BlockCipher>(plaintext: &[u8], cipher: &C) -> Result<Vec<u8>, Error> {
     let len = plaintext.len();
     if len % aes::BLOCK_LEN != 0 {
         return Err(Error::InvalidLength);
     }
 
-    let mut res = Vec::with_capacity(len);
-    for block in plaintext.chunks_exact(aes::BLOCK_LEN) {
-        res.extend_from_slice(&aes::aes_128(block.try_into().unwrap(), &key)[..]);
-    }
+    let mut blocks = to_blocks(plaintext);
+    cipher.encrypt_blocks(&mut blocks);
 
-    Ok(res)
+    Ok(blocks.concat())
 }
 
-/// Decrypt a ciphertext using AES-128-ECB
-pub fn decrypt(ciphertext: &[u8], key: &[u8; aes::KEY_LEN_128]) -> Result<Vec<u8>, Error> {
+/// Decrypt a ciphertext using ECB mode
+pub fn decrypt<C: BlockCipher>(ciphertext: &[u8], cipher: &C) -> Result<Vec<u8>, Error> {
     let len = ciphertext.len();
     if len % aes::BLOCK_LEN != 0 {
         return Err(Error::InvalidLength);
     }
 
-    let mut res = Vec::with_capacity(len);
-    for block in ciphertext.chunks_exact(aes::BLOCK_LEN) {
-        res.extend_from_slice(&aes::aes_inv_128(block.try_into().unwrap(), &key)[..]);
-    }
+    let mut blocks = to_blocks(ciphertext);
+    cipher.decrypt_blocks(&mut blocks);
 
-    Ok(res)
+    Ok(blocks.concat())
 }
 
 #[cfg(test)]
@@ -43,10 +38,12 @@ mod tests {
         let txt = b"This block good! This block good";
         let key = b"YELLOW SUBMARINE";
 
-        assert!(encrypt(&txt[..], &key).is_ok());
+        for aes in [aes::Aes128::new_portable(key), aes::Aes128::new(key)].iter() {
+            assert!(encrypt(&txt[..], aes).is_ok());
 
-        // use an invalid length plaintext, ensure error is returned
-        assert!(encrypt(&txt[1..], &key).is_err());
+            // use an invalid length plaintext, ensure error is returned
+            assert!(encrypt(&txt[1..], aes).is_err());
+        }
     }
 
     #[test]
@@ -54,11 +51,68 @@ mod tests {
         let txt = b"This block good! This block good";
         let key = b"YELLOW SUBMARINE";
 
-        let cipher = encrypt(&txt[..], &key);
-        assert!(cipher.is_ok());
+        for aes in [aes::Aes128::new_portable(key), aes::Aes128::new(key)].iter() {
+            let cipher = encrypt(&txt[..], aes);
+            assert!(cipher.is_ok());
+
+            let pt = decrypt(&cipher.unwrap(), aes);
+            assert!(pt.is_ok());
+            assert_eq!(pt.unwrap()[..], txt[..]);
+        }
+    }
+
+    // NIST SP 800-38A F.1.3: ECB-AES192.Encrypt
+    #[test]
+    fn nist_test_vector_aes_192() {
+        let key = [
+            0x8e, 0x73, 0xb0, 0xf7, 0xda, 0x0e, 0x64, 0x52, 0xc8, 0x10, 0xf3, 0x2b, 0x80, 0x90,
+            0x79, 0xe5, 0x62, 0xf8, 0xea, 0xd2, 0x52, 0x2c, 0x6b, 0x7b,
+        ];
+        let msg = [
+            0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93,
+            0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac,
+            0x45, 0xaf, 0x8e, 0x51,
+        ];
+        let exp_cipher = [
+            0xbd, 0x33, 0x4f, 0x1d, 0x6e, 0x45, 0xf2, 0x5f, 0xf7, 0x12, 0xa2, 0x14, 0x57, 0x1f,
+            0xa5, 0xcc, 0x97, 0x41, 0x04, 0x84, 0x6d, 0x0a, 0xd3, 0xad, 0x77, 0x34, 0xec, 0xb3,
+            0xec, 0xee, 0x4e, 0xef,
+        ];
+
+        for aes in [aes::Aes192::new_portable(&key), aes::Aes192::new(&key)].iter() {
+            let cipher = encrypt(msg.as_ref(), aes).unwrap();
+            assert_eq!(cipher.as_slice(), exp_cipher.as_ref());
+
+            let plaintext = decrypt(&cipher, aes).unwrap();
+            assert_eq!(plaintext.as_slice(), msg.as_ref());
+        }
+    }
+
+    // NIST SP 800-38A F.1.5: ECB-AES256.Encrypt
+    #[test]
+    fn nist_test_vector_aes_256() {
+        let key = [
+            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d,
+            0x77, 0x81, 0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3,
+            0x09, 0x14, 0xdf, 0xf4,
+        ];
+        let msg = [
+            0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93,
+            0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac,
+            0x45, 0xaf, 0x8e, 0x51,
+        ];
+        let exp_cipher = [
+            0xf3, 0xee, 0xd1, 0xbd, 0xb5, 0xd2, 0xa0, 0x3c, 0x06, 0x4b, 0x5a, 0x7e, 0x3d, 0xb1,
+            0x81, 0xf8, 0x59, 0x1c, 0xcb, 0x10, 0xd4, 0x10, 0xed, 0x26, 0xdc, 0x5b, 0xa7, 0x4a,
+            0x31, 0x36, 0x28, 0x70,
+        ];
+
+        for aes in [aes::Aes256::new_portable(&key), aes::Aes256::new(&key)].iter() {
+            let cipher = encrypt(msg.as_ref(), aes).unwrap();
+            assert_eq!(cipher.as_slice(), exp_cipher.as_ref());
 
-        let pt = decrypt(&cipher.unwrap(), &key);
-        assert!(pt.is_ok());
-        assert_eq!(pt.unwrap()[..], txt[..]);
+            let plaintext = decrypt(&cipher, aes).unwrap();
+            assert_eq!(plaintext.as_slice(), msg.as_ref());
+        }
     }
 }
@@ -0,0 +1,594 @@
+//! Galois/Counter Mode (NIST SP 800-38D)
+//!
+//! Authenticated encryption combining CTR mode (GCTR) with the GHASH universal hash over GF(2^128).
+//!
+//! Ciphertexts are returned with the authentication tag appended.
+
+use alloc::vec::Vec;
+
+use crate::{aes, ct_eq, xor, BlockCipher, Error, PAR_BLOCKS};
+
+/// Recommended initialization vector length (96 bits)
+pub const IV_LEN: usize = 12;
+
+/// Full authentication tag length
+pub const TAG_LEN: usize = 16;
+
+// Maximum plaintext length: 2^39 - 256 bits
+const MAX_MSG_LEN: u64 = (1 << 36) - 32;
+
+// R = 11100001 || 0^120
+const R: u128 = 0xe1 << 120;
+
+/// Encrypt and authenticate a message in GCM mode
+///
+/// The IV must not be empty, and should be 12 bytes unless interoperating with other IV lengths
+///
+/// Tag length must be 4, 8 or 12 to 16 bytes, short tags (4 and 8) are only safe for short messages
+pub fn encrypt<C: BlockCipher>(
+    msg: &[u8],
+    cipher: &C,
+    iv: &[u8],
+    aad: &[u8],
+    tag_len: usize,
+) -> Result<Vec<u8>, Error> {
+    check_params(msg.len(), iv, tag_len)?;
+
+    let h = hash_key(cipher);
+    let j0 = pre_counter_block(h, iv);
+
+    let mut res = gctr(cipher, &inc32(&j0), msg);
+    let tag = compute_tag(cipher, h, &j0, aad, &res);
+
+    res.extend_from_slice(&tag[..tag_len]);
+
+    Ok(res)
+}
+
+/// Verify and decrypt a message in GCM mode
+///
+/// Ciphertext must have the `tag_len` byte authentication tag appended
+///
+/// No plaintext is returned unless the tag is valid
+pub fn decrypt<C: BlockCipher>(
+    ciphertext: &[u8],
+    cipher: &C,
+    iv: &[u8],
+    aad: &[u8],
+    tag_len: usize,
+) -> Result<Vec<u8>, Error> {
+    if ciphertext.len() < tag_len {
+        return Err(Error::InvalidLength);
+    }
+
+    let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - tag_len);
+
+    check_params(ciphertext.len(), iv, tag_len)?;
+
+    let h = hash_key(cipher);
+    let j0 = pre_counter_block(h, iv);
+
+    let exp_tag = compute_tag(cipher, h, &j0, aad, ciphertext);
+
+    if !ct_eq(&exp_tag[..tag_len], tag) {
+        return Err(Error::InvalidTag);
+    }
+
+    Ok(gctr(cipher, &inc32(&j0), ciphertext))
+}
+
+/// GHASH over a sequence of whole blocks
+///
+/// Input length must be a multiple of the AES block length (16)
+///
+/// @param h: Hash subkey, the encryption of the zero block
+/// @param blocks: Blocks to be hashed
+pub fn ghash(h: &[u8; aes::BLOCK_LEN], blocks: &[u8]) -> Result<[u8; aes::BLOCK_LEN], Error> {
+    if blocks.len() % aes::BLOCK_LEN != 0 {
+        return Err(Error::InvalidLength);
+    }
+
+    let mut y = 0;
+    ghash_update(u128::from_be_bytes(*h), &mut y, blocks);
+
+    Ok(y.to_be_bytes())
+}
+
+fn check_params(msg_len: usize, iv: &[u8], tag_len: usize) -> Result<(), Error> {
+    if iv.is_empty() || msg_len as u64 > MAX_MSG_LEN {
+        return Err(Error::InvalidLength);
+    }
+
+    match tag_len {
+        4 | 8 | 12..=TAG_LEN => Ok(()),
+        _ => Err(Error::InvalidLength),
+    }
+}
+
+// H = CIPHER( 0^128 , key )
+fn hash_key<C: BlockCipher>(cipher: &C) -> u128 {
+    u128::from_be_bytes(cipher.encrypt_block(&[0_u8; aes::BLOCK_LEN]))
+}
+
+// J0 = IV || 0^31 || 1                                      if len(IV) = 96
+// J0 = GHASH( IV || 0^(s+64) || [len(IV)]_64 )              otherwise
+fn pre_counter_block(h: u128, iv: &[u8]) -> [u8; aes::BLOCK_LEN] {
+    let mut j0 = [0_u8; aes::BLOCK_LEN];
+
+    if iv.len() == IV_LEN {
+        j0[..IV_LEN].copy_from_slice(iv);
+        j0[aes::BLOCK_LEN - 1] = 1;
+    } else {
+        let mut y = 0;
+        ghash_update(h, &mut y, iv);
+        ghash_update(h, &mut y, &len_block(0, iv.len()));
+        j0 = y.to_be_bytes();
+    }
+
+    j0
+}
+
+// S = GHASH( A || 0^v || C || 0^u || [len(A)]_64 || [len(C)]_64 )
+// T = GCTR( J0 , S )
+fn compute_tag<C: BlockCipher>(
+    cipher: &C,
+    h: u128,
+    j0: &[u8; aes::BLOCK_LEN],
+    aad: &[u8],
+    ciphertext: &[u8],
+) -> [u8; aes::BLOCK_LEN] {
+    let mut s = 0;
+    ghash_update(h, &mut s, aad);
+    ghash_update(h, &mut s, ciphertext);
+    ghash_update(h, &mut s, &len_block(aad.len(), ciphertext.len()));
+
+    let mut tag = cipher.encrypt_block(j0);
+    for (t, b) in tag.iter_mut().zip(s.to_be_bytes().iter()) {
+        *t ^= b;
+    }
+
+    tag
+}
+
+// [len(A)]_64 || [len(C)]_64, lengths in bits
+fn len_block(aad_len: usize, text_len: usize) -> [u8; aes::BLOCK_LEN] {
+    let lens = ((aad_len as u128 * 8) << 64) | (text_len as u128 * 8);
+    lens.to_be_bytes()
+}
+
+// Y_i = ( Y_i-1 ^ X_i ) * H, with zero padding of a partial final block
+fn ghash_update(h: u128, y: &mut u128, data: &[u8]) {
+    for chunk in data.chunks(aes::BLOCK_LEN) {
+        let mut block = [0_u8; aes::BLOCK_LEN];
+        block[..chunk.len()].copy_from_slice(chunk);
+
+        *y = gf_mul(*y ^ u128::from_be_bytes(block), h);
+    }
+}
+
+// Multiplication in GF(2^128) with the GCM bit ordering (SP 800-38D Algorithm 1)
+//
+// Uses masks instead of branches, so runtime does not depend on the operands
+fn gf_mul(x: u128, y: u128) -> u128 {
+    let mut z = 0_u128;
+    let mut v = y;
+
+    for i in 0..128 {
+        // Z = Z ^ V    if x_i = 1
+        let x_i = (x >> (127 - i)) & 1;
+        z ^= v & 0_u128.wrapping_sub(x_i);
+
+        // V = V >> 1 ^ R    if LSB_1(V) = 1
+        let lsb = v & 1;
+        v = (v >> 1) ^ (R & 0_u128.wrapping_sub(lsb));
+    }
+
+    z
+}
+
+// inc_32: increment the rightmost 32 bits, modulo 2^32
+fn inc32(block: &[u8; aes::BLOCK_LEN]) -> [u8; aes::BLOCK_LEN] {
+    let mut out = *block;
+    let count = u32::from_be_bytes([block[12], block[13], block[14], block[15]]).wrapping_add(1);
+    out[12..].copy_from_slice(&count.to_be_bytes());
+    out
+}
+
+// GCTR( ICB , X ): CTR mode with a 32-bit big endian counter
+fn gctr<C: BlockCipher>(cipher: &C, icb: &[u8; aes::BLOCK_LEN], text: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(text.len());
+
+    let mut cb = *icb;
+    let mut stream = [[0_u8; aes::BLOCK_LEN]; PAR_BLOCKS];
+
+    for chunk in text.chunks(aes::BLOCK_LEN * PAR_BLOCKS) {
+        let n_block = (chunk.len() / aes::BLOCK_LEN) + ((chunk.len() % aes::BLOCK_LEN) != 0) as usize;
+
+        // counter blocks are independent, encrypt them together
+        for stream_block in stream[..n_block].iter_mut() {
+            *stream_block = cb;
+            cb = inc32(&cb);
+        }
+
+        cipher.encrypt_blocks(&mut stream[..n_block]);
+
+        for (block, stream_block) in chunk.chunks(aes::BLOCK_LEN).zip(stream.iter()) {
+            // block and stream guaranteed to be the same length, safe to just unwrap here
+            res.extend_from_slice(&xor(block, &stream_block[..block.len()]).unwrap());
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // McGrew-Viega GCM test cases 3-6 and 9-12 share the key and plaintext
+    const KEY_128: [u8; aes::KEY_LEN_128] = [
+        0xfe, 0xff, 0xe9, 0x92, 0x86, 0x65, 0x73, 0x1c, 0x6d, 0x6a, 0x8f, 0x94, 0x67, 0x30, 0x83, 0x08,
+    ];
+
+    const KEY_192: [u8; aes::KEY_LEN_192] = [
+        0xfe, 0xff, 0xe9, 0x92, 0x86, 0x65, 0x73, 0x1c, 0x6d, 0x6a, 0x8f, 0x94, 0x67, 0x30, 0x83, 0x08,
+        0xfe, 0xff, 0xe9, 0x92, 0x86, 0x65, 0x73, 0x1c,
+    ];
+
+    const PLAINTEXT: [u8; 64] = [
+        0xd9, 0x31, 0x32, 0x25, 0xf8, 0x84, 0x06, 0xe5, 0xa5, 0x59, 0x09, 0xc5, 0xaf, 0xf5, 0x26, 0x9a,
+        0x86, 0xa7, 0xa9, 0x53, 0x15, 0x34, 0xf7, 0xda, 0x2e, 0x4c, 0x30, 0x3d, 0x8a, 0x31, 0x8a, 0x72,
+        0x1c, 0x3c, 0x0c, 0x95, 0x95, 0x68, 0x09, 0x53, 0x2f, 0xcf, 0x0e, 0x24, 0x49, 0xa6, 0xb5, 0x25,
+        0xb1, 0x6a, 0xed, 0xf5, 0xaa, 0x0d, 0xe6, 0x57, 0xba, 0x63, 0x7b, 0x39, 0x1a, 0xaf, 0xd2, 0x55,
+    ];
+
+    const AAD: [u8; 20] = [
+        0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad, 0xbe, 0xef, 0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad, 0xbe, 0xef,
+        0xab, 0xad, 0xda, 0xd2,
+    ];
+
+    #[test]
+    fn check_gf_mul() {
+        let h = 0x66e94bd4ef8a2c3b884cfa59ca342b2e;
+        let one = 1 << 127;
+
+        // 1 is the multiplicative identity
+        assert_eq!(gf_mul(h, one), h);
+        assert_eq!(gf_mul(one, h), h);
+        assert_eq!(gf_mul(h, 0), 0);
+
+        // x * x^127 = x^128 = 1 + x + x^2 + x^7
+        assert_eq!(gf_mul(1 << 126, 1), R);
+    }
+
+    #[test]
+    fn check_ghash() {
+        // McGrew-Viega test case 2: GHASH( H , {} , C )
+        let h = [0x66, 0xe9, 0x4b, 0xd4, 0xef, 0x8a, 0x2c, 0x3b, 0x88, 0x4c, 0xfa, 0x59, 0xca, 0x34, 0x2b, 0x2e];
+        let c = [0x03, 0x88, 0xda, 0xce, 0x60, 0xb6, 0xa3, 0x92, 0xf3, 0x28, 0xc2, 0xb9, 0x71, 0xb2, 0xfe, 0x78];
+        let exp_hash = [0xf3, 0x8c, 0xbb, 0x1a, 0xd6, 0x92, 0x23, 0xdc, 0xc3, 0x45, 0x7a, 0xe5, 0xb6, 0xb0, 0xf8, 0x85];
+
+        let mut blocks = c.to_vec();
+        blocks.extend_from_slice(&len_block(0, c.len()));
+
+        assert_eq!(ghash(&h, &blocks).unwrap(), exp_hash);
+        assert!(ghash(&h, &blocks[1..]).is_err());
+    }
+
+    #[test]
+    fn check_inc32() {
+        let mut block = [0xff_u8; aes::BLOCK_LEN];
+        block[11] = 0x00;
+
+        // only the rightmost 32 bits wrap
+        let exp_block = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(inc32(&block), exp_block);
+    }
+
+    // McGrew-Viega test case 1
+    #[test]
+    fn mcgrew_viega_case_1() {
+        let aes = aes::Aes128::new(&[0_u8; aes::KEY_LEN_128]);
+        let exp_tag = [0x58, 0xe2, 0xfc, 0xce, 0xfa, 0x7e, 0x30, 0x61, 0x36, 0x7f, 0x1d, 0x57, 0xa4, 0xe7, 0x45, 0x5a];
+
+        let res = encrypt(&[], &aes, &[0_u8; IV_LEN], &[], TAG_LEN).unwrap();
+        assert_eq!(res, exp_tag);
+
+        assert!(decrypt(&res, &aes, &[0_u8; IV_LEN], &[], TAG_LEN).unwrap().is_empty());
+    }
+
+    // McGrew-Viega test case 2
+    #[test]
+    fn mcgrew_viega_case_2() {
+        let aes = aes::Aes128::new(&[0_u8; aes::KEY_LEN_128]);
+        let exp_cipher = [
+            0x03, 0x88, 0xda, 0xce, 0x60, 0xb6, 0xa3, 0x92, 0xf3, 0x28, 0xc2, 0xb9, 0x71, 0xb2, 0xfe, 0x78,
+            0xab, 0x6e, 0x47, 0xd4, 0x2c, 0xec, 0x13, 0xbd, 0xf5, 0x3a, 0x67, 0xb2, 0x12, 0x57, 0xbd, 0xdf,
+        ];
+
+        let res = encrypt(&[0_u8; aes::BLOCK_LEN], &aes, &[0_u8; IV_LEN], &[], TAG_LEN).unwrap();
+        assert_eq!(res, exp_cipher);
+
+        let pt = decrypt(&res, &aes, &[0_u8; IV_LEN], &[], TAG_LEN).unwrap();
+        assert_eq!(pt, [0_u8; aes::BLOCK_LEN]);
+    }
+
+    // McGrew-Viega test case 3
+    #[test]
+    fn mcgrew_viega_case_3() {
+        let iv = [0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88];
+        let exp_cipher = [
+            0x42, 0x83, 0x1e, 0xc2, 0x21, 0x77, 0x74, 0x24, 0x4b, 0x72, 0x21, 0xb7, 0x84, 0xd0, 0xd4, 0x9c,
+            0xe3, 0xaa, 0x21, 0x2f, 0x2c, 0x02, 0xa4, 0xe0, 0x35, 0xc1, 0x7e, 0x23, 0x29, 0xac, 0xa1, 0x2e,
+            0x21, 0xd5, 0x14, 0xb2, 0x54, 0x66, 0x93, 0x1c, 0x7d, 0x8f, 0x6a, 0x5a, 0xac, 0x84, 0xaa, 0x05,
+            0x1b, 0xa3, 0x0b, 0x39, 0x6a, 0x0a, 0xac, 0x97, 0x3d, 0x58, 0xe0, 0x91, 0x47, 0x3f, 0x59, 0x85,
+            0x4d, 0x5c, 0x2a, 0xf3, 0x27, 0xcd, 0x64, 0xa6, 0x2c, 0xf3, 0x5a, 0xbd, 0x2b, 0xa6, 0xfa, 0xb4,
+        ];
+
+        for aes in [aes::Aes128::new_portable(&KEY_128), aes::Aes128::new(&KEY_128)].iter() {
+            let res = encrypt(&PLAINTEXT, aes, &iv, &[], TAG_LEN).unwrap();
+            assert_eq!(res[..], exp_cipher[..]);
+
+            let pt = decrypt(&res, aes, &iv, &[], TAG_LEN).unwrap();
+            assert_eq!(pt[..], PLAINTEXT[..]);
+        }
+    }
+
+    // McGrew-Viega test case 4
+    #[test]
+    fn mcgrew_viega_case_4() {
+        let iv = [0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88];
+        let exp_cipher = [
+            0x42, 0x83, 0x1e, 0xc2, 0x21, 0x77, 0x74, 0x24, 0x4b, 0x72, 0x21, 0xb7, 0x84, 0xd0, 0xd4, 0x9c,
+            0xe3, 0xaa, 0x21, 0x2f, 0x2c, 0x02, 0xa4, 0xe0, 0x35, 0xc1, 0x7e, 0x23, 0x29, 0xac, 0xa1, 0x2e,
+            0x21, 0xd5, 0x14, 0xb2, 0x54, 0x66, 0x93, 0x1c, 0x7d, 0x8f, 0x6a, 0x5a, 0xac, 0x84, 0xaa, 0x05,
+            0x1b, 0xa3, 0x0b, 0x39, 0x6a, 0x0a, 0xac, 0x97, 0x3d, 0x58, 0xe0, 0x91,
+            0x5b, 0xc9, 0x4f, 0xbc, 0x32, 0x21, 0xa5, 0xdb, 0x94, 0xfa, 0xe9, 0x5a, 0xe7, 0x12, 0x1a, 0x47,
+        ];
+
+        let aes = aes::Aes128::new(&KEY_128);
+
+        let res = encrypt(&PLAINTEXT[..60], &aes, &iv, &AAD, TAG_LEN).unwrap();
+        assert_eq!(res[..], exp_cipher[..]);
+
+        let pt = decrypt(&res, &aes, &iv, &AAD, TAG_LEN).unwrap();
+        assert_eq!(pt[..], PLAINTEXT[..60]);
+
+        // tampered AAD, ciphertext or tag must fail verification
+        assert!(decrypt(&res, &aes, &iv, &AAD[1..], TAG_LEN).is_err());
+
+        let mut tampered = res.clone();
+        tampered[0] ^= 0x01;
+        assert!(decrypt(&tampered, &aes, &iv, &AAD, TAG_LEN).is_err());
+
+        tampered = res;
+        tampered[60] ^= 0x80;
+        assert!(decrypt(&tampered, &aes, &iv, &AAD, TAG_LEN).is_err());
+    }
+
+    // McGrew-Viega test case 5: 64-bit IV
+    #[test]
+    fn mcgrew_viega_case_5() {
+        let iv = [0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad];
+        let exp_cipher = [
+            0x61, 0x35, 0x3b, 0x4c, 0x28, 0x06, 0x93, 0x4a, 0x77, 0x7f, 0xf5, 0x1f, 0xa2, 0x2a, 0x47, 0x55,
+            0x69, 0x9b, 0x2a, 0x71, 0x4f, 0xcd, 0xc6, 0xf8, 0x37, 0x66, 0xe5, 0xf9, 0x7b, 0x6c, 0x74, 0x23,
+            0x73, 0x80, 0x69, 0x00, 0xe4, 0x9f, 0x24, 0xb2, 0x2b, 0x09, 0x75, 0x44, 0xd4, 0x89, 0x6b, 0x42,
+            0x49, 0x89, 0xb5, 0xe1, 0xeb, 0xac, 0x0f, 0x07, 0xc2, 0x3f, 0x45, 0x98,
+            0x36, 0x12, 0xd2, 0xe7, 0x9e, 0x3b, 0x07, 0x85, 0x56, 0x1b, 0xe1, 0x4a, 0xac, 0xa2, 0xfc, 0xcb,
+        ];
+
+        let aes = aes::Aes128::new(&KEY_128);
+
+        let res = encrypt(&PLAINTEXT[..60], &aes, &iv, &AAD, TAG_LEN).unwrap();
+        assert_eq!(res[..], exp_cipher[..]);
+
+        let pt = decrypt(&res, &aes, &iv, &AAD, TAG_LEN).unwrap();
+        assert_eq!(pt[..], PLAINTEXT[..60]);
+    }
+
+    // McGrew-Viega test case 6: 480-bit IV
+    #[test]
+    fn mcgrew_viega_case_6() {
+        let iv = [
+            0x93, 0x13, 0x22, 0x5d, 0xf8, 0x84, 0x06, 0xe5, 0x55, 0x90, 0x9c, 0x5a, 0xff, 0x52, 0x69, 0xaa,
+            0x6a, 0x7a, 0x95, 0x38, 0x53, 0x4f, 0x7d, 0xa1, 0xe4, 0xc3, 0x03, 0xd2, 0xa3, 0x18, 0xa7, 0x28,
+            0xc3, 0xc0, 0xc9, 0x51, 0x56, 0x80, 0x95, 0x39, 0xfc, 0xf0, 0xe2, 0x42, 0x9a, 0x6b, 0x52, 0x54,
+            0x16, 0xae, 0xdb, 0xf5, 0xa0, 0xde, 0x6a, 0x57, 0xa6, 0x37, 0xb3, 0x9b,
+        ];
+        let exp_cipher = [
+            0x8c, 0xe2, 0x49, 0x98, 0x62, 0x56, 0x15, 0xb6, 0x03, 0xa0, 0x33, 0xac, 0xa1, 0x3f, 0xb8, 0x94,
+            0xbe, 0x91, 0x12, 0xa5, 0xc3, 0xa2, 0x11, 0xa8, 0xba, 0x26, 0x2a, 0x3c, 0xca, 0x7e, 0x2c, 0xa7,
+            0x01, 0xe4, 0xa9, 0xa4, 0xfb, 0xa4, 0x3c, 0x90, 0xcc, 0xdc, 0xb2, 0x81, 0xd4, 0x8c, 0x7c, 0x6f,
+            0xd6, 0x28, 0x75, 0xd2, 0xac, 0xa4, 0x17, 0x03, 0x4c, 0x34, 0xae, 0xe5,
+            0x61, 0x9c, 0xc5, 0xae, 0xff, 0xfe, 0x0b, 0xfa, 0x46, 0x2a, 0xf4, 0x3c, 0x16, 0x99, 0xd0, 0x50,
+        ];
+
+        let aes = aes::Aes128::new(&KEY_128);
+
+        let res = encrypt(&PLAINTEXT[..60], &aes, &iv, &AAD, TAG_LEN).unwrap();
+        assert_eq!(res[..], exp_cipher[..]);
+
+        let pt = decrypt(&res, &aes, &iv, &AAD, TAG_LEN).unwrap();
+        assert_eq!(pt[..], PLAINTEXT[..60]);
+    }
+
+    // McGrew-Viega test case 7
+    #[test]
+    fn mcgrew_viega_case_7() {
+        let aes = aes::Aes192::new(&[0_u8; aes::KEY_LEN_192]);
+        let exp_tag = [0xcd, 0x33, 0xb2, 0x8a, 0xc7, 0x73, 0xf7, 0x4b, 0xa0, 0x0e, 0xd1, 0xf3, 0x12, 0x57, 0x24, 0x35];
+
+        let res = encrypt(&[], &aes, &[0_u8; IV_LEN], &[], TAG_LEN).unwrap();
+        assert_eq!(res, exp_tag);
+
+        assert!(decrypt(&res, &aes, &[0_u8; IV_LEN], &[], TAG_LEN).unwrap().is_empty());
+    }
+
+    // McGrew-Viega test case 8
+    #[test]
+    fn mcgrew_viega_case_8() {
+        let aes = aes::Aes192::new(&[0_u8; aes::KEY_LEN_192]);
+        let exp_cipher = [
+            0x98, 0xe7, 0x24, 0x7c, 0x07, 0xf0, 0xfe, 0x41, 0x1c, 0x26, 0x7e, 0x43, 0x84, 0xb0, 0xf6, 0x00,
+            0x2f, 0xf5, 0x8d, 0x80, 0x03, 0x39, 0x27, 0xab, 0x8e, 0xf4, 0xd4, 0x58, 0x75, 0x14, 0xf0, 0xfb,
+        ];
+
+        let res = encrypt(&[0_u8; aes::BLOCK_LEN], &aes, &[0_u8; IV_LEN], &[], TAG_LEN).unwrap();
+        assert_eq!(res, exp_cipher);
+
+        let pt = decrypt(&res, &aes, &[0_u8; IV_LEN], &[], TAG_LEN).unwrap();
+        assert_eq!(pt, [0_u8; aes::BLOCK_LEN]);
+    }
+
+    // McGrew-Viega test case 9
+    #[test]
+    fn mcgrew_viega_case_9() {
+        let iv = [0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88];
+        let exp_cipher = [
+            0x39, 0x80, 0xca, 0x0b, 0x3c, 0x00, 0xe8, 0x41, 0xeb, 0x06, 0xfa, 0xc4, 0x87, 0x2a, 0x27, 0x57,
+            0x85, 0x9e, 0x1c, 0xea, 0xa6, 0xef, 0xd9, 0x84, 0x62, 0x85, 0x93, 0xb4, 0x0c, 0xa1, 0xe1, 0x9c,
+            0x7d, 0x77, 0x3d, 0x00, 0xc1, 0x44, 0xc5, 0x25, 0xac, 0x61, 0x9d, 0x18, 0xc8, 0x4a, 0x3f, 0x47,
+            0x18, 0xe2, 0x44, 0x8b, 0x2f, 0xe3, 0x24, 0xd9, 0xcc, 0xda, 0x27, 0x10, 0xac, 0xad, 0xe2, 0x56,
+            0x99, 0x24, 0xa7, 0xc8, 0x58, 0x73, 0x36, 0xbf, 0xb1, 0x18, 0x02, 0x4d, 0xb8, 0x67, 0x4a, 0x14,
+        ];
+
+        for aes in [aes::Aes192::new_portable(&KEY_192), aes::Aes192::new(&KEY_192)].iter() {
+            let res = encrypt(&PLAINTEXT, aes, &iv, &[], TAG_LEN).unwrap();
+            assert_eq!(res[..], exp_cipher[..]);
+
+            let pt = decrypt(&res, aes, &iv, &[], TAG_LEN).unwrap();
+            assert_eq!(pt[..], PLAINTEXT[..]);
+        }
+    }
+
+    // McGrew-Viega test case 10
+    #[test]
+    fn mcgrew_viega_case_10() {
+        let iv = [0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88];
+        let exp_cipher = [
+            0x39, 0x80, 0xca, 0x0b, 0x3c, 0x00, 0xe8, 0x41, 0xeb, 0x06, 0xfa, 0xc4, 0x87, 0x2a, 0x27, 0x57,
+            0x85, 0x9e, 0x1c, 0xea, 0xa6, 0xef, 0xd9, 0x84, 0x62, 0x85, 0x93, 0xb4, 0x0c, 0xa1, 0xe1, 0x9c,
+            0x7d, 0x77, 0x3d, 0x00, 0xc1, 0x44, 0xc5, 0x25, 0xac, 0x61, 0x9d, 0x18, 0xc8, 0x4a, 0x3f, 0x47,
+            0x18, 0xe2, 0x44, 0x8b, 0x2f, 0xe3, 0x24, 0xd9, 0xcc, 0xda, 0x27, 0x10,
+            0x25, 0x19, 0x49, 0x8e, 0x80, 0xf1, 0x47, 0x8f, 0x37, 0xba, 0x55, 0xbd, 0x6d, 0x27, 0x61, 0x8c,
+        ];
+
+        let aes = aes::Aes192::new(&KEY_192);
+
+        let res = encrypt(&PLAINTEXT[..60], &aes, &iv, &AAD, TAG_LEN).unwrap();
+        assert_eq!(res[..], exp_cipher[..]);
+
+        let pt = decrypt(&res, &aes, &iv, &AAD, TAG_LEN).unwrap();
+        assert_eq!(pt[..], PLAINTEXT[..60]);
+    }
+
+    // McGrew-Viega test case 11: 64-bit IV
+    #[test]
+    fn mcgrew_viega_case_11() {
+        let iv = [0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad];
+        let exp_cipher = [
+            0x0f, 0x10, 0xf5, 0x99, 0xae, 0x14, 0xa1, 0x54, 0xed, 0x24, 0xb3, 0x6e, 0x25, 0x32, 0x4d, 0xb8,
+            0xc5, 0x66, 0x63, 0x2e, 0xf2, 0xbb, 0xb3, 0x4f, 0x83, 0x47, 0x28, 0x0f, 0xc4, 0x50, 0x70, 0x57,
+            0xfd, 0xdc, 0x29, 0xdf, 0x9a, 0x47, 0x1f, 0x75, 0xc6, 0x65, 0x41, 0xd4, 0xd4, 0xda, 0xd1, 0xc9,
+            0xe9, 0x3a, 0x19, 0xa5, 0x8e, 0x8b, 0x47, 0x3f, 0xa0, 0xf0, 0x62, 0xf7,
+            0x65, 0xdc, 0xc5, 0x7f, 0xcf, 0x62, 0x3a, 0x24, 0x09, 0x4f, 0xcc, 0xa4, 0x0d, 0x35, 0x33, 0xf8,
+        ];
+
+        let aes = aes::Aes192::new(&KEY_192);
+
+        let res = encrypt(&PLAINTEXT[..60], &aes, &iv, &AAD, TAG_LEN).unwrap();
+        assert_eq!(res[..], exp_cipher[..]);
+
+        let pt = decrypt(&res, &aes, &iv, &AAD, TAG_LEN).unwrap();
+        assert_eq!(pt[..], PLAINTEXT[..60]);
+    }
+
+    // McGrew-Viega test case 12: 480-bit IV
+    #[test]
+    fn mcgrew_viega_case_12() {
+        let iv = [
+            0x93, 0x13, 0x22, 0x5d, 0xf8, 0x84, 0x06, 0xe5, 0x55, 0x90, 0x9c, 0x5a, 0xff, 0x52, 0x69, 0xaa,
+            0x6a, 0x7a, 0x95, 0x38, 0x53, 0x4f, 0x7d, 0xa1, 0xe4, 0xc3, 0x03, 0xd2, 0xa3, 0x18, 0xa7, 0x28,
+            0xc3, 0xc0, 0xc9, 0x51, 0x56, 0x80, 0x95, 0x39, 0xfc, 0xf0, 0xe2, 0x42, 0x9a, 0x6b, 0x52, 0x54,
+            0x16, 0xae, 0xdb, 0xf5, 0xa0, 0xde, 0x6a, 0x57, 0xa6, 0x37, 0xb3, 0x9b,
+        ];
+        let exp_cipher = [
+            0xd2, 0x7e, 0x88, 0x68, 0x1c, 0xe3, 0x24, 0x3c, 0x48, 0x30, 0x16, 0x5a, 0x8f, 0xdc, 0xf9, 0xff,
+            0x1d, 0xe9, 0xa1, 0xd8, 0xe6, 0xb4, 0x47, 0xef, 0x6e, 0xf7, 0xb7, 0x98, 0x28, 0x66, 0x6e, 0x45,
+            0x81, 0xe7, 0x90, 0x12, 0xaf, 0x34, 0xdd, 0xd9, 0xe2, 0xf0, 0x37, 0x58, 0x9b, 0x29, 0x2d, 0xb3,
+            0xe6, 0x7c, 0x03, 0x67, 0x45, 0xfa, 0x22, 0xe7, 0xe9, 0xb7, 0x37, 0x3b,
+            0xdc, 0xf5, 0x66, 0xff, 0x29, 0x1c, 0x25, 0xbb, 0xb8, 0x56, 0x8f, 0xc3, 0xd3, 0x76, 0xa6, 0xd9,
+        ];
+
+        let aes = aes::Aes192::new(&KEY_192);
+
+        let res = encrypt(&PLAINTEXT[..60], &aes, &iv, &AAD, TAG_LEN).unwrap();
+        assert_eq!(res[..], exp_cipher[..]);
+
+        let pt = decrypt(&res, &aes, &iv, &AAD, TAG_LEN).unwrap();
+        assert_eq!(pt[..], PLAINTEXT[..60]);
+    }
+
+    // McGrew-Viega test case 14
+    #[test]
+    fn mcgrew_viega_case_14() {
+        let aes = aes::Aes256::new(&[0_u8; aes::KEY_LEN_256]);
+        let exp_cipher = [
+            0xce, 0xa7, 0x40, 0x3d, 0x4d, 0x60, 0x6b, 0x6e, 0x07, 0x4e, 0xc5, 0xd3, 0xba, 0xf3, 0x9d, 0x18,
+            0xd0, 0xd1, 0xc8, 0xa7, 0x99, 0x99, 0x6b, 0xf0, 0x26, 0x5b, 0x98, 0xb5, 0xd4, 0x8a, 0xb9, 0x19,
+        ];
+
+        let res = encrypt(&[0_u8; aes::BLOCK_LEN], &aes, &[0_u8; IV_LEN], &[], TAG_LEN).unwrap();
+        assert_eq!(res, exp_cipher);
+    }
+
+    // McGrew-Viega test case 16
+    #[test]
+    fn mcgrew_viega_case_16() {
+        let key = [
+            0xfe, 0xff, 0xe9, 0x92, 0x86, 0x65, 0x73, 0x1c, 0x6d, 0x6a, 0x8f, 0x94, 0x67, 0x30, 0x83, 0x08,
+            0xfe, 0xff, 0xe9, 0x92, 0x86, 0x65, 0x73, 0x1c, 0x6d, 0x6a, 0x8f, 0x94, 0x67, 0x30, 0x83, 0x08,
+        ];
+        let iv = [0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88];
+        let exp_cipher = [
+            0x52, 0x2d, 0xc1, 0xf0, 0x99, 0x56, 0x7d, 0x07, 0xf4, 0x7f, 0x37, 0xa3, 0x2a, 0x84, 0x42, 0x7d,
+            0x64, 0x3a, 0x8c, 0xdc, 0xbf, 0xe5, 0xc0, 0xc9, 0x75, 0x98, 0xa2, 0xbd, 0x25, 0x55, 0xd1, 0xaa,
+            0x8c, 0xb0, 0x8e, 0x48, 0x59, 0x0d, 0xbb, 0x3d, 0xa7, 0xb0, 0x8b, 0x10, 0x56, 0x82, 0x88, 0x38,
+            0xc5, 0xf6, 0x1e, 0x63, 0x93, 0xba, 0x7a, 0x0a, 0xbc, 0xc9, 0xf6, 0x62,
+            0x76, 0xfc, 0x6e, 0xce, 0x0f, 0x4e, 0x17, 0x68, 0xcd, 0xdf, 0x88, 0x53, 0xbb, 0x2d, 0x55, 0x1b,
+        ];
+
+        for aes in [aes::Aes256::new_portable(&key), aes::Aes256::new(&key)].iter() {
+            let res = encrypt(&PLAINTEXT[..60], aes, &iv, &AAD, TAG_LEN).unwrap();
+            assert_eq!(res[..], exp_cipher[..]);
+
+            let pt = decrypt(&res, aes, &iv, &AAD, TAG_LEN).unwrap();
+            assert_eq!(pt[..], PLAINTEXT[..60]);
+        }
+    }
+
+    #[test]
+    fn check_truncated_tags() {
+        let iv = [0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88];
+        let aes = aes::Aes128::new(&KEY_128);
+
+        let full = encrypt(&PLAINTEXT[..60], &aes, &iv, &AAD, TAG_LEN).unwrap();
+
+        for &tag_len in [4, 8, 12, 13, 14, 15].iter() {
+            // truncated tag is the leftmost bytes of the full tag
+            let res = encrypt(&PLAINTEXT[..60], &aes, &iv, &AAD, tag_len).unwrap();
+            assert_eq!(res[..], full[..60 + tag_len]);
+
+            assert_eq!(decrypt(&res, &aes, &iv, &AAD, tag_len).unwrap()[..], PLAINTEXT[..60]);
+            assert!(decrypt(&res, &aes, &iv, &AAD, tag_len + 1).is_err());
+        }
+
+        for &tag_len in [0, 1, 5, 11, 17].iter() {
+            assert!(encrypt(&PLAINTEXT, &aes, &iv, &AAD, tag_len).is_err());
+        }
+
+        // IV must not be empty
+        assert!(encrypt(&PLAINTEXT, &aes, &[], &AAD, TAG_LEN).is_err());
+    }
+}
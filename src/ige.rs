@@ -0,0 +1,170 @@
+//! Infinite Garble Extension mode (as used by OpenSSL and Telegram MTProto)
+//!
+//! Each block is chained to both the previous cipher and plaintext blocks:
+//!
+//! y_i = CIPHER( x_i ^ y_i-1 , key ) ^ x_i-1
+//!
+//! The 32 byte IV holds both initial blocks: y_0 || x_0 (OpenSSL layout).
+
+use alloc::vec::Vec;
+
+use crate::{aes, xor_equals, BlockCipher, Error};
+
+/// Initialization vector length for AES-IGE: two chained blocks
+pub const IV_LEN: usize = 2 * aes::BLOCK_LEN;
+
+/// Encrypt a message in IGE mode
+///
+/// Message must be a multiple of the AES block length (16)
+pub fn encrypt<C: BlockCipher>(
+    msg: &[u8],
+    cipher: &C,
+    iv: &[u8; IV_LEN],
+) -> Result<Vec<u8>, Error> {
+    let len = msg.len();
+    if len % aes::BLOCK_LEN != 0 {
+        return Err(Error::InvalidLength);
+    }
+
+    let mut res = Vec::with_capacity(len);
+
+    let mut prev_cipher = [0_u8; aes::BLOCK_LEN];
+    let mut prev_plain = [0_u8; aes::BLOCK_LEN];
+    prev_cipher.copy_from_slice(&iv[..aes::BLOCK_LEN]);
+    prev_plain.copy_from_slice(&iv[aes::BLOCK_LEN..]);
+
+    for block in msg.chunks_exact(aes::BLOCK_LEN) {
+        // next cipher block:       CIPHER( block_i ^ cipher_block[i-1] , key ) ^ plain_block[i-1]
+        // blocks guaranteed to be the same length, safe to just unwrap here
+        xor_equals(&mut prev_cipher, block).unwrap();
+        prev_cipher = cipher.encrypt_block(&prev_cipher);
+        xor_equals(&mut prev_cipher, &prev_plain).unwrap();
+
+        res.extend_from_slice(&prev_cipher);
+        prev_plain.copy_from_slice(block);
+    }
+
+    Ok(res)
+}
+
+/// Decrypt a message in IGE mode
+///
+/// Message must be a multiple of the AES block length (16)
+pub fn decrypt<C: BlockCipher>(
+    ciphertext: &[u8],
+    cipher: &C,
+    iv: &[u8; IV_LEN],
+) -> Result<Vec<u8>, Error> {
+    let len = ciphertext.len();
+    if len % aes::BLOCK_LEN != 0 {
+        return Err(Error::InvalidLength);
+    }
+
+    let mut res = Vec::with_capacity(len);
+
+    let mut prev_cipher = [0_u8; aes::BLOCK_LEN];
+    let mut prev_plain = [0_u8; aes::BLOCK_LEN];
+    prev_cipher.copy_from_slice(&iv[..aes::BLOCK_LEN]);
+    prev_plain.copy_from_slice(&iv[aes::BLOCK_LEN..]);
+
+    for block in ciphertext.chunks_exact(aes::BLOCK_LEN) {
+        // next plaintext block:       CIPHER-INV( block_i ^ plain_block[i-1] , key ) ^ cipher_block[i-1]
+        // blocks guaranteed to be the same length, safe to just unwrap here
+        xor_equals(&mut prev_plain, block).unwrap();
+        prev_plain = cipher.decrypt_block(&prev_plain);
+        xor_equals(&mut prev_plain, &prev_cipher).unwrap();
+
+        res.extend_from_slice(&prev_plain);
+        prev_cipher.copy_from_slice(block);
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // OpenSSL test/igetest.c: ige_test_vectors[0]
+    #[test]
+    fn openssl_test_vector_one() {
+        let key = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+        ];
+        let iv = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+            0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+        ];
+        let msg = [0_u8; 32];
+        let exp_cipher = [
+            0x1a, 0x85, 0x19, 0xa6, 0x55, 0x7b, 0xe6, 0x52, 0xe9, 0xda, 0x8e, 0x43, 0xda, 0x4e, 0xf4, 0x45,
+            0x3c, 0xf4, 0x56, 0xb4, 0xca, 0x48, 0x8a, 0xa3, 0x83, 0xc7, 0x9c, 0x98, 0xb3, 0x47, 0x97, 0xcb,
+        ];
+
+        for aes in [aes::Aes128::new_portable(&key), aes::Aes128::new(&key)].iter() {
+            let cipher = encrypt(&msg, aes, &iv).unwrap();
+            assert_eq!(cipher, exp_cipher);
+
+            assert_eq!(decrypt(&cipher, aes, &iv).unwrap(), msg);
+        }
+    }
+
+    // OpenSSL test/igetest.c: ige_test_vectors[1]
+    #[test]
+    fn openssl_test_vector_two() {
+        let key = b"This is an imple";
+        let iv = b"mentation of IGE mode for OpenSS";
+        let msg = [
+            0x99, 0x70, 0x64, 0x87, 0xa1, 0xcd, 0xe6, 0x13, 0xbc, 0x6d, 0xe0, 0xb6, 0xf2, 0x4b, 0x1c, 0x7a,
+            0xa4, 0x48, 0xc8, 0xb9, 0xc3, 0x40, 0x3e, 0x34, 0x67, 0xa8, 0xca, 0xd8, 0x93, 0x40, 0xf5, 0x3b,
+        ];
+        let exp_cipher = b"L. Let's hope Ben got it right!\n";
+
+        let aes = aes::Aes128::new(key);
+
+        let cipher = encrypt(&msg, &aes, iv).unwrap();
+        assert_eq!(cipher[..], exp_cipher[..]);
+
+        assert_eq!(decrypt(&cipher, &aes, iv).unwrap(), msg);
+    }
+
+    #[test]
+    fn check_round_trip() {
+        // MTProto uses AES-256-IGE
+        let key = [0x5a_u8; aes::KEY_LEN_256];
+        let iv = [0xa5_u8; IV_LEN];
+
+        let mut msg = [0_u8; aes::BLOCK_LEN * 19];
+        for (i, byte) in msg.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+
+        for aes in [aes::Aes256::new_portable(&key), aes::Aes256::new(&key)].iter() {
+            for len in (0..=msg.len()).step_by(aes::BLOCK_LEN) {
+                let cipher = encrypt(&msg[..len], aes, &iv).unwrap();
+                assert_eq!(decrypt(&cipher, aes, &iv).unwrap(), msg[..len]);
+            }
+
+            // use an invalid length, ensure error is returned
+            assert!(encrypt(&msg[1..], aes, &iv).is_err());
+            assert!(decrypt(&msg[1..], aes, &iv).is_err());
+        }
+    }
+
+    #[test]
+    fn check_plaintext_chaining() {
+        let aes = aes::Aes128::new(b"YELLOW SUBMARINE");
+        let iv = [0_u8; IV_LEN];
+
+        // changing the second IV half (x_0) changes every cipher block
+        let mut other_iv = iv;
+        other_iv[IV_LEN - 1] ^= 0x01;
+
+        let cipher = encrypt(&[0_u8; 48], &aes, &iv).unwrap();
+        let other = encrypt(&[0_u8; 48], &aes, &other_iv).unwrap();
+
+        for (block, other_block) in cipher.chunks(aes::BLOCK_LEN).zip(other.chunks(aes::BLOCK_LEN)) {
+            assert_ne!(block, other_block);
+        }
+    }
+}
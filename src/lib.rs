@@ -1,27 +1,128 @@
 #![no_std]
+// length checks use `%`, `usize::is_multiple_of` would require Rust 1.87
+#![allow(clippy::manual_is_multiple_of)]
 
 extern crate alloc;
 
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
 use alloc::vec::Vec;
+use core::convert::TryInto;
 
 /// AES engine for single block en/decryption
 pub mod aes;
 
+/// AES-NI hardware backend for x86_64
+#[cfg(target_arch = "x86_64")]
+pub mod aesni;
+
+/// Bitsliced constant-time AES backend
+#[cfg(feature = "bitslice")]
+pub mod bitslice;
+
 /// CBC mode for AES
 pub mod cbc;
 
+/// CBC mode with ciphertext stealing for AES
+pub mod cbc_cs;
+
+/// ISO/IEC 9797-1 CBC-MAC for AES
+pub mod cbc_mac;
+
+/// CCM authenticated encryption mode for AES
+pub mod ccm;
+
+/// CCM* mode for IEEE 802.15.4
+pub mod ccm_star;
+
+/// CFB mode for AES
+pub mod cfb;
+
+/// CMAC message authentication for AES
+pub mod cmac;
+
 /// CTR mode for AES
 pub mod ctr;
 
+/// EAX authenticated encryption mode for AES
+pub mod eax;
+
 /// ECB mode for AES
 pub mod ecb;
 
+/// GCM authenticated encryption mode for AES
+pub mod gcm;
+
+/// IGE mode for AES (OpenSSL, Telegram MTProto)
+pub mod ige;
+
+/// OFB mode for AES
+pub mod ofb;
+
+/// OCB authenticated encryption mode for AES
+pub mod ocb;
+
+/// PCBC mode for AES (legacy Kerberos v4 interop)
+pub mod pcbc;
+
 /// PKCS#7 padding for AES block size
 pub mod pkcs7;
 
+/// SIV deterministic authenticated encryption mode for AES (RFC 5297)
+pub mod siv;
+
+/// T-table AES backend (fast, but not constant-time)
+#[cfg(feature = "ttable")]
+mod ttable;
+
+/// XCBC message authentication and PRF for AES (IPsec)
+pub mod xcbc;
+
+/// XTS mode for AES (IEEE 1619 sector encryption)
+pub mod xts;
+
 #[derive(Debug)]
 pub enum Error {
     InvalidLength,
+    InvalidTag,
+    InvalidSecurityLevel,
+}
+
+// Number of blocks per call to `encrypt_blocks` / `decrypt_blocks` in batched modes,
+// matches the AES-NI interleave width
+pub(crate) const PAR_BLOCKS: usize = 8;
+
+/// Block cipher interface used by the modes of operation
+///
+/// All ciphers in the crate use 128-bit blocks
+pub trait BlockCipher {
+    /// Block length (in bytes)
+    const BLOCK_LEN: usize = aes::BLOCK_LEN;
+
+    /// Encrypt a single block
+    fn encrypt_block(&self, block: &[u8; aes::BLOCK_LEN]) -> [u8; aes::BLOCK_LEN];
+
+    /// Decrypt a single block
+    fn decrypt_block(&self, block: &[u8; aes::BLOCK_LEN]) -> [u8; aes::BLOCK_LEN];
+
+    /// Encrypt multiple independent blocks in place
+    ///
+    /// Default implementation encrypts one block at a time
+    fn encrypt_blocks(&self, blocks: &mut [[u8; aes::BLOCK_LEN]]) {
+        for block in blocks.iter_mut() {
+            *block = self.encrypt_block(block);
+        }
+    }
+
+    /// Decrypt multiple independent blocks in place
+    ///
+    /// Default implementation decrypts one block at a time
+    fn decrypt_blocks(&self, blocks: &mut [[u8; aes::BLOCK_LEN]]) {
+        for block in blocks.iter_mut() {
+            *block = self.decrypt_block(block);
+        }
+    }
 }
 
 /// Perform bitwise XOR on equal length byte slices
@@ -56,5 +157,26 @@ pub fn xor_equals(left: &mut [u8], right: &[u8]) -> Result<(), Error> {
     Ok(())
 }
 
+// Compare byte slices in constant time (for equal lengths)
+pub(crate) fn ct_eq(left: &[u8], right: &[u8]) -> bool {
+    if left.len() != right.len() {
+        return false;
+    }
+
+    let mut diff = 0_u8;
+    for (el, ar) in left.iter().zip(right.iter()) {
+        diff |= el ^ ar;
+    }
+
+    diff == 0
+}
+
+// Split a buffer into AES blocks, buffer length must be a multiple of the block length
+pub(crate) fn to_blocks(buf: &[u8]) -> Vec<[u8; aes::BLOCK_LEN]> {
+    buf.chunks_exact(aes::BLOCK_LEN)
+        .map(|block| block.try_into().unwrap())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {}
@@ -0,0 +1,482 @@
+//! OCB3 authenticated encryption mode (RFC 7253)
+//!
+//! Single-pass AEAD: each block is whitened with an offset before and after the block cipher,
+//! offsets are derived from the nonce and a table of doublings of L = CIPHER( 0^128 , key ).
+//! The tag encrypts a checksum of the plaintext, and is masked with a hash of the associated data.
+//!
+//! Ciphertexts are returned with the authentication tag appended.
+
+use alloc::vec::Vec;
+
+use crate::cmac::dbl;
+use crate::{aes, ct_eq, to_blocks, xor, xor_equals, BlockCipher, Error};
+
+/// Recommended nonce length (96 bits)
+pub const NONCE_LEN: usize = 12;
+
+/// Maximum nonce length (120 bits)
+pub const MAX_NONCE_LEN: usize = 15;
+
+/// Full authentication tag length
+pub const TAG_LEN: usize = 16;
+
+/// Encrypt and authenticate a message in OCB mode
+///
+/// Nonce must be 1 to MAX_NONCE_LEN bytes, tag length must be 8, 12 or 16 bytes
+pub fn encrypt<C: BlockCipher>(
+    msg: &[u8],
+    cipher: &C,
+    nonce: &[u8],
+    aad: &[u8],
+    tag_len: usize,
+) -> Result<Vec<u8>, Error> {
+    check_params(nonce, tag_len)?;
+
+    let mut table = LTable::new(cipher);
+    let mut offset = initial_offset(cipher, nonce, tag_len);
+
+    let mut res = ocb_blocks(msg, cipher, &mut table, &mut offset, false);
+
+    let tag = compute_tag(cipher, &mut table, &offset, aad, msg);
+
+    res.extend_from_slice(&tag[..tag_len]);
+
+    Ok(res)
+}
+
+/// Verify and decrypt a message in OCB mode
+///
+/// Ciphertext must have the `tag_len` byte authentication tag appended
+///
+/// No plaintext is returned unless the tag is valid
+pub fn decrypt<C: BlockCipher>(
+    ciphertext: &[u8],
+    cipher: &C,
+    nonce: &[u8],
+    aad: &[u8],
+    tag_len: usize,
+) -> Result<Vec<u8>, Error> {
+    check_params(nonce, tag_len)?;
+
+    if ciphertext.len() < tag_len {
+        return Err(Error::InvalidLength);
+    }
+
+    let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - tag_len);
+
+    let mut table = LTable::new(cipher);
+    let mut offset = initial_offset(cipher, nonce, tag_len);
+
+    // the checksum covers the plaintext, so decrypt before verifying
+    let res = ocb_blocks(ciphertext, cipher, &mut table, &mut offset, true);
+
+    let exp_tag = compute_tag(cipher, &mut table, &offset, aad, &res);
+
+    if !ct_eq(&exp_tag[..tag_len], tag) {
+        return Err(Error::InvalidTag);
+    }
+
+    Ok(res)
+}
+
+fn check_params(nonce: &[u8], tag_len: usize) -> Result<(), Error> {
+    if nonce.is_empty() || nonce.len() > MAX_NONCE_LEN {
+        return Err(Error::InvalidLength);
+    }
+
+    match tag_len {
+        8 | 12 | TAG_LEN => Ok(()),
+        _ => Err(Error::InvalidLength),
+    }
+}
+
+// L_* = CIPHER( 0^128 , key ),  L_$ = double( L_* ),  L_0 = double( L_$ ),  L_i = double( L_i-1 )
+//
+// L_i is only needed for i up to ntz( block count ), so the table grows on demand
+struct LTable {
+    star: [u8; aes::BLOCK_LEN],
+    dollar: [u8; aes::BLOCK_LEN],
+    l: Vec<[u8; aes::BLOCK_LEN]>,
+}
+
+impl LTable {
+    fn new<C: BlockCipher>(cipher: &C) -> Self {
+        let star = cipher.encrypt_block(&[0_u8; aes::BLOCK_LEN]);
+        let dollar = dbl(&star);
+        let mut l = Vec::with_capacity(8);
+        l.push(dbl(&dollar));
+
+        Self { star, dollar, l }
+    }
+
+    fn get(&mut self, i: usize) -> [u8; aes::BLOCK_LEN] {
+        while self.l.len() <= i {
+            let next = dbl(&self.l[self.l.len() - 1]);
+            self.l.push(next);
+        }
+
+        self.l[i]
+    }
+
+    // Offset_i = Offset_i-1 ^ L_ntz(i), for the next `n` whole blocks
+    fn offsets(&mut self, offset: &mut [u8; aes::BLOCK_LEN], n: usize) -> Vec<[u8; aes::BLOCK_LEN]> {
+        let mut res = Vec::with_capacity(n);
+
+        for i in 1..=n {
+            // blocks guaranteed to be the same length, safe to just unwrap here
+            xor_equals(offset, &self.get(i.trailing_zeros() as usize)).unwrap();
+            res.push(*offset);
+        }
+
+        res
+    }
+}
+
+// Nonce = num2str( TAGLEN mod 128 , 7 ) || zeros( 120 - bitlen(N) ) || 1 || N
+// Ktop = CIPHER( Nonce[1..122] || zeros(6) , key )
+// Stretch = Ktop || ( Ktop[1..64] ^ Ktop[9..72] )
+// Offset_0 = Stretch[1+bottom..128+bottom]
+fn initial_offset<C: BlockCipher>(cipher: &C, nonce: &[u8], tag_len: usize) -> [u8; aes::BLOCK_LEN] {
+    let mut full = [0_u8; aes::BLOCK_LEN];
+    full[aes::BLOCK_LEN - nonce.len()..].copy_from_slice(nonce);
+    full[aes::BLOCK_LEN - 1 - nonce.len()] |= 1;
+    full[0] |= (((tag_len * 8) % 128) as u8) << 1;
+
+    let bottom = (full[aes::BLOCK_LEN - 1] & 0x3f) as u32;
+    full[aes::BLOCK_LEN - 1] &= 0xc0;
+
+    let ktop = u128::from_be_bytes(cipher.encrypt_block(&full));
+    let stretch = ((ktop >> 64) as u64) ^ ((ktop >> 56) as u64);
+
+    let offset = if bottom == 0 {
+        ktop
+    } else {
+        (ktop << bottom) | ((stretch >> (64 - bottom)) as u128)
+    };
+
+    offset.to_be_bytes()
+}
+
+// C_i = Offset_i ^ CIPHER( P_i ^ Offset_i , key )
+// C_* = P_* ^ MSB_len(P_*)( CIPHER( Offset_* , key ) )    Offset_* = Offset_m ^ L_*
+//
+// Decryption uses CIPHER-INV for whole blocks, the final partial block is the same
+fn ocb_blocks<C: BlockCipher>(
+    text: &[u8],
+    cipher: &C,
+    table: &mut LTable,
+    offset: &mut [u8; aes::BLOCK_LEN],
+    decrypt: bool,
+) -> Vec<u8> {
+    let full_len = text.len() - text.len() % aes::BLOCK_LEN;
+    let (full, partial) = text.split_at(full_len);
+
+    // whole blocks are independent once the offsets are known
+    let mut blocks = to_blocks(full);
+    let offsets = table.offsets(offset, blocks.len());
+
+    for (block, block_offset) in blocks.iter_mut().zip(offsets.iter()) {
+        xor_equals(block, block_offset).unwrap();
+    }
+
+    if decrypt {
+        cipher.decrypt_blocks(&mut blocks);
+    } else {
+        cipher.encrypt_blocks(&mut blocks);
+    }
+
+    for (block, block_offset) in blocks.iter_mut().zip(offsets.iter()) {
+        xor_equals(block, block_offset).unwrap();
+    }
+
+    let mut res = blocks.concat();
+
+    if !partial.is_empty() {
+        xor_equals(offset, &table.star).unwrap();
+        let pad = cipher.encrypt_block(offset);
+
+        // partial block is shorter than the pad, safe to just unwrap here
+        res.extend_from_slice(&xor(partial, &pad[..partial.len()]).unwrap());
+    }
+
+    res
+}
+
+// Tag = CIPHER( Checksum ^ Offset ^ L_$ , key ) ^ HASH( key , A )
+fn compute_tag<C: BlockCipher>(
+    cipher: &C,
+    table: &mut LTable,
+    offset: &[u8; aes::BLOCK_LEN],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> [u8; aes::BLOCK_LEN] {
+    // Checksum = P_1 ^ ... ^ P_m ^ ( P_* || 1 || 0^... )
+    let mut checksum = [0_u8; aes::BLOCK_LEN];
+    for block in plaintext.chunks(aes::BLOCK_LEN) {
+        xor_equals(&mut checksum, &pad_block(block)).unwrap();
+    }
+
+    xor_equals(&mut checksum, offset).unwrap();
+    xor_equals(&mut checksum, &table.dollar).unwrap();
+
+    let mut tag = cipher.encrypt_block(&checksum);
+    xor_equals(&mut tag, &hash(cipher, table, aad)).unwrap();
+
+    tag
+}
+
+// Sum = CIPHER( A_1 ^ Offset_1 , key ) ^ ... ^ CIPHER( A_m ^ Offset_m , key )
+//     ^ CIPHER( ( A_* || 1 || 0^... ) ^ Offset_* , key )
+//
+// Offsets start from zero, Offset_* = Offset_m ^ L_*
+fn hash<C: BlockCipher>(cipher: &C, table: &mut LTable, aad: &[u8]) -> [u8; aes::BLOCK_LEN] {
+    let full_len = aad.len() - aad.len() % aes::BLOCK_LEN;
+    let (full, partial) = aad.split_at(full_len);
+
+    let mut offset = [0_u8; aes::BLOCK_LEN];
+
+    let mut blocks = to_blocks(full);
+    let offsets = table.offsets(&mut offset, blocks.len());
+
+    for (block, block_offset) in blocks.iter_mut().zip(offsets.iter()) {
+        xor_equals(block, block_offset).unwrap();
+    }
+
+    if !partial.is_empty() {
+        xor_equals(&mut offset, &table.star).unwrap();
+
+        let mut last = pad_block(partial);
+        xor_equals(&mut last, &offset).unwrap();
+        blocks.push(last);
+    }
+
+    cipher.encrypt_blocks(&mut blocks);
+
+    let mut sum = [0_u8; aes::BLOCK_LEN];
+    for block in blocks.iter() {
+        xor_equals(&mut sum, block).unwrap();
+    }
+
+    sum
+}
+
+// Pad a partial block with 1 || 0^..., whole blocks are left as-is
+fn pad_block(block: &[u8]) -> [u8; aes::BLOCK_LEN] {
+    let mut res = [0_u8; aes::BLOCK_LEN];
+    res[..block.len()].copy_from_slice(block);
+
+    if block.len() < aes::BLOCK_LEN {
+        res[block.len()] = 0x80;
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seq(len: usize) -> Vec<u8> {
+        (0..len as u8).collect()
+    }
+
+    // RFC 7253 Appendix A: sample results, AES-128 key 000102...0f, 128-bit tags
+    #[test]
+    fn rfc_sample_results() {
+        let key = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+        ];
+
+        // (aad length, plaintext length, ciphertext || tag) with nonce BBAA99887766554433221100 + i
+        let vectors: [(usize, usize, &[u8]); 16] = [
+            (0, 0, &[0x78, 0x54, 0x07, 0xbf, 0xff, 0xc8, 0xad, 0x9e, 0xdc, 0xc5, 0x52, 0x0a, 0xc9, 0x11, 0x1e, 0xe6]),
+            (8, 8, &[
+                0x68, 0x20, 0xb3, 0x65, 0x7b, 0x6f, 0x61, 0x5a, 0x57, 0x25, 0xbd, 0xa0, 0xd3, 0xb4, 0xeb, 0x3a,
+                0x25, 0x7c, 0x9a, 0xf1, 0xf8, 0xf0, 0x30, 0x09,
+            ]),
+            (8, 0, &[0x81, 0x01, 0x7f, 0x82, 0x03, 0xf0, 0x81, 0x27, 0x71, 0x52, 0xfa, 0xde, 0x69, 0x4a, 0x0a, 0x00]),
+            (0, 8, &[
+                0x45, 0xdd, 0x69, 0xf8, 0xf5, 0xaa, 0xe7, 0x24, 0x14, 0x05, 0x4c, 0xd1, 0xf3, 0x5d, 0x82, 0x76,
+                0x0b, 0x2c, 0xd0, 0x0d, 0x2f, 0x99, 0xbf, 0xa9,
+            ]),
+            (16, 16, &[
+                0x57, 0x1d, 0x53, 0x5b, 0x60, 0xb2, 0x77, 0x18, 0x8b, 0xe5, 0x14, 0x71, 0x70, 0xa9, 0xa2, 0x2c,
+                0x3a, 0xd7, 0xa4, 0xff, 0x38, 0x35, 0xb8, 0xc5, 0x70, 0x1c, 0x1c, 0xce, 0xc8, 0xfc, 0x33, 0x58,
+            ]),
+            (16, 0, &[0x8c, 0xf7, 0x61, 0xb6, 0x90, 0x2e, 0xf7, 0x64, 0x46, 0x2a, 0xd8, 0x64, 0x98, 0xca, 0x6b, 0x97]),
+            (0, 16, &[
+                0x5c, 0xe8, 0x8e, 0xc2, 0xe0, 0x69, 0x27, 0x06, 0xa9, 0x15, 0xc0, 0x0a, 0xeb, 0x8b, 0x23, 0x96,
+                0xf4, 0x0e, 0x1c, 0x74, 0x3f, 0x52, 0x43, 0x6b, 0xdf, 0x06, 0xd8, 0xfa, 0x1e, 0xca, 0x34, 0x3d,
+            ]),
+            (24, 24, &[
+                0x1c, 0xa2, 0x20, 0x73, 0x08, 0xc8, 0x7c, 0x01, 0x07, 0x56, 0x10, 0x4d, 0x88, 0x40, 0xce, 0x19,
+                0x52, 0xf0, 0x96, 0x73, 0xa4, 0x48, 0xa1, 0x22, 0xc9, 0x2c, 0x62, 0x24, 0x10, 0x51, 0xf5, 0x73,
+                0x56, 0xd7, 0xf3, 0xc9, 0x0b, 0xb0, 0xe0, 0x7f,
+            ]),
+            (24, 0, &[0x6d, 0xc2, 0x25, 0xa0, 0x71, 0xfc, 0x1b, 0x9f, 0x7c, 0x69, 0xf9, 0x3b, 0x0f, 0x1e, 0x10, 0xde]),
+            (0, 24, &[
+                0x22, 0x1b, 0xd0, 0xde, 0x7f, 0xa6, 0xfe, 0x99, 0x3e, 0xcc, 0xd7, 0x69, 0x46, 0x0a, 0x0a, 0xf2,
+                0xd6, 0xcd, 0xed, 0x0c, 0x39, 0x5b, 0x1c, 0x3c, 0xe7, 0x25, 0xf3, 0x24, 0x94, 0xb9, 0xf9, 0x14,
+                0xd8, 0x5c, 0x0b, 0x1e, 0xb3, 0x83, 0x57, 0xff,
+            ]),
+            (32, 32, &[
+                0xbd, 0x6f, 0x6c, 0x49, 0x62, 0x01, 0xc6, 0x92, 0x96, 0xc1, 0x1e, 0xfd, 0x13, 0x8a, 0x46, 0x7a,
+                0xbd, 0x3c, 0x70, 0x79, 0x24, 0xb9, 0x64, 0xde, 0xaf, 0xfc, 0x40, 0x31, 0x9a, 0xf5, 0xa4, 0x85,
+                0x40, 0xfb, 0xba, 0x18, 0x6c, 0x55, 0x53, 0xc6, 0x8a, 0xd9, 0xf5, 0x92, 0xa7, 0x9a, 0x42, 0x40,
+            ]),
+            (32, 0, &[0xfe, 0x80, 0x69, 0x0b, 0xee, 0x8a, 0x48, 0x5d, 0x11, 0xf3, 0x29, 0x65, 0xbc, 0x9d, 0x2a, 0x32]),
+            (0, 32, &[
+                0x29, 0x42, 0xbf, 0xc7, 0x73, 0xbd, 0xa2, 0x3c, 0xab, 0xc6, 0xac, 0xfd, 0x9b, 0xfd, 0x58, 0x35,
+                0xbd, 0x30, 0x0f, 0x09, 0x73, 0x79, 0x2e, 0xf4, 0x60, 0x40, 0xc5, 0x3f, 0x14, 0x32, 0xbc, 0xdf,
+                0xb5, 0xe1, 0xdd, 0xe3, 0xbc, 0x18, 0xa5, 0xf8, 0x40, 0xb5, 0x2e, 0x65, 0x34, 0x44, 0xd5, 0xdf,
+            ]),
+            (40, 40, &[
+                0xd5, 0xca, 0x91, 0x74, 0x84, 0x10, 0xc1, 0x75, 0x1f, 0xf8, 0xa2, 0xf6, 0x18, 0x25, 0x5b, 0x68,
+                0xa0, 0xa1, 0x2e, 0x09, 0x3f, 0xf4, 0x54, 0x60, 0x6e, 0x59, 0xf9, 0xc1, 0xd0, 0xdd, 0xc5, 0x4b,
+                0x65, 0xe8, 0x62, 0x8e, 0x56, 0x8b, 0xad, 0x7a, 0xed, 0x07, 0xba, 0x06, 0xa4, 0xa6, 0x94, 0x83,
+                0xa7, 0x03, 0x54, 0x90, 0xc5, 0x76, 0x9e, 0x60,
+            ]),
+            (40, 0, &[0xc5, 0xcd, 0x9d, 0x18, 0x50, 0xc1, 0x41, 0xe3, 0x58, 0x64, 0x99, 0x94, 0xee, 0x70, 0x1b, 0x68]),
+            (0, 40, &[
+                0x44, 0x12, 0x92, 0x34, 0x93, 0xc5, 0x7d, 0x5d, 0xe0, 0xd7, 0x00, 0xf7, 0x53, 0xcc, 0xe0, 0xd1,
+                0xd2, 0xd9, 0x50, 0x60, 0x12, 0x2e, 0x9f, 0x15, 0xa5, 0xdd, 0xbf, 0xc5, 0x78, 0x7e, 0x50, 0xb5,
+                0xcc, 0x55, 0xee, 0x50, 0x7b, 0xcb, 0x08, 0x4e, 0x47, 0x9a, 0xd3, 0x63, 0xac, 0x36, 0x6b, 0x95,
+                0xa9, 0x8c, 0xa5, 0xf3, 0x00, 0x0b, 0x14, 0x79,
+            ]),
+        ];
+
+        for aes in [aes::Aes128::new_portable(&key), aes::Aes128::new(&key)].iter() {
+            for (i, (aad_len, msg_len, exp_cipher)) in vectors.iter().enumerate() {
+                let nonce = [0xbb, 0xaa, 0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, i as u8];
+                let aad = seq(*aad_len);
+                let msg = seq(*msg_len);
+
+                let cipher = encrypt(&msg, aes, &nonce, &aad, TAG_LEN).unwrap();
+                assert_eq!(cipher[..], exp_cipher[..]);
+
+                assert_eq!(decrypt(&cipher, aes, &nonce, &aad, TAG_LEN).unwrap(), msg);
+            }
+        }
+    }
+
+    // RFC 7253 Appendix A: sample result with a 96-bit tag
+    #[test]
+    fn rfc_sample_result_tag_96() {
+        let key = [
+            0x0f, 0x0e, 0x0d, 0x0c, 0x0b, 0x0a, 0x09, 0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, 0x00,
+        ];
+        let nonce = [0xbb, 0xaa, 0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x0d];
+        let exp_cipher = [
+            0x17, 0x92, 0xa4, 0xe3, 0x1e, 0x07, 0x55, 0xfb, 0x03, 0xe3, 0x1b, 0x22, 0x11, 0x6e, 0x6c, 0x2d,
+            0xdf, 0x9e, 0xfd, 0x6e, 0x33, 0xd5, 0x36, 0xf1, 0xa0, 0x12, 0x4b, 0x0a, 0x55, 0xba, 0xe8, 0x84,
+            0xed, 0x93, 0x48, 0x15, 0x29, 0xc7, 0x6b, 0x6a, 0xd0, 0xc5, 0x15, 0xf4, 0xd1, 0xcd, 0xd4, 0xfd,
+            0xac, 0x4f, 0x02, 0xaa,
+        ];
+
+        let aes = aes::Aes128::new(&key);
+
+        let cipher = encrypt(&seq(40), &aes, &nonce, &seq(40), 12).unwrap();
+        assert_eq!(cipher[..], exp_cipher[..]);
+
+        assert_eq!(decrypt(&cipher, &aes, &nonce, &seq(40), 12).unwrap(), seq(40));
+    }
+
+    // RFC 7253 Appendix A: iterative test over many message and AAD lengths
+    fn iterative_test<C: BlockCipher>(aes: &C, tag_len: usize) -> Vec<u8> {
+        let nonce = |n: u32| {
+            let mut res = [0_u8; NONCE_LEN];
+            res[NONCE_LEN - 4..].copy_from_slice(&n.to_be_bytes());
+            res
+        };
+
+        let zeros = [0_u8; 128];
+        let mut c = Vec::new();
+
+        for i in 0..128_u32 {
+            let s = &zeros[..i as usize];
+            c.extend_from_slice(&encrypt(s, aes, &nonce(3 * i + 1), s, tag_len).unwrap());
+            c.extend_from_slice(&encrypt(s, aes, &nonce(3 * i + 2), &[], tag_len).unwrap());
+            c.extend_from_slice(&encrypt(&[], aes, &nonce(3 * i + 3), s, tag_len).unwrap());
+        }
+
+        encrypt(&[], aes, &nonce(385), &c, tag_len).unwrap()
+    }
+
+    #[test]
+    fn rfc_iterative_test() {
+        let exp_tags: [(usize, [&[u8]; 3]); 3] = [
+            (16, [
+                &[0x67, 0xe9, 0x44, 0xd2, 0x32, 0x56, 0xc5, 0xe0, 0xb6, 0xc6, 0x1f, 0xa2, 0x2f, 0xdf, 0x1e, 0xa2],
+                &[0xf6, 0x73, 0xf2, 0xc3, 0xe7, 0x17, 0x4a, 0xae, 0x7b, 0xae, 0x98, 0x6c, 0xa9, 0xf2, 0x9e, 0x17],
+                &[0xd9, 0x0e, 0xb8, 0xe9, 0xc9, 0x77, 0xc8, 0x8b, 0x79, 0xdd, 0x79, 0x3d, 0x7f, 0xfa, 0x16, 0x1c],
+            ]),
+            (12, [
+                &[0x77, 0xa3, 0xd8, 0xe7, 0x35, 0x89, 0x15, 0x8d, 0x25, 0xd0, 0x12, 0x09],
+                &[0x05, 0xd5, 0x6e, 0xad, 0x27, 0x52, 0xc8, 0x6b, 0xe6, 0x93, 0x2c, 0x5e],
+                &[0x54, 0x58, 0x35, 0x9a, 0xc2, 0x3b, 0x0c, 0xba, 0x9e, 0x63, 0x30, 0xdd],
+            ]),
+            (8, [
+                &[0x19, 0x2c, 0x9b, 0x7b, 0xd9, 0x0b, 0xa0, 0x6a],
+                &[0x00, 0x66, 0xbc, 0x6e, 0x0e, 0xf3, 0x4e, 0x24],
+                &[0x7d, 0x4e, 0xa5, 0xd4, 0x45, 0x50, 0x1c, 0xbe],
+            ]),
+        ];
+
+        for (tag_len, exp_tag) in exp_tags.iter() {
+            // K = zeros( KEYLEN - 8 ) || num2str( TAGLEN , 8 )
+            let mut key_128 = [0_u8; aes::KEY_LEN_128];
+            let mut key_192 = [0_u8; aes::KEY_LEN_192];
+            let mut key_256 = [0_u8; aes::KEY_LEN_256];
+            key_128[aes::KEY_LEN_128 - 1] = (tag_len * 8) as u8;
+            key_192[aes::KEY_LEN_192 - 1] = (tag_len * 8) as u8;
+            key_256[aes::KEY_LEN_256 - 1] = (tag_len * 8) as u8;
+
+            assert_eq!(iterative_test(&aes::Aes128::new(&key_128), *tag_len), exp_tag[0]);
+            assert_eq!(iterative_test(&aes::Aes192::new(&key_192), *tag_len), exp_tag[1]);
+            assert_eq!(iterative_test(&aes::Aes256::new(&key_256), *tag_len), exp_tag[2]);
+        }
+    }
+
+    #[test]
+    fn check_invalid_params() {
+        let aes = aes::Aes128::new(b"YELLOW SUBMARINE");
+        let nonce = [0x42_u8; NONCE_LEN];
+        let msg = seq(37);
+
+        assert!(encrypt(&msg, &aes, &[], &[], TAG_LEN).is_err());
+        assert!(encrypt(&msg, &aes, &[0_u8; MAX_NONCE_LEN + 1], &[], TAG_LEN).is_err());
+
+        for &tag_len in [0, 4, 10, 15, TAG_LEN + 1].iter() {
+            assert!(encrypt(&msg, &aes, &nonce, &[], tag_len).is_err());
+        }
+
+        // every nonce length is usable
+        let long_nonce = [0x42_u8; MAX_NONCE_LEN];
+        for nonce_len in 1..=MAX_NONCE_LEN {
+            let cipher = encrypt(&msg, &aes, &long_nonce[..nonce_len], &[], 8).unwrap();
+            assert_eq!(decrypt(&cipher, &aes, &long_nonce[..nonce_len], &[], 8).unwrap(), msg);
+        }
+    }
+
+    #[test]
+    fn check_invalid_tag() {
+        let aes = aes::Aes128::new(b"YELLOW SUBMARINE");
+        let nonce = [0x42_u8; NONCE_LEN];
+        let aad = seq(19);
+        let msg = seq(37);
+
+        let mut cipher = encrypt(&msg, &aes, &nonce, &aad, TAG_LEN).unwrap();
+
+        // tampered nonce, aad, ciphertext or tag must fail verification
+        assert!(decrypt(&cipher, &aes, &nonce[1..], &aad, TAG_LEN).is_err());
+        assert!(decrypt(&cipher, &aes, &nonce, &aad[1..], TAG_LEN).is_err());
+        assert!(decrypt(&cipher[..TAG_LEN - 1], &aes, &nonce, &aad, TAG_LEN).is_err());
+
+        // flip a bit in a whole block, and in the final partial block
+        for &pos in [0, 33, cipher.len() - 1].iter() {
+            cipher[pos] ^= 0x01;
+            assert!(decrypt(&cipher, &aes, &nonce, &aad, TAG_LEN).is_err());
+            cipher[pos] ^= 0x01;
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Output Feedback mode (NIST SP 800-38A Section 6.4)
+//!
+//! The key stream is generated by repeatedly encrypting the IV,
+//! and XORed with the plaintext, so messages can be any length.
+//!
+//! The key stream does not depend on the message, so encryption and decryption are the same operation.
+//! An IV must never be reused with the same key.
+
+use alloc::vec::Vec;
+
+use crate::{aes, BlockCipher};
+
+/// Initialization vector length for AES-OFB
+pub const IV_LEN: usize = 16_usize;
+
+/// Resumable OFB key stream
+///
+/// Unused key stream bytes are kept between calls to `apply`,
+/// so a long stream can be processed in pieces of any length
+pub struct Ofb<'a, C: BlockCipher> {
+    cipher: &'a C,
+    output: [u8; aes::BLOCK_LEN],
+    pos: usize,
+}
+
+impl<'a, C: BlockCipher> Ofb<'a, C> {
+    /// Start an OFB key stream from the IV
+    pub fn new(cipher: &'a C, iv: &[u8; IV_LEN]) -> Self {
+        Self {
+            cipher,
+            output: *iv,
+            // no key stream generated yet, the first output block is CIPHER( IV , key )
+            pos: aes::BLOCK_LEN,
+        }
+    }
+
+    /// En/decrypt the next piece of the stream
+    pub fn apply(&mut self, text: &[u8]) -> Vec<u8> {
+        let mut res = Vec::with_capacity(text.len());
+
+        for &byte in text.iter() {
+            if self.pos == aes::BLOCK_LEN {
+                // O_j = CIPHER( O_j-1 , key )     O_0 = IV
+                self.output = self.cipher.encrypt_block(&self.output);
+                self.pos = 0;
+            }
+
+            res.push(byte ^ self.output[self.pos]);
+            self.pos += 1;
+        }
+
+        res
+    }
+}
+
+/// Encrypt a message in OFB mode
+///
+/// Message can be any length
+pub fn encrypt<C: BlockCipher>(msg: &[u8], cipher: &C, iv: &[u8; IV_LEN]) -> Vec<u8> {
+    Ofb::new(cipher, iv).apply(msg)
+}
+
+/// Decrypt a message in OFB mode
+///
+/// Ciphertext can be any length
+pub fn decrypt<C: BlockCipher>(ciphertext: &[u8], cipher: &C, iv: &[u8; IV_LEN]) -> Vec<u8> {
+    Ofb::new(cipher, iv).apply(ciphertext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IV: [u8; IV_LEN] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    ];
+
+    // NIST SP 800-38A plaintext
+    const MSG: [u8; 64] = [
+        0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a,
+        0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51,
+        0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef,
+        0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10,
+    ];
+
+    // NIST SP 800-38A F.4.1 and F.4.2: OFB-AES128
+    #[test]
+    fn nist_aes_128_test_vectors() {
+        let key = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+        let exp_cipher = [
+            0x3b, 0x3f, 0xd9, 0x2e, 0xb7, 0x2d, 0xad, 0x20, 0x33, 0x34, 0x49, 0xf8, 0xe8, 0x3c, 0xfb, 0x4a,
+            0x77, 0x89, 0x50, 0x8d, 0x16, 0x91, 0x8f, 0x03, 0xf5, 0x3c, 0x52, 0xda, 0xc5, 0x4e, 0xd8, 0x25,
+            0x97, 0x40, 0x05, 0x1e, 0x9c, 0x5f, 0xec, 0xf6, 0x43, 0x44, 0xf7, 0xa8, 0x22, 0x60, 0xed, 0xcc,
+            0x30, 0x4c, 0x65, 0x28, 0xf6, 0x59, 0xc7, 0x78, 0x66, 0xa5, 0x10, 0xd9, 0xc1, 0xd6, 0xae, 0x5e,
+        ];
+
+        for aes in [aes::Aes128::new_portable(&key), aes::Aes128::new(&key)].iter() {
+            let cipher = encrypt(&MSG, aes, &IV);
+            assert_eq!(cipher[..], exp_cipher[..]);
+
+            assert_eq!(decrypt(&cipher, aes, &IV)[..], MSG[..]);
+        }
+    }
+
+    // NIST SP 800-38A F.4.5 and F.4.6: OFB-AES256
+    #[test]
+    fn nist_aes_256_test_vectors() {
+        let key = [
+            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d, 0x77, 0x81,
+            0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3, 0x09, 0x14, 0xdf, 0xf4,
+        ];
+        let exp_cipher = [
+            0xdc, 0x7e, 0x84, 0xbf, 0xda, 0x79, 0x16, 0x4b, 0x7e, 0xcd, 0x84, 0x86, 0x98, 0x5d, 0x38, 0x60,
+            0x4f, 0xeb, 0xdc, 0x67, 0x40, 0xd2, 0x0b, 0x3a, 0xc8, 0x8f, 0x6a, 0xd8, 0x2a, 0x4f, 0xb0, 0x8d,
+            0x71, 0xab, 0x47, 0xa0, 0x86, 0xe8, 0x6e, 0xed, 0xf3, 0x9d, 0x1c, 0x5b, 0xba, 0x97, 0xc4, 0x08,
+            0x01, 0x26, 0x14, 0x1d, 0x67, 0xf3, 0x7b, 0xe8, 0x53, 0x8f, 0x5a, 0x8b, 0xe7, 0x40, 0xe4, 0x84,
+        ];
+
+        for aes in [aes::Aes256::new_portable(&key), aes::Aes256::new(&key)].iter() {
+            let cipher = encrypt(&MSG, aes, &IV);
+            assert_eq!(cipher[..], exp_cipher[..]);
+
+            assert_eq!(decrypt(&cipher, aes, &IV)[..], MSG[..]);
+        }
+    }
+
+    #[test]
+    fn check_resumable_stream() {
+        let key = b"YELLOW SUBMARINE";
+        let aes = aes::Aes128::new(key);
+        let exp_cipher = encrypt(&MSG, &aes, &IV);
+
+        // split at every offset, including block boundaries and empty pieces
+        for split in 0..=MSG.len() {
+            let mut ofb = Ofb::new(&aes, &IV);
+            let mut cipher = ofb.apply(&MSG[..split]);
+            cipher.extend_from_slice(&ofb.apply(&[]));
+            cipher.extend_from_slice(&ofb.apply(&MSG[split..]));
+            assert_eq!(cipher, exp_cipher);
+        }
+
+        // a truncated message encrypts to the truncated ciphertext
+        assert_eq!(encrypt(&MSG[..21], &aes, &IV)[..], exp_cipher[..21]);
+    }
+}
@@ -0,0 +1,173 @@
+//! Propagating Cipher Block Chaining mode (Kerberos v4, WASTE)
+//!
+//! Each plaintext block is XORed with both the previous plaintext and previous cipher block before encryption,
+//! so a single corrupted cipher block garbles every following plaintext block.
+//!
+//! Provided for decrypting legacy data, PCBC is not a secure mode for new designs:
+//! swapping two adjacent cipher blocks only corrupts those two blocks.
+
+use alloc::vec::Vec;
+
+use crate::{aes, to_blocks, xor, xor_equals};
+use crate::{BlockCipher, Error};
+
+/// Initialization vector length for AES-PCBC
+pub const IV_LEN: usize = 16_usize;
+
+/// Encrypt a message in PCBC mode
+///
+/// Message must be a multiple of the AES block length (16)
+pub fn encrypt<C: BlockCipher>(
+    msg: &[u8],
+    cipher: &C,
+    iv: &[u8; IV_LEN],
+) -> Result<Vec<u8>, Error> {
+    let len = msg.len();
+    if len % aes::BLOCK_LEN != 0 {
+        return Err(Error::InvalidLength);
+    }
+
+    let mut res = Vec::with_capacity(len);
+    let mut feedback = *iv;
+
+    for block in msg.chunks_exact(aes::BLOCK_LEN) {
+        // next cipher block:       CIPHER( block_i ^ IV , key )                                        i = 0
+        // next cipher block:       CIPHER( block_i ^ plain_block[i-1] ^ cipher_block[i-1] , key )      i > 0
+        // blocks guaranteed to be the same length, safe to just unwrap here
+        xor_equals(&mut feedback, block).unwrap();
+        let cipher_block = cipher.encrypt_block(&feedback);
+        res.extend_from_slice(&cipher_block);
+
+        feedback = cipher_block;
+        xor_equals(&mut feedback, block).unwrap();
+    }
+
+    Ok(res)
+}
+
+/// Decrypt a message in PCBC mode
+///
+/// Message must be a multiple of the AES block length (16)
+pub fn decrypt<C: BlockCipher>(
+    ciphertext: &[u8],
+    cipher: &C,
+    iv: &[u8; IV_LEN],
+) -> Result<Vec<u8>, Error> {
+    let len = ciphertext.len();
+    if len % aes::BLOCK_LEN != 0 {
+        return Err(Error::InvalidLength);
+    }
+
+    // blocks are independent before chaining, decrypt them all at once
+    let mut blocks = to_blocks(ciphertext);
+    cipher.decrypt_blocks(&mut blocks);
+
+    let mut feedback = *iv;
+
+    for (block, cipher_block) in blocks.iter_mut().zip(ciphertext.chunks_exact(aes::BLOCK_LEN)) {
+        // next plaintext block:       CIPHER-INV( block_i , key ) ^ IV                                      i = 0
+        // next plaintext block:       CIPHER-INV( block_i , key ) ^ cipher_block[i-1] ^ plain_block[i-1]   i > 0
+        xor_equals(block, &feedback)?;
+        feedback.copy_from_slice(&xor(block, cipher_block)?);
+    }
+
+    Ok(blocks.concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cbc;
+
+    const KEY: [u8; aes::KEY_LEN_128] = [
+        0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,
+    ];
+
+    const IV: [u8; IV_LEN] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    ];
+
+    // NIST SP 800-38A plaintext
+    const MSG: [u8; 64] = [
+        0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a,
+        0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51,
+        0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef,
+        0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10,
+    ];
+
+    #[test]
+    fn check_known_answer() {
+        // first block matches CBC (SP 800-38A F.2.1), the rest also chain the plaintext
+        let exp_cipher = [
+            0x76, 0x49, 0xab, 0xac, 0x81, 0x19, 0xb2, 0x46, 0xce, 0xe9, 0x8e, 0x9b, 0x12, 0xe9, 0x19, 0x7d,
+            0x9e, 0x8b, 0xaf, 0xf1, 0x2a, 0xd5, 0x27, 0x0a, 0x0d, 0x1e, 0xef, 0x93, 0xd7, 0x03, 0x79, 0x94,
+            0x57, 0x00, 0xb3, 0x98, 0x03, 0x77, 0x9f, 0xa3, 0x5a, 0x3c, 0x60, 0x0a, 0x49, 0xa1, 0x63, 0xc0,
+            0x33, 0xae, 0x19, 0x9f, 0x27, 0x37, 0x9f, 0x21, 0xbe, 0x6d, 0xd5, 0x7d, 0x29, 0x5c, 0xc8, 0x7d,
+        ];
+
+        for aes in [aes::Aes128::new_portable(&KEY), aes::Aes128::new(&KEY)].iter() {
+            let cipher = encrypt(&MSG, aes, &IV).unwrap();
+            assert_eq!(cipher[..], exp_cipher[..]);
+
+            assert_eq!(decrypt(&cipher, aes, &IV).unwrap()[..], MSG[..]);
+        }
+    }
+
+    #[test]
+    fn check_round_trip() {
+        let aes = aes::Aes128::new(b"YELLOW SUBMARINE");
+
+        let mut msg = [0_u8; aes::BLOCK_LEN * 19];
+        for (i, byte) in msg.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+
+        for len in (0..=msg.len()).step_by(aes::BLOCK_LEN) {
+            let cipher = encrypt(&msg[..len], &aes, &IV).unwrap();
+            assert_eq!(decrypt(&cipher, &aes, &IV).unwrap(), msg[..len]);
+        }
+
+        // use an invalid length, ensure error is returned
+        assert!(encrypt(&msg[1..], &aes, &IV).is_err());
+        assert!(decrypt(&msg[1..], &aes, &IV).is_err());
+    }
+
+    #[test]
+    fn check_error_propagation() {
+        let aes = aes::Aes128::new(&KEY);
+
+        let mut pcbc_cipher = encrypt(&MSG, &aes, &IV).unwrap();
+        let mut cbc_cipher = cbc::encrypt(&MSG, &aes, &IV).unwrap();
+
+        // corrupt a single bit of the first cipher block
+        pcbc_cipher[0] ^= 0x01;
+        cbc_cipher[0] ^= 0x01;
+
+        let pcbc_plain = decrypt(&pcbc_cipher, &aes, &IV).unwrap();
+        let cbc_plain = cbc::decrypt(&cbc_cipher, &aes, &IV).unwrap();
+
+        // CBC recovers after the next block, PCBC garbles every following block
+        for (i, block) in MSG.chunks_exact(aes::BLOCK_LEN).enumerate() {
+            let range = i * aes::BLOCK_LEN..(i + 1) * aes::BLOCK_LEN;
+
+            assert_ne!(pcbc_plain[range.clone()], block[..]);
+            assert_eq!(cbc_plain[range] == block[..], i > 1);
+        }
+    }
+
+    #[test]
+    fn check_swapped_blocks() {
+        let aes = aes::Aes128::new(&KEY);
+        let mut cipher = encrypt(&MSG, &aes, &IV).unwrap();
+
+        // swapping two adjacent cipher blocks leaves the following blocks intact
+        let (first, second) = cipher[aes::BLOCK_LEN..aes::BLOCK_LEN * 3].split_at_mut(aes::BLOCK_LEN);
+        first.swap_with_slice(second);
+
+        let plain = decrypt(&cipher, &aes, &IV).unwrap();
+
+        assert_eq!(plain[..aes::BLOCK_LEN], MSG[..aes::BLOCK_LEN]);
+        assert_ne!(plain[aes::BLOCK_LEN..aes::BLOCK_LEN * 3], MSG[aes::BLOCK_LEN..aes::BLOCK_LEN * 3]);
+        assert_eq!(plain[aes::BLOCK_LEN * 3..], MSG[aes::BLOCK_LEN * 3..]);
+    }
+}
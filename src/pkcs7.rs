@@ -47,35 +47,35 @@ pub fn unpad(buf: &[u8]) -> Result<Vec<u8>, Error> {
 
     let padded_block = &buf[buf_len - aes::BLOCK_LEN..];
 
-    if padded_block == &[16_u8; 16] {
+    if padded_block == [16_u8; 16] {
         Ok(buf[..buf_len - 16].to_vec())
-    } else if &padded_block[1..] == &[15_u8; 15] {
+    } else if padded_block[1..] == [15_u8; 15] {
         Ok(buf[..buf_len - 15].to_vec())
-    } else if &padded_block[2..] == &[14_u8; 14] {
+    } else if padded_block[2..] == [14_u8; 14] {
         Ok(buf[..buf_len - 14].to_vec())
-    } else if &padded_block[3..] == &[13_u8; 13] {
+    } else if padded_block[3..] == [13_u8; 13] {
         Ok(buf[..buf_len - 13].to_vec())
-    } else if &padded_block[4..] == &[12_u8; 12] {
+    } else if padded_block[4..] == [12_u8; 12] {
         Ok(buf[..buf_len - 12].to_vec())
-    } else if &padded_block[5..] == &[11_u8; 11] {
+    } else if padded_block[5..] == [11_u8; 11] {
         Ok(buf[..buf_len - 11].to_vec())
-    } else if &padded_block[6..] == &[10_u8; 10] {
+    } else if padded_block[6..] == [10_u8; 10] {
         Ok(buf[..buf_len - 10].to_vec())
-    } else if &padded_block[7..] == &[9_u8; 9] {
+    } else if padded_block[7..] == [9_u8; 9] {
         Ok(buf[..buf_len - 9].to_vec())
-    } else if &padded_block[8..] == &[8_u8; 8] {
+    } else if padded_block[8..] == [8_u8; 8] {
         Ok(buf[..buf_len - 8].to_vec())
-    } else if &padded_block[9..] == &[7_u8; 7] {
+    } else if padded_block[9..] == [7_u8; 7] {
         Ok(buf[..buf_len - 7].to_vec())
-    } else if &padded_block[10..] == &[6_u8; 6] {
+    } else if padded_block[10..] == [6_u8; 6] {
         Ok(buf[..buf_len - 6].to_vec())
-    } else if &padded_block[11..] == &[5_u8; 5] {
+    } else if padded_block[11..] == [5_u8; 5] {
         Ok(buf[..buf_len - 5].to_vec())
-    } else if &padded_block[12..] == &[4_u8; 4] {
+    } else if padded_block[12..] == [4_u8; 4] {
         Ok(buf[..buf_len - 4].to_vec())
-    } else if &padded_block[13..] == &[3_u8; 3] {
+    } else if padded_block[13..] == [3_u8; 3] {
         Ok(buf[..buf_len - 3].to_vec())
-    } else if &padded_block[14..] == &[2_u8; 2] {
+    } else if padded_block[14..] == [2_u8; 2] {
         Ok(buf[..buf_len - 2].to_vec())
     } else if padded_block[15] == 1 {
         Ok(buf[..buf_len - 1].to_vec())
@@ -221,8 +221,8 @@ mod tests {
         for i in 0..aes::BLOCK_LEN {
             let last_bytes = aes::BLOCK_LEN - i - 1;
 
-            for j in last_bytes..aes::BLOCK_LEN {
-                block[j] = (i + 1) as u8;
+            for byte in block[last_bytes..].iter_mut() {
+                *byte = (i + 1) as u8;
             }
 
             assert_eq!(unpad(block.as_ref()).unwrap()[..], block[..last_bytes]);
@@ -0,0 +1,244 @@
+//! Synthetic Initialization Vector mode (RFC 5297, AES-SIV)
+//!
+//! Deterministic AEAD under two keys: key_1 derives the synthetic IV from the associated data
+//! and plaintext with S2V (CMAC over a vector of strings), key_2 encrypts the plaintext in CTR mode
+//! starting from that IV. Encrypting the same inputs twice gives the same ciphertext,
+//! and reusing a nonce only reveals whether two messages were identical.
+//!
+//! For nonce-based use, pass the nonce as the last associated data string.
+//! Ciphertexts are returned with the synthetic IV prepended: V || C.
+
+use alloc::vec::Vec;
+
+use crate::cmac::{self, dbl};
+use crate::ctr;
+use crate::{aes, ct_eq, xor_equals, BlockCipher, Error};
+
+/// Synthetic IV length, also serves as the authentication tag
+pub const IV_LEN: usize = aes::BLOCK_LEN;
+
+/// Maximum number of associated data strings: S2V takes at most 127 strings including the plaintext
+pub const MAX_AAD_COUNT: usize = 126;
+
+/// Encrypt and authenticate a message in SIV mode
+///
+/// @param msg: Plaintext, any length
+/// @param cipher_1: S2V (CMAC) cipher (key_1)
+/// @param cipher_2: CTR cipher (key_2)
+/// @param aad: Associated data strings, at most MAX_AAD_COUNT, a nonce goes last
+pub fn encrypt<C: BlockCipher>(
+    msg: &[u8],
+    cipher_1: &C,
+    cipher_2: &C,
+    aad: &[&[u8]],
+) -> Result<Vec<u8>, Error> {
+    if aad.len() > MAX_AAD_COUNT {
+        return Err(Error::InvalidLength);
+    }
+
+    let v = s2v(cipher_1, aad, msg);
+
+    let mut res = Vec::with_capacity(IV_LEN + msg.len());
+    res.extend_from_slice(&v);
+    res.extend_from_slice(&ctr(cipher_2, &v, msg));
+
+    Ok(res)
+}
+
+/// Verify and decrypt a message in SIV mode
+///
+/// @param ciphertext: Synthetic IV followed by the ciphertext (V || C)
+/// @param cipher_1: S2V (CMAC) cipher (key_1)
+/// @param cipher_2: CTR cipher (key_2)
+/// @param aad: Associated data strings, must match those used for encryption
+///
+/// No plaintext is returned unless the synthetic IV is valid
+pub fn decrypt<C: BlockCipher>(
+    ciphertext: &[u8],
+    cipher_1: &C,
+    cipher_2: &C,
+    aad: &[&[u8]],
+) -> Result<Vec<u8>, Error> {
+    if aad.len() > MAX_AAD_COUNT || ciphertext.len() < IV_LEN {
+        return Err(Error::InvalidLength);
+    }
+
+    let (v, ciphertext) = ciphertext.split_at(IV_LEN);
+
+    // the IV covers the plaintext, so decrypt before verifying
+    let mut iv = [0_u8; IV_LEN];
+    iv.copy_from_slice(v);
+    let res = ctr(cipher_2, &iv, ciphertext);
+
+    if !ct_eq(&s2v(cipher_1, aad, &res), v) {
+        return Err(Error::InvalidTag);
+    }
+
+    Ok(res)
+}
+
+// D = CMAC( <zero> , key )
+// D = dbl( D ) ^ CMAC( S_i , key )                     for each associated data string
+// T = S_n xorend D                                     len( S_n ) >= 128
+// T = dbl( D ) ^ pad( S_n )                            otherwise
+// V = CMAC( T , key )
+fn s2v<C: BlockCipher>(cipher: &C, aad: &[&[u8]], msg: &[u8]) -> [u8; aes::BLOCK_LEN] {
+    let mut d = cmac::mac(cipher, &[0_u8; aes::BLOCK_LEN]);
+
+    for string in aad.iter() {
+        d = dbl(&d);
+        // blocks guaranteed to be the same length, safe to just unwrap here
+        xor_equals(&mut d, &cmac::mac(cipher, string)).unwrap();
+    }
+
+    if msg.len() >= aes::BLOCK_LEN {
+        // xorend: XOR D into the last block of the plaintext
+        let mut t = msg.to_vec();
+        let end = t.len() - aes::BLOCK_LEN;
+        xor_equals(&mut t[end..], &d).unwrap();
+
+        cmac::mac(cipher, &t)
+    } else {
+        // pad( S_n ) = S_n || 1 || 0^...
+        let mut t = [0_u8; aes::BLOCK_LEN];
+        t[..msg.len()].copy_from_slice(msg);
+        t[msg.len()] = 0x80;
+        xor_equals(&mut t, &dbl(&d)).unwrap();
+
+        cmac::mac(cipher, &t)
+    }
+}
+
+// Q = V & 1^64 || 0^1 || 1^31 || 0^1 || 1^31
+//
+// Clearing bits 31 and 63 lets implementations increment only the low 32 or 64 bits,
+// the whole block is still a 128-bit big-endian counter
+fn ctr<C: BlockCipher>(cipher: &C, v: &[u8; IV_LEN], text: &[u8]) -> Vec<u8> {
+    let q = u128::from_be_bytes(*v) & !(1_u128 << 63 | 1_u128 << 31);
+
+    ctr::ctr_128(cipher, q, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 5297 Appendix A.1: deterministic authenticated encryption
+    #[test]
+    fn rfc_deterministic_vector() {
+        let key_1 = [
+            0xff, 0xfe, 0xfd, 0xfc, 0xfb, 0xfa, 0xf9, 0xf8, 0xf7, 0xf6, 0xf5, 0xf4, 0xf3, 0xf2, 0xf1, 0xf0,
+        ];
+        let key_2 = [
+            0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff,
+        ];
+        let aad = [
+            0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+            0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27,
+        ];
+        let msg = [
+            0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee,
+        ];
+        let exp_cipher = [
+            0x85, 0x63, 0x2d, 0x07, 0xc6, 0xe8, 0xf3, 0x7f, 0x95, 0x0a, 0xcd, 0x32, 0x0a, 0x2e, 0xcc, 0x93,
+            0x40, 0xc0, 0x2b, 0x96, 0x90, 0xc4, 0xdc, 0x04, 0xda, 0xef, 0x7f, 0x6a, 0xfe, 0x5c,
+        ];
+
+        let ciphers = [
+            (aes::Aes128::new_portable(&key_1), aes::Aes128::new_portable(&key_2)),
+            (aes::Aes128::new(&key_1), aes::Aes128::new(&key_2)),
+        ];
+
+        for (aes_1, aes_2) in ciphers.iter() {
+            let cipher = encrypt(&msg, aes_1, aes_2, &[&aad]).unwrap();
+            assert_eq!(cipher[..], exp_cipher[..]);
+
+            assert_eq!(decrypt(&cipher, aes_1, aes_2, &[&aad]).unwrap(), msg);
+        }
+    }
+
+    // RFC 5297 Appendix A.2: nonce-based authenticated encryption
+    #[test]
+    fn rfc_nonce_based_vector() {
+        let key_1 = [
+            0x7f, 0x7e, 0x7d, 0x7c, 0x7b, 0x7a, 0x79, 0x78, 0x77, 0x76, 0x75, 0x74, 0x73, 0x72, 0x71, 0x70,
+        ];
+        let key_2 = [
+            0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f,
+        ];
+        let aad_1 = [
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff,
+            0xde, 0xad, 0xda, 0xda, 0xde, 0xad, 0xda, 0xda, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88,
+            0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00,
+        ];
+        let aad_2 = [0x10, 0x20, 0x30, 0x40, 0x50, 0x60, 0x70, 0x80, 0x90, 0xa0];
+        let nonce = [
+            0x09, 0xf9, 0x11, 0x02, 0x9d, 0x74, 0xe3, 0x5b, 0xd8, 0x41, 0x56, 0xc5, 0x63, 0x56, 0x88, 0xc0,
+        ];
+        let msg = b"this is some plaintext to encrypt using SIV-AES";
+        let exp_cipher = [
+            0x7b, 0xdb, 0x6e, 0x3b, 0x43, 0x26, 0x67, 0xeb, 0x06, 0xf4, 0xd1, 0x4b, 0xff, 0x2f, 0xbd, 0x0f,
+            0xcb, 0x90, 0x0f, 0x2f, 0xdd, 0xbe, 0x40, 0x43, 0x26, 0x60, 0x19, 0x65, 0xc8, 0x89, 0xbf, 0x17,
+            0xdb, 0xa7, 0x7c, 0xeb, 0x09, 0x4f, 0xa6, 0x63, 0xb7, 0xa3, 0xf7, 0x48, 0xba, 0x8a, 0xf8, 0x29,
+            0xea, 0x64, 0xad, 0x54, 0x4a, 0x27, 0x2e, 0x9c, 0x48, 0x5b, 0x62, 0xa3, 0xfd, 0x5c, 0x0d,
+        ];
+
+        let aes_1 = aes::Aes128::new(&key_1);
+        let aes_2 = aes::Aes128::new(&key_2);
+        let aad: [&[u8]; 3] = [&aad_1, &aad_2, &nonce];
+
+        let cipher = encrypt(msg, &aes_1, &aes_2, &aad).unwrap();
+        assert_eq!(cipher[..], exp_cipher[..]);
+
+        assert_eq!(decrypt(&cipher, &aes_1, &aes_2, &aad).unwrap(), msg[..]);
+    }
+
+    #[test]
+    fn check_deterministic() {
+        let aes_1 = aes::Aes128::new(b"YELLOW SUBMARINE");
+        let aes_2 = aes::Aes128::new(b"SUBMARINE YELLOW");
+
+        let mut msg = [0_u8; 67];
+        for (i, byte) in msg.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+
+        for len in 0..=msg.len() {
+            let cipher = encrypt(&msg[..len], &aes_1, &aes_2, &[b"header"]).unwrap();
+            assert_eq!(cipher.len(), IV_LEN + len);
+
+            // same inputs give the same ciphertext, any other associated data does not
+            assert_eq!(encrypt(&msg[..len], &aes_1, &aes_2, &[b"header"]).unwrap(), cipher);
+            assert_ne!(encrypt(&msg[..len], &aes_1, &aes_2, &[b"header", b""]).unwrap(), cipher);
+            assert_ne!(encrypt(&msg[..len], &aes_1, &aes_2, &[]).unwrap(), cipher);
+
+            assert_eq!(decrypt(&cipher, &aes_1, &aes_2, &[b"header"]).unwrap(), msg[..len]);
+        }
+    }
+
+    #[test]
+    fn check_invalid_tag() {
+        let aes_1 = aes::Aes128::new(b"YELLOW SUBMARINE");
+        let aes_2 = aes::Aes128::new(b"SUBMARINE YELLOW");
+        let aad: [&[u8]; 2] = [b"header", b"nonce"];
+
+        let mut cipher = encrypt(b"attack at dawn", &aes_1, &aes_2, &aad).unwrap();
+
+        // tampered or reordered associated data must fail verification
+        assert!(decrypt(&cipher, &aes_1, &aes_2, &aad[..1]).is_err());
+        assert!(decrypt(&cipher, &aes_1, &aes_2, &[aad[1], aad[0]]).is_err());
+        assert!(decrypt(&cipher[..IV_LEN - 1], &aes_1, &aes_2, &aad).is_err());
+
+        // flip a bit in the synthetic IV, and in the ciphertext
+        for &pos in [0, IV_LEN, cipher.len() - 1].iter() {
+            cipher[pos] ^= 0x01;
+            assert!(decrypt(&cipher, &aes_1, &aes_2, &aad).is_err());
+            cipher[pos] ^= 0x01;
+        }
+
+        // too many associated data strings
+        let aad = [&b""[..]; MAX_AAD_COUNT + 1];
+        assert!(encrypt(b"attack at dawn", &aes_1, &aes_2, &aad).is_err());
+        assert!(encrypt(b"attack at dawn", &aes_1, &aes_2, &aad[..MAX_AAD_COUNT]).is_ok());
+    }
+}
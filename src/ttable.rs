@@ -0,0 +1,197 @@
+//! T-table AES implementation
+//!
+//! Combines SubBytes, ShiftRows and MixColumns into four 256-entry word tables per direction.
+//!
+//! Table lookups are indexed by secret state bytes, so this backend is NOT resistant
+//! to cache-timing attacks. Only enable the `ttable` feature on trusted hosts.
+
+use crate::aes::{self, BLOCK_LEN, N_BLOCK};
+
+// Te0[x] = [0x02 * S(x), S(x), S(x), 0x03 * S(x)]
+static TE0: [u32; 256] = enc_table(0);
+// Te1[x] = Te0[x] >>> 8
+static TE1: [u32; 256] = enc_table(1);
+// Te2[x] = Te0[x] >>> 16
+static TE2: [u32; 256] = enc_table(2);
+// Te3[x] = Te0[x] >>> 24
+static TE3: [u32; 256] = enc_table(3);
+
+// Td0[x] = [0x0e * InvS(x), 0x09 * InvS(x), 0x0d * InvS(x), 0x0b * InvS(x)]
+static TD0: [u32; 256] = dec_table(0);
+// Td1[x] = Td0[x] >>> 8
+static TD1: [u32; 256] = dec_table(1);
+// Td2[x] = Td0[x] >>> 16
+static TD2: [u32; 256] = dec_table(2);
+// Td3[x] = Td0[x] >>> 24
+static TD3: [u32; 256] = dec_table(3);
+
+// InvS(x) for the final decryption round
+static SBOX_INV: [u8; 256] = inv_sbox_table();
+
+// Build an encryption table from the S-box and MixColumns column [0x02, 0x01, 0x01, 0x03]
+const fn enc_table(rotation: u32) -> [u32; 256] {
+    let mut table = [0_u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let s = aes::sbox(i as u8);
+        let word = u32::from_be_bytes([aes::xmul(s, 0x02), s, s, aes::xmul(s, 0x03)]);
+        table[i] = word.rotate_right(rotation * 8);
+        i += 1;
+    }
+    table
+}
+
+// Build a decryption table from the inverse S-box and InvMixColumns column [0x0e, 0x09, 0x0d, 0x0b]
+const fn dec_table(rotation: u32) -> [u32; 256] {
+    let mut table = [0_u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let s = aes::sbox_inv(i as u8);
+        let word = u32::from_be_bytes([
+            aes::xmul(s, 0x0e),
+            aes::xmul(s, 0x09),
+            aes::xmul(s, 0x0d),
+            aes::xmul(s, 0x0b),
+        ]);
+        table[i] = word.rotate_right(rotation * 8);
+        i += 1;
+    }
+    table
+}
+
+const fn inv_sbox_table() -> [u8; 256] {
+    let mut table = [0_u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = aes::sbox_inv(i as u8);
+        i += 1;
+    }
+    table
+}
+
+// Get byte at row r (0 = most significant) of a state word as a table index
+fn byte(word: u32, r: usize) -> usize {
+    ((word >> (24 - 8 * r)) & 0xff) as usize
+}
+
+/// Cipher over a single block using T-tables
+///
+/// The expanded key must contain Nb * (Nr + 1) words
+pub fn cipher(msg: &[u8; BLOCK_LEN], w: &[u32], n_round: usize) -> [u8; BLOCK_LEN] {
+    let mut s = aes::block_to_state(msg);
+
+    aes::add_round_key(&mut s, &w[..N_BLOCK]);
+
+    for round in 1..n_round {
+        let rk = &w[round * N_BLOCK..(round + 1) * N_BLOCK];
+        let mut t = [0_u32; N_BLOCK];
+
+        // column c takes row r from column (c + r) mod 4 (ShiftRows)
+        for (c, word) in t.iter_mut().enumerate() {
+            *word = TE0[byte(s[c], 0)]
+                ^ TE1[byte(s[(c + 1) % N_BLOCK], 1)]
+                ^ TE2[byte(s[(c + 2) % N_BLOCK], 2)]
+                ^ TE3[byte(s[(c + 3) % N_BLOCK], 3)]
+                ^ rk[c];
+        }
+
+        s = t;
+    }
+
+    // final round has no MixColumns, Te0[x] byte 1 is S(x)
+    let rk = &w[n_round * N_BLOCK..(n_round + 1) * N_BLOCK];
+    let mut t = [0_u32; N_BLOCK];
+
+    for (c, word) in t.iter_mut().enumerate() {
+        *word = u32::from_be_bytes([
+            (TE0[byte(s[c], 0)] >> 16) as u8,
+            (TE0[byte(s[(c + 1) % N_BLOCK], 1)] >> 16) as u8,
+            (TE0[byte(s[(c + 2) % N_BLOCK], 2)] >> 16) as u8,
+            (TE0[byte(s[(c + 3) % N_BLOCK], 3)] >> 16) as u8,
+        ]) ^ rk[c];
+    }
+
+    aes::state_to_block(&t)
+}
+
+/// Equivalent Inverse Cipher over a single block using T-tables
+///
+/// The decryption key schedule must contain Nb * (Nr + 1) words
+pub fn inv_cipher(msg: &[u8; BLOCK_LEN], dw: &[u32], n_round: usize) -> [u8; BLOCK_LEN] {
+    let mut s = aes::block_to_state(msg);
+
+    aes::add_round_key(&mut s, &dw[n_round * N_BLOCK..(n_round + 1) * N_BLOCK]);
+
+    for round in (1..n_round).rev() {
+        let rk = &dw[round * N_BLOCK..(round + 1) * N_BLOCK];
+        let mut t = [0_u32; N_BLOCK];
+
+        // column c takes row r from column (c - r) mod 4 (InvShiftRows)
+        for (c, word) in t.iter_mut().enumerate() {
+            *word = TD0[byte(s[c], 0)]
+                ^ TD1[byte(s[(c + 3) % N_BLOCK], 1)]
+                ^ TD2[byte(s[(c + 2) % N_BLOCK], 2)]
+                ^ TD3[byte(s[(c + 1) % N_BLOCK], 3)]
+                ^ rk[c];
+        }
+
+        s = t;
+    }
+
+    // final round has no InvMixColumns
+    let mut t = [0_u32; N_BLOCK];
+
+    for (c, word) in t.iter_mut().enumerate() {
+        *word = u32::from_be_bytes([
+            SBOX_INV[byte(s[c], 0)],
+            SBOX_INV[byte(s[(c + 3) % N_BLOCK], 1)],
+            SBOX_INV[byte(s[(c + 2) % N_BLOCK], 2)],
+            SBOX_INV[byte(s[(c + 1) % N_BLOCK], 3)],
+        ]) ^ dw[c];
+    }
+
+    aes::state_to_block(&t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_tables() {
+        // FIPS-197 S(0x00) = 0x63: [0xc6, 0x63, 0x63, 0xa5]
+        assert_eq!(TE0[0x00], 0xc66363a5);
+        assert_eq!(TE1[0x00], 0xa5c66363);
+        assert_eq!(TE2[0x00], 0x63a5c663);
+        assert_eq!(TE3[0x00], 0x6363a5c6);
+
+        // FIPS-197 InvS(0x00) = 0x52: [0x51, 0xf4, 0xa7, 0x50]
+        assert_eq!(TD0[0x00], 0x51f4a750);
+        assert_eq!(TD3[0x00], 0xf4a75051);
+
+        for i in 0..=0xff_u8 {
+            assert_eq!((TE0[i as usize] >> 16) as u8, aes::sbox(i));
+            assert_eq!(SBOX_INV[i as usize], aes::sbox_inv(i));
+        }
+    }
+
+    #[test]
+    fn check_cipher_against_reference() {
+        let key: [u8; aes::KEY_LEN_256] = [
+            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d, 0x77, 0x81,
+            0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3, 0x09, 0x14, 0xdf, 0xf4,
+        ];
+        let w = aes::expand_key_256(&key);
+        let mut dw = [0_u32; aes::N_EXPW_256];
+        aes::expand_dec_key(&w, &mut dw);
+
+        for i in 0..=0xff_u8 {
+            let mut msg = [i; BLOCK_LEN];
+            msg[i as usize % BLOCK_LEN] ^= 0x5a;
+
+            let cipher_block = cipher(&msg, &w, aes::N_ROUND_256);
+            assert_eq!(cipher_block, aes::aes_inner(&msg, &w, aes::N_ROUND_256));
+            assert_eq!(inv_cipher(&cipher_block, &dw, aes::N_ROUND_256), msg);
+        }
+    }
+}
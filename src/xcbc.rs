@@ -0,0 +1,187 @@
+//! AES-XCBC-MAC (RFC 3566) and AES-XCBC-PRF-128 (RFC 4434)
+//!
+//! CBC-MAC keyed with K1, with the final block masked by K2 when complete, or padded and masked by K3.
+//! All three keys are derived from the AES-128 key.
+
+use crate::aes::{self, Aes128};
+use crate::cmac::CbcCore;
+use crate::{ct_eq, BlockCipher, Error};
+
+/// Full MAC length
+pub const MAC_LEN: usize = 16;
+
+/// AES-XCBC-MAC-96 truncated MAC length
+pub const MAC_96_LEN: usize = 12;
+
+/// Streaming AES-XCBC-MAC computation
+///
+/// The final block is buffered until `finalize`, since its masking depends on it being complete
+pub struct Xcbc {
+    cipher: Aes128,
+    k2: [u8; aes::BLOCK_LEN],
+    k3: [u8; aes::BLOCK_LEN],
+    core: CbcCore,
+}
+
+impl Xcbc {
+    /// Start an AES-XCBC-MAC computation, deriving K1, K2 and K3 from the key
+    pub fn new(key: &[u8; aes::KEY_LEN_128]) -> Self {
+        // K1 = CIPHER( 0x01010101 ... , K )
+        // K2 = CIPHER( 0x02020202 ... , K )
+        // K3 = CIPHER( 0x03030303 ... , K )
+        let base = Aes128::new(key);
+        let k1 = base.encrypt_block(&[0x01; aes::BLOCK_LEN]);
+        let k2 = base.encrypt_block(&[0x02; aes::BLOCK_LEN]);
+        let k3 = base.encrypt_block(&[0x03; aes::BLOCK_LEN]);
+
+        Self {
+            cipher: Aes128::new(&k1),
+            k2,
+            k3,
+            core: CbcCore::new(),
+        }
+    }
+
+    /// Absorb more of the message
+    pub fn update(&mut self, data: &[u8]) {
+        // E[i] = CIPHER( M[i] ^ E[i-1] , K1 )
+        self.core.update(&self.cipher, data);
+    }
+
+    /// Finish the computation, and return the full MAC
+    pub fn finalize(self) -> [u8; MAC_LEN] {
+        // E[n] = CIPHER( M[n] ^ E[n-1] ^ K2 , K1 )                if M[n] is complete
+        // E[n] = CIPHER( ( M[n] || 10^i ) ^ E[n-1] ^ K3 , K1 )    otherwise
+        self.core.finalize(&self.cipher, &self.k2, &self.k3)
+    }
+}
+
+/// Compute the AES-XCBC-MAC of a message
+pub fn mac(key: &[u8; aes::KEY_LEN_128], msg: &[u8]) -> [u8; MAC_LEN] {
+    let mut xcbc = Xcbc::new(key);
+    xcbc.update(msg);
+    xcbc.finalize()
+}
+
+/// Compute the AES-XCBC-MAC-96 of a message (RFC 3566)
+pub fn mac_96(key: &[u8; aes::KEY_LEN_128], msg: &[u8]) -> [u8; MAC_96_LEN] {
+    let mut res = [0_u8; MAC_96_LEN];
+    res.copy_from_slice(&mac(key, msg)[..MAC_96_LEN]);
+    res
+}
+
+/// Verify an AES-XCBC-MAC-96 of a message in constant time
+pub fn verify_96(key: &[u8; aes::KEY_LEN_128], msg: &[u8], tag: &[u8]) -> Result<(), Error> {
+    if tag.len() != MAC_96_LEN {
+        return Err(Error::InvalidLength);
+    }
+
+    if ct_eq(&mac_96(key, msg), tag) {
+        Ok(())
+    } else {
+        Err(Error::InvalidTag)
+    }
+}
+
+/// AES-XCBC-PRF-128 (RFC 4434)
+///
+/// Accepts keys of any length:
+///
+/// - shorter than 16 bytes: padded with zeros
+/// - longer than 16 bytes: replaced with AES-XCBC-PRF-128( 0^128 , key )
+///
+/// @param key: Variable length key
+/// @param msg: PRF input
+pub fn prf_128(key: &[u8], msg: &[u8]) -> [u8; MAC_LEN] {
+    let mut base_key = [0_u8; aes::KEY_LEN_128];
+
+    if key.len() <= aes::KEY_LEN_128 {
+        base_key[..key.len()].copy_from_slice(key);
+    } else {
+        base_key = mac(&[0_u8; aes::KEY_LEN_128], key);
+    }
+
+    mac(&base_key, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    const KEY: [u8; aes::KEY_LEN_128] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    ];
+
+    fn msg(len: usize) -> Vec<u8> {
+        (0..len as u8).collect()
+    }
+
+    // RFC 3566 Section 4.6: Test Cases #1-#6
+    #[test]
+    fn rfc_test_vectors() {
+        let exp_macs = [
+            (0, [0x75, 0xf0, 0x25, 0x1d, 0x52, 0x8a, 0xc0, 0x1c, 0x45, 0x73, 0xdf, 0xd5, 0x84, 0xd7, 0x9f, 0x29]),
+            (3, [0x5b, 0x37, 0x65, 0x80, 0xae, 0x2f, 0x19, 0xaf, 0xe7, 0x21, 0x9c, 0xee, 0xf1, 0x72, 0x75, 0x6f]),
+            (16, [0xd2, 0xa2, 0x46, 0xfa, 0x34, 0x9b, 0x68, 0xa7, 0x99, 0x98, 0xa4, 0x39, 0x4f, 0xf7, 0xa2, 0x63]),
+            (20, [0x47, 0xf5, 0x1b, 0x45, 0x64, 0x96, 0x62, 0x15, 0xb8, 0x98, 0x5c, 0x63, 0x05, 0x5e, 0xd3, 0x08]),
+            (32, [0xf5, 0x4f, 0x0e, 0xc8, 0xd2, 0xb9, 0xf3, 0xd3, 0x68, 0x07, 0x73, 0x4b, 0xd5, 0x28, 0x3f, 0xd4]),
+            (34, [0xbe, 0xcb, 0xb3, 0xbc, 0xcd, 0xb5, 0x18, 0xa3, 0x06, 0x77, 0xd5, 0x48, 0x1f, 0xb6, 0xb4, 0xd8]),
+        ];
+
+        for (len, exp_mac) in exp_macs.iter() {
+            assert_eq!(mac(&KEY, &msg(*len)), *exp_mac);
+            assert_eq!(mac_96(&KEY, &msg(*len)), exp_mac[..MAC_96_LEN]);
+            assert!(verify_96(&KEY, &msg(*len), &exp_mac[..MAC_96_LEN]).is_ok());
+        }
+    }
+
+    // RFC 3566 Section 4.6: Test Case #7
+    #[test]
+    fn rfc_test_vector_long() {
+        let exp_mac = [0xf0, 0xda, 0xfe, 0xe8, 0x95, 0xdb, 0x30, 0x25, 0x37, 0x61, 0x10, 0x3b, 0x5d, 0x84, 0x52, 0x8f];
+
+        assert_eq!(mac(&KEY, &[0_u8; 1000]), exp_mac);
+    }
+
+    // RFC 4434 Section 6: Test Vectors
+    #[test]
+    fn rfc_prf_test_vectors() {
+        let exp_prf_16 = [0x47, 0xf5, 0x1b, 0x45, 0x64, 0x96, 0x62, 0x15, 0xb8, 0x98, 0x5c, 0x63, 0x05, 0x5e, 0xd3, 0x08];
+        let exp_prf_10 = [0x0f, 0xa0, 0x87, 0xaf, 0x7d, 0x86, 0x6e, 0x76, 0x53, 0x43, 0x4e, 0x60, 0x2f, 0xdd, 0xe8, 0x35];
+        let exp_prf_18 = [0x8c, 0xd3, 0xc9, 0x3a, 0xe5, 0x98, 0xa9, 0x80, 0x30, 0x06, 0xff, 0xb6, 0x7c, 0x40, 0xe9, 0xe4];
+
+        let mut long_key = KEY.to_vec();
+        long_key.extend_from_slice(&[0xed, 0xcb]);
+
+        assert_eq!(prf_128(&KEY, &msg(20)), exp_prf_16);
+        assert_eq!(prf_128(&KEY[..10], &msg(20)), exp_prf_10);
+        assert_eq!(prf_128(&long_key, &msg(20)), exp_prf_18);
+    }
+
+    #[test]
+    fn check_streaming() {
+        let data = msg(50);
+        let exp_mac = mac(&KEY, &data);
+
+        // split at every offset, including block boundaries and empty updates
+        for split in 0..=data.len() {
+            let mut xcbc = Xcbc::new(&KEY);
+            xcbc.update(&data[..split]);
+            xcbc.update(&[]);
+            xcbc.update(&data[split..]);
+            assert_eq!(xcbc.finalize(), exp_mac);
+        }
+    }
+
+    #[test]
+    fn check_invalid_verify() {
+        let mut tag = mac_96(&KEY, &msg(20));
+
+        assert!(verify_96(&KEY, &msg(19), &tag).is_err());
+        assert!(verify_96(&KEY, &msg(20), &tag[..MAC_96_LEN - 1]).is_err());
+
+        tag[0] ^= 0x01;
+        assert!(verify_96(&KEY, &msg(20), &tag).is_err());
+    }
+}
@@ -0,0 +1,397 @@
+//! XTS-AES tweakable block cipher mode (IEEE 1619-2007, NIST SP 800-38E)
+//!
+//! Each data unit (sector) is encrypted under two keys: key_1 encrypts the data,
+//! key_2 encrypts the sector number into the initial tweak.
+//! The tweak is multiplied by alpha in GF(2^128) for each following block.
+//!
+//! Sectors that are not a multiple of the block length use ciphertext stealing,
+//! so the ciphertext is always the same length as the plaintext.
+
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+use crate::{aes, to_blocks, xor_equals, BlockCipher, Error};
+
+/// Minimum data unit (sector) length
+pub const MIN_SECTOR_LEN: usize = aes::BLOCK_LEN;
+
+/// Maximum data unit (sector) length: 2^20 blocks
+pub const MAX_SECTOR_LEN: usize = aes::BLOCK_LEN << 20;
+
+// Reduction polynomial x^128 + x^7 + x^2 + x + 1
+const R_128: u8 = 0x87;
+
+/// Encrypt a single sector in XTS mode
+///
+/// @param sector: Sector plaintext, MIN_SECTOR_LEN to MAX_SECTOR_LEN bytes
+/// @param cipher_1: Data cipher (key_1)
+/// @param cipher_2: Tweak cipher (key_2)
+/// @param sector_num: Data unit sequence number, encoded little-endian into the tweak
+pub fn encrypt_sector<C: BlockCipher>(
+    sector: &[u8],
+    cipher_1: &C,
+    cipher_2: &C,
+    sector_num: u128,
+) -> Result<Vec<u8>, Error> {
+    xts(sector, cipher_1, cipher_2, sector_num, false)
+}
+
+/// Decrypt a single sector in XTS mode
+///
+/// @param sector: Sector ciphertext, MIN_SECTOR_LEN to MAX_SECTOR_LEN bytes
+/// @param cipher_1: Data cipher (key_1)
+/// @param cipher_2: Tweak cipher (key_2)
+/// @param sector_num: Data unit sequence number, encoded little-endian into the tweak
+pub fn decrypt_sector<C: BlockCipher>(
+    sector: &[u8],
+    cipher_1: &C,
+    cipher_2: &C,
+    sector_num: u128,
+) -> Result<Vec<u8>, Error> {
+    xts(sector, cipher_1, cipher_2, sector_num, true)
+}
+
+/// Encrypt consecutive sectors in XTS mode
+///
+/// Data must be a multiple of the sector size, sectors are numbered from `first_sector`
+pub fn encrypt<C: BlockCipher>(
+    data: &[u8],
+    cipher_1: &C,
+    cipher_2: &C,
+    first_sector: u128,
+    sector_size: usize,
+) -> Result<Vec<u8>, Error> {
+    sectors(data, cipher_1, cipher_2, first_sector, sector_size, false)
+}
+
+/// Decrypt consecutive sectors in XTS mode
+///
+/// Data must be a multiple of the sector size, sectors are numbered from `first_sector`
+pub fn decrypt<C: BlockCipher>(
+    data: &[u8],
+    cipher_1: &C,
+    cipher_2: &C,
+    first_sector: u128,
+    sector_size: usize,
+) -> Result<Vec<u8>, Error> {
+    sectors(data, cipher_1, cipher_2, first_sector, sector_size, true)
+}
+
+fn sectors<C: BlockCipher>(
+    data: &[u8],
+    cipher_1: &C,
+    cipher_2: &C,
+    first_sector: u128,
+    sector_size: usize,
+    decrypt: bool,
+) -> Result<Vec<u8>, Error> {
+    if !(MIN_SECTOR_LEN..=MAX_SECTOR_LEN).contains(&sector_size) || data.len() % sector_size != 0 {
+        return Err(Error::InvalidLength);
+    }
+
+    let mut res = Vec::with_capacity(data.len());
+
+    for (i, sector) in data.chunks_exact(sector_size).enumerate() {
+        res.extend_from_slice(&xts(sector, cipher_1, cipher_2, first_sector + i as u128, decrypt)?);
+    }
+
+    Ok(res)
+}
+
+fn xts<C: BlockCipher>(
+    text: &[u8],
+    cipher_1: &C,
+    cipher_2: &C,
+    sector_num: u128,
+    decrypt: bool,
+) -> Result<Vec<u8>, Error> {
+    let len = text.len();
+    if !(MIN_SECTOR_LEN..=MAX_SECTOR_LEN).contains(&len) {
+        return Err(Error::InvalidLength);
+    }
+
+    let full = len / aes::BLOCK_LEN;
+    let rem = len % aes::BLOCK_LEN;
+
+    // T_0 = CIPHER( i , key_2 )
+    // T_j = T_j-1 ⊗ α
+    let mut tweak = cipher_2.encrypt_block(&sector_num.to_le_bytes());
+    let mut tweaks = Vec::with_capacity(full + 1);
+    for _ in 0..full + (rem != 0) as usize {
+        tweaks.push(tweak);
+        tweak = mul_alpha(&tweak);
+    }
+
+    // all blocks before the stolen one are independent
+    let n_blocks = if rem == 0 { full } else { full - 1 };
+    let mut blocks = to_blocks(&text[..n_blocks * aes::BLOCK_LEN]);
+    xts_blocks(&mut blocks, &tweaks[..n_blocks], cipher_1, decrypt);
+
+    let mut res = blocks.concat();
+
+    if rem != 0 {
+        // encrypt:
+        //   CC = XTS( P_m-1 , T_m-1 )
+        //   C_m-1 = XTS( P_m || CC[rem..] , T_m ),   C_m = CC[..rem]
+        //
+        // decrypt uses the tweaks in the opposite order:
+        //   PP = XTS-INV( C_m-1 , T_m )
+        //   P_m-1 = XTS-INV( C_m || PP[rem..] , T_m-1 ),   P_m = PP[..rem]
+        let (first_tweak, second_tweak) = if decrypt {
+            (tweaks[full], tweaks[full - 1])
+        } else {
+            (tweaks[full - 1], tweaks[full])
+        };
+
+        let (last_full, tail) = text[n_blocks * aes::BLOCK_LEN..].split_at(aes::BLOCK_LEN);
+
+        // split is exactly one block, safe to just unwrap here
+        let mut stolen = [last_full.try_into().unwrap()];
+        xts_blocks(&mut stolen, &[first_tweak], cipher_1, decrypt);
+
+        let mut last = stolen;
+        last[0][..rem].copy_from_slice(tail);
+        xts_blocks(&mut last, &[second_tweak], cipher_1, decrypt);
+
+        res.extend_from_slice(&last[0]);
+        res.extend_from_slice(&stolen[0][..rem]);
+    }
+
+    Ok(res)
+}
+
+// C_j = CIPHER( P_j ^ T_j , key_1 ) ^ T_j
+fn xts_blocks<C: BlockCipher>(
+    blocks: &mut [[u8; aes::BLOCK_LEN]],
+    tweaks: &[[u8; aes::BLOCK_LEN]],
+    cipher: &C,
+    decrypt: bool,
+) {
+    // blocks and tweaks guaranteed to be the same length, safe to just unwrap here
+    for (block, tweak) in blocks.iter_mut().zip(tweaks.iter()) {
+        xor_equals(block, tweak).unwrap();
+    }
+
+    if decrypt {
+        cipher.decrypt_blocks(blocks);
+    } else {
+        cipher.encrypt_blocks(blocks);
+    }
+
+    for (block, tweak) in blocks.iter_mut().zip(tweaks.iter()) {
+        xor_equals(block, tweak).unwrap();
+    }
+}
+
+// Multiply by alpha (x) in GF(2^128), with the tweak as a little-endian polynomial:
+// tweak << 1, reduced by R_128 if the MSB of the last byte was set
+//
+// Uses a mask instead of a branch, so runtime does not depend on the (secret) tweak
+fn mul_alpha(tweak: &[u8; aes::BLOCK_LEN]) -> [u8; aes::BLOCK_LEN] {
+    let mut out = [0_u8; aes::BLOCK_LEN];
+
+    for i in (1..aes::BLOCK_LEN).rev() {
+        out[i] = (tweak[i] << 1) | (tweak[i - 1] >> 7);
+    }
+
+    let msb_mask = 0_u8.wrapping_sub(tweak[aes::BLOCK_LEN - 1] >> 7);
+    out[0] = (tweak[0] << 1) ^ (R_128 & msb_mask);
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // IEEE 1619-2007 Annex B: Vector 1
+    #[test]
+    fn ieee_vector_1() {
+        let key = [0_u8; aes::KEY_LEN_128];
+        let exp_cipher = [
+            0x91, 0x7c, 0xf6, 0x9e, 0xbd, 0x68, 0xb2, 0xec, 0x9b, 0x9f, 0xe9, 0xa3, 0xea, 0xdd, 0xa6, 0x92,
+            0xcd, 0x43, 0xd2, 0xf5, 0x95, 0x98, 0xed, 0x85, 0x8c, 0x02, 0xc2, 0x65, 0x2f, 0xbf, 0x92, 0x2e,
+        ];
+
+        for aes in [aes::Aes128::new_portable(&key), aes::Aes128::new(&key)].iter() {
+            let cipher = encrypt_sector(&[0_u8; 32], aes, aes, 0).unwrap();
+            assert_eq!(cipher, exp_cipher);
+
+            assert_eq!(decrypt_sector(&cipher, aes, aes, 0).unwrap(), [0_u8; 32]);
+        }
+    }
+
+    // IEEE 1619-2007 Annex B: Vector 3
+    #[test]
+    fn ieee_vector_3() {
+        let key_1 = [
+            0xff, 0xfe, 0xfd, 0xfc, 0xfb, 0xfa, 0xf9, 0xf8, 0xf7, 0xf6, 0xf5, 0xf4, 0xf3, 0xf2, 0xf1, 0xf0,
+        ];
+        let key_2 = [0x22_u8; aes::KEY_LEN_128];
+        let msg = [0x44_u8; 32];
+        let exp_cipher = [
+            0xaf, 0x85, 0x33, 0x6b, 0x59, 0x7a, 0xfc, 0x1a, 0x90, 0x0b, 0x2e, 0xb2, 0x1e, 0xc9, 0x49, 0xd2,
+            0x92, 0xdf, 0x4c, 0x04, 0x7e, 0x0b, 0x21, 0x53, 0x21, 0x86, 0xa5, 0x97, 0x1a, 0x22, 0x7a, 0x89,
+        ];
+
+        let aes_1 = aes::Aes128::new(&key_1);
+        let aes_2 = aes::Aes128::new(&key_2);
+
+        let cipher = encrypt_sector(&msg, &aes_1, &aes_2, 0x3333333333).unwrap();
+        assert_eq!(cipher, exp_cipher);
+
+        assert_eq!(decrypt_sector(&cipher, &aes_1, &aes_2, 0x3333333333).unwrap(), msg);
+    }
+
+    // IEEE 1619-2007 Annex B: Vector 10 (AES-256, 512 byte sector)
+    #[test]
+    fn ieee_vector_10() {
+        let key_1 = [
+            0x27, 0x18, 0x28, 0x18, 0x28, 0x45, 0x90, 0x45, 0x23, 0x53, 0x60, 0x28, 0x74, 0x71, 0x35, 0x26,
+            0x62, 0x49, 0x77, 0x57, 0x24, 0x70, 0x93, 0x69, 0x99, 0x59, 0x57, 0x49, 0x66, 0x96, 0x76, 0x27,
+        ];
+        let key_2 = [
+            0x31, 0x41, 0x59, 0x26, 0x53, 0x58, 0x97, 0x93, 0x23, 0x84, 0x62, 0x64, 0x33, 0x83, 0x27, 0x95,
+            0x02, 0x88, 0x41, 0x97, 0x16, 0x93, 0x99, 0x37, 0x51, 0x05, 0x82, 0x09, 0x74, 0x94, 0x45, 0x92,
+        ];
+        let msg: Vec<u8> = (0..512).map(|i| i as u8).collect();
+        let exp_cipher = [
+            0x1c, 0x3b, 0x3a, 0x10, 0x2f, 0x77, 0x03, 0x86, 0xe4, 0x83, 0x6c, 0x99, 0xe3, 0x70, 0xcf, 0x9b,
+            0xea, 0x00, 0x80, 0x3f, 0x5e, 0x48, 0x23, 0x57, 0xa4, 0xae, 0x12, 0xd4, 0x14, 0xa3, 0xe6, 0x3b,
+            0x5d, 0x31, 0xe2, 0x76, 0xf8, 0xfe, 0x4a, 0x8d, 0x66, 0xb3, 0x17, 0xf9, 0xac, 0x68, 0x3f, 0x44,
+            0x68, 0x0a, 0x86, 0xac, 0x35, 0xad, 0xfc, 0x33, 0x45, 0xbe, 0xfe, 0xcb, 0x4b, 0xb1, 0x88, 0xfd,
+            0x57, 0x76, 0x92, 0x6c, 0x49, 0xa3, 0x09, 0x5e, 0xb1, 0x08, 0xfd, 0x10, 0x98, 0xba, 0xec, 0x70,
+            0xaa, 0xa6, 0x69, 0x99, 0xa7, 0x2a, 0x82, 0xf2, 0x7d, 0x84, 0x8b, 0x21, 0xd4, 0xa7, 0x41, 0xb0,
+            0xc5, 0xcd, 0x4d, 0x5f, 0xff, 0x9d, 0xac, 0x89, 0xae, 0xba, 0x12, 0x29, 0x61, 0xd0, 0x3a, 0x75,
+            0x71, 0x23, 0xe9, 0x87, 0x0f, 0x8a, 0xcf, 0x10, 0x00, 0x02, 0x08, 0x87, 0x89, 0x14, 0x29, 0xca,
+            0x2a, 0x3e, 0x7a, 0x7d, 0x7d, 0xf7, 0xb1, 0x03, 0x55, 0x16, 0x5c, 0x8b, 0x9a, 0x6d, 0x0a, 0x7d,
+            0xe8, 0xb0, 0x62, 0xc4, 0x50, 0x0d, 0xc4, 0xcd, 0x12, 0x0c, 0x0f, 0x74, 0x18, 0xda, 0xe3, 0xd0,
+            0xb5, 0x78, 0x1c, 0x34, 0x80, 0x3f, 0xa7, 0x54, 0x21, 0xc7, 0x90, 0xdf, 0xe1, 0xde, 0x18, 0x34,
+            0xf2, 0x80, 0xd7, 0x66, 0x7b, 0x32, 0x7f, 0x6c, 0x8c, 0xd7, 0x55, 0x7e, 0x12, 0xac, 0x3a, 0x0f,
+            0x93, 0xec, 0x05, 0xc5, 0x2e, 0x04, 0x93, 0xef, 0x31, 0xa1, 0x2d, 0x3d, 0x92, 0x60, 0xf7, 0x9a,
+            0x28, 0x9d, 0x6a, 0x37, 0x9b, 0xc7, 0x0c, 0x50, 0x84, 0x14, 0x73, 0xd1, 0xa8, 0xcc, 0x81, 0xec,
+            0x58, 0x3e, 0x96, 0x45, 0xe0, 0x7b, 0x8d, 0x96, 0x70, 0x65, 0x5b, 0xa5, 0xbb, 0xcf, 0xec, 0xc6,
+            0xdc, 0x39, 0x66, 0x38, 0x0a, 0xd8, 0xfe, 0xcb, 0x17, 0xb6, 0xba, 0x02, 0x46, 0x9a, 0x02, 0x0a,
+            0x84, 0xe1, 0x8e, 0x8f, 0x84, 0x25, 0x20, 0x70, 0xc1, 0x3e, 0x9f, 0x1f, 0x28, 0x9b, 0xe5, 0x4f,
+            0xbc, 0x48, 0x14, 0x57, 0x77, 0x8f, 0x61, 0x60, 0x15, 0xe1, 0x32, 0x7a, 0x02, 0xb1, 0x40, 0xf1,
+            0x50, 0x5e, 0xb3, 0x09, 0x32, 0x6d, 0x68, 0x37, 0x8f, 0x83, 0x74, 0x59, 0x5c, 0x84, 0x9d, 0x84,
+            0xf4, 0xc3, 0x33, 0xec, 0x44, 0x23, 0x88, 0x51, 0x43, 0xcb, 0x47, 0xbd, 0x71, 0xc5, 0xed, 0xae,
+            0x9b, 0xe6, 0x9a, 0x2f, 0xfe, 0xce, 0xb1, 0xbe, 0xc9, 0xde, 0x24, 0x4f, 0xbe, 0x15, 0x99, 0x2b,
+            0x11, 0xb7, 0x7c, 0x04, 0x0f, 0x12, 0xbd, 0x8f, 0x6a, 0x97, 0x5a, 0x44, 0xa0, 0xf9, 0x0c, 0x29,
+            0xa9, 0xab, 0xc3, 0xd4, 0xd8, 0x93, 0x92, 0x72, 0x84, 0xc5, 0x87, 0x54, 0xcc, 0xe2, 0x94, 0x52,
+            0x9f, 0x86, 0x14, 0xdc, 0xd2, 0xab, 0xa9, 0x91, 0x92, 0x5f, 0xed, 0xc4, 0xae, 0x74, 0xff, 0xac,
+            0x6e, 0x33, 0x3b, 0x93, 0xeb, 0x4a, 0xff, 0x04, 0x79, 0xda, 0x9a, 0x41, 0x0e, 0x44, 0x50, 0xe0,
+            0xdd, 0x7a, 0xe4, 0xc6, 0xe2, 0x91, 0x09, 0x00, 0x57, 0x5d, 0xa4, 0x01, 0xfc, 0x07, 0x05, 0x9f,
+            0x64, 0x5e, 0x8b, 0x7e, 0x9b, 0xfd, 0xef, 0x33, 0x94, 0x30, 0x54, 0xff, 0x84, 0x01, 0x14, 0x93,
+            0xc2, 0x7b, 0x34, 0x29, 0xea, 0xed, 0xb4, 0xed, 0x53, 0x76, 0x44, 0x1a, 0x77, 0xed, 0x43, 0x85,
+            0x1a, 0xd7, 0x7f, 0x16, 0xf5, 0x41, 0xdf, 0xd2, 0x69, 0xd5, 0x0d, 0x6a, 0x5f, 0x14, 0xfb, 0x0a,
+            0xab, 0x1c, 0xbb, 0x4c, 0x15, 0x50, 0xbe, 0x97, 0xf7, 0xab, 0x40, 0x66, 0x19, 0x3c, 0x4c, 0xaa,
+            0x77, 0x3d, 0xad, 0x38, 0x01, 0x4b, 0xd2, 0x09, 0x2f, 0xa7, 0x55, 0xc8, 0x24, 0xbb, 0x5e, 0x54,
+            0xc4, 0xf3, 0x6f, 0xfd, 0xa9, 0xfc, 0xea, 0x70, 0xb9, 0xc6, 0xe6, 0x93, 0xe1, 0x48, 0xc1, 0x51,
+        ];
+
+        for (aes_1, aes_2) in [
+            (aes::Aes256::new_portable(&key_1), aes::Aes256::new_portable(&key_2)),
+            (aes::Aes256::new(&key_1), aes::Aes256::new(&key_2)),
+        ]
+        .iter()
+        {
+            let cipher = encrypt_sector(&msg, aes_1, aes_2, 0xff).unwrap();
+            assert_eq!(cipher[..], exp_cipher[..]);
+
+            assert_eq!(decrypt_sector(&cipher, aes_1, aes_2, 0xff).unwrap(), msg);
+        }
+    }
+
+    // IEEE 1619-2007 Annex B: Vectors 15-18 (ciphertext stealing)
+    #[test]
+    fn ieee_ciphertext_stealing_vectors() {
+        let key_1 = [
+            0xff, 0xfe, 0xfd, 0xfc, 0xfb, 0xfa, 0xf9, 0xf8, 0xf7, 0xf6, 0xf5, 0xf4, 0xf3, 0xf2, 0xf1, 0xf0,
+        ];
+        let key_2 = [
+            0xbf, 0xbe, 0xbd, 0xbc, 0xbb, 0xba, 0xb9, 0xb8, 0xb7, 0xb6, 0xb5, 0xb4, 0xb3, 0xb2, 0xb1, 0xb0,
+        ];
+        // data unit sequence number bytes: 9a 78 56 34 12
+        let sector_num = 0x123456789a;
+
+        let exp_ciphers: [&[u8]; 4] = [
+            &[
+                0x6c, 0x16, 0x25, 0xdb, 0x46, 0x71, 0x52, 0x2d, 0x3d, 0x75, 0x99, 0x60, 0x1d, 0xe7, 0xca, 0x09,
+                0xed,
+            ],
+            &[
+                0xd0, 0x69, 0x44, 0x4b, 0x7a, 0x7e, 0x0c, 0xab, 0x09, 0xe2, 0x44, 0x47, 0xd2, 0x4d, 0xeb, 0x1f,
+                0xed, 0xbf,
+            ],
+            &[
+                0xe5, 0xdf, 0x13, 0x51, 0xc0, 0x54, 0x4b, 0xa1, 0x35, 0x0b, 0x33, 0x63, 0xcd, 0x8e, 0xf4, 0xbe,
+                0xed, 0xbf, 0x9d,
+            ],
+            &[
+                0x9d, 0x84, 0xc8, 0x13, 0xf7, 0x19, 0xaa, 0x2c, 0x7b, 0xe3, 0xf6, 0x61, 0x71, 0xc7, 0xc5, 0xc2,
+                0xed, 0xbf, 0x9d, 0xac,
+            ],
+        ];
+
+        let aes_1 = aes::Aes128::new(&key_1);
+        let aes_2 = aes::Aes128::new(&key_2);
+
+        for exp_cipher in exp_ciphers.iter() {
+            let msg: Vec<u8> = (0..exp_cipher.len() as u8).collect();
+
+            let cipher = encrypt_sector(&msg, &aes_1, &aes_2, sector_num).unwrap();
+            assert_eq!(cipher[..], exp_cipher[..]);
+
+            assert_eq!(decrypt_sector(&cipher, &aes_1, &aes_2, sector_num).unwrap(), msg);
+        }
+    }
+
+    #[test]
+    fn check_sectors() {
+        let aes_1 = aes::Aes128::new(b"YELLOW SUBMARINE");
+        let aes_2 = aes::Aes128::new(b"PURPLE SUBMARINE");
+
+        // CTS sector size, several sectors
+        let sector_size = 100;
+        let data: Vec<u8> = (0..sector_size * 5).map(|i| i as u8).collect();
+
+        let cipher = encrypt(&data, &aes_1, &aes_2, 7, sector_size).unwrap();
+
+        for (i, (sector, cipher_sector)) in data.chunks(sector_size).zip(cipher.chunks(sector_size)).enumerate() {
+            assert_eq!(encrypt_sector(sector, &aes_1, &aes_2, 7 + i as u128).unwrap(), cipher_sector);
+        }
+
+        assert_eq!(decrypt(&cipher, &aes_1, &aes_2, 7, sector_size).unwrap(), data);
+    }
+
+    #[test]
+    fn check_invalid_lengths() {
+        let aes = aes::Aes128::new(b"YELLOW SUBMARINE");
+
+        assert!(encrypt_sector(&[0_u8; MIN_SECTOR_LEN - 1], &aes, &aes, 0).is_err());
+        assert!(decrypt_sector(&[], &aes, &aes, 0).is_err());
+
+        // data not a multiple of the sector size, or sector size too short
+        assert!(encrypt(&[0_u8; 100], &aes, &aes, 0, 64).is_err());
+        assert!(decrypt(&[0_u8; 64], &aes, &aes, 0, MIN_SECTOR_LEN - 1).is_err());
+    }
+
+    #[test]
+    fn check_mul_alpha() {
+        let mut tweak = [0_u8; aes::BLOCK_LEN];
+        tweak[0] = 0x01;
+        assert_eq!(mul_alpha(&tweak)[0], 0x02);
+
+        // carry between bytes, little-endian
+        tweak[0] = 0x80;
+        assert_eq!(mul_alpha(&tweak)[..2], [0x00, 0x01]);
+
+        // reduction when x^127 is set
+        let mut tweak = [0_u8; aes::BLOCK_LEN];
+        tweak[aes::BLOCK_LEN - 1] = 0x80;
+        let mut exp = [0_u8; aes::BLOCK_LEN];
+        exp[0] = R_128;
+        assert_eq!(mul_alpha(&tweak), exp);
+    }
+}